@@ -1,4 +1,4 @@
-use hax_types::cli_options::{Command, Options, ENV_VAR_OPTIONS_FRONTEND};
+use hax_types::cli_options::ENV_VAR_OPTIONS_FRONTEND;
 
 use rustc_driver::{Callbacks, Compilation};
 use rustc_interface::{interface, Queries};
@@ -8,16 +8,18 @@ use rustc_span::symbol::Symbol;
 /// configuration in the `config` phase of rustc
 pub struct CallbacksWrapper<'a> {
     pub sub: &'a mut (dyn Callbacks + Send + 'a),
-    pub options: Options,
+    /// The options of hax relevant to the crate, as JSON: `cargo`
+    /// rebuilds the crate whenever they change.
+    pub cache_key: String,
 }
 impl<'a> Callbacks for CallbacksWrapper<'a> {
     fn config(&mut self, config: &mut interface::Config) {
-        let options = self.options.clone();
+        let cache_key = self.cache_key.clone();
         config.psess_created = Some(Box::new(move |parse_sess| {
             let depinfo = parse_sess.env_depinfo.get_mut();
             depinfo.insert((
                 Symbol::intern(ENV_VAR_OPTIONS_FRONTEND),
-                Some(Symbol::intern(&serde_json::to_string(&options).unwrap())),
+                Some(Symbol::intern(&cache_key)),
             ));
             depinfo.insert((
                 Symbol::intern("HAX_CARGO_CACHE_KEY"),
@@ -10,9 +10,12 @@
 #![feature(internal_output_capture)]
 
 extern crate rustc_ast;
+extern crate rustc_attr;
+extern crate rustc_builtin_macros;
 extern crate rustc_data_structures;
 extern crate rustc_driver;
 extern crate rustc_errors;
+extern crate rustc_expand;
 extern crate rustc_feature;
 extern crate rustc_hir;
 extern crate rustc_hir_analysis;
@@ -20,6 +23,7 @@ extern crate rustc_index;
 extern crate rustc_interface;
 extern crate rustc_middle;
 extern crate rustc_mir_build;
+extern crate rustc_parse;
 extern crate rustc_session;
 extern crate rustc_span;
 extern crate rustc_target;
@@ -94,18 +98,6 @@ fn main() {
         rustc_args.extend(vec!["--sysroot".into(), rustc_sysroot()])
     };
 
-    // When `HAX_FEATURES_DETECTION_MODE` is set, we just detect
-    // features for the current crate, output them in JSON on stderr
-    // and exit immediately
-    if std::env::var("HAX_FEATURES_DETECTION_MODE").is_ok() {
-        use std::io::BufWriter;
-        return serde_json::to_writer(
-            BufWriter::new(std::io::stderr()),
-            &Features::detect(&options, &rustc_args),
-        )
-        .unwrap();
-    }
-
     let (vanilla_rustc, vanilla_rustc_never) = {
         let vanilla_rustc = std::env::var(HAX_VANILLA_RUSTC);
         let vanilla_rustc_never = vanilla_rustc == Ok("never".into());
@@ -119,31 +111,53 @@ fn main() {
     // coerce options
     let is_primary_package = std::env::var("CARGO_PRIMARY_PACKAGE").is_ok();
     let is_build_script = std::env::var("CARGO_CRATE_NAME") == Ok("build_script_build".to_string()); // FIXME: is there a more robust way to do this?
+
+    // `cargo` queries rustc for information about the target (e.g.
+    // `--print=cfg`) without compiling any crate: `CARGO_CRATE_NAME`
+    // is unset then
+    let is_crate = std::env::var("CARGO_CRATE_NAME").is_ok();
     let translate_package =
-        !vanilla_rustc && !is_build_script && (options.deps || is_primary_package);
+        !vanilla_rustc && is_crate && !is_build_script && (options.deps || is_primary_package);
     let mut callbacks: Box<dyn Callbacks + Send> = if translate_package {
         Box::new(exporter::ExtractionCallbacks {
             inline_macro_calls: options.inline_macro_calls.clone(),
+            evaluate_const_fns: options.evaluate_const_fns,
             body_types: options.command.body_kinds(),
-            macro_calls: std::collections::HashMap::new(),
+            uses_quote_macros: false,
+            // We want to enable certain features, but only if the
+            // crate itself doesn't enable those: this is decided
+            // right after parsing
+            features: Features {
+                adt_const_params: false,    // not useful for now
+                generic_const_exprs: false, // not useful for now
+                register_tool: true,
+                registered_tools: HashSet::from_iter(vec![hax_lib_macros_types::HAX_TOOL.into()]),
+                auto_traits: true,
+                negative_impls: true,
+                custom_inner_attributes: true,
+                proc_macro_hygiene: true,
+            },
         })
     } else {
-        struct CallbacksNoop;
-        impl Callbacks for CallbacksNoop {}
         Box::new(CallbacksNoop)
     };
 
+    // The arguments of rustc, without the cfgs of hax. The lints are
+    // already reported by the hax session: they are silenced.
+    let vanilla_rustc_args: Vec<String> = {
+        let mut args = rustc_args.iter().cloned();
+        let mut vanilla_args = vec![];
+        while let Some(arg) = args.next() {
+            if arg == "--cap-lints" {
+                args.next();
+            } else if !arg.starts_with("--cap-lints=") {
+                vanilla_args.push(arg);
+            }
+        }
+        vanilla_args.push("--cap-lints=allow".into());
+        vanilla_args
+    };
     if translate_package {
-        // We want to enable certain features, but only if the crate
-        // itself doesn't enable those
-        let features = Features {
-            adt_const_params: false,    // not useful for now
-            generic_const_exprs: false, // not useful for now
-            register_tool: true,
-            registered_tools: HashSet::from_iter(vec![hax_lib_macros_types::HAX_TOOL.into()]),
-            auto_traits: true,
-            negative_impls: true,
-        } - Features::detect_forking();
         rustc_args = [rustc_args[0].clone()]
             .into_iter()
             .chain([
@@ -151,66 +165,66 @@ fn main() {
                 hax_lib_macros_types::HAX_CFG_OPTION_NAME.into(),
             ])
             .chain(match &options.command {
-                Command::Backend(BackendOptions { backend, .. }) => {
+                Command::Backend(BackendOptions { backend, .. })
+                | Command::Reduce {
+                    backend: BackendOptions { backend, .. },
+                    ..
+                } => {
                     vec!["--cfg".into(), format!("hax_backend_{backend}")]
                 }
                 _ => vec![],
             })
-            .chain(features.into_iter().map(|s| format!("-Zcrate-attr={}", s)))
             .chain(rustc_args[1..].iter().cloned())
             .collect();
     };
 
-    let mut callbacks = CallbacksWrapper {
-        sub: &mut *callbacks,
-        options: {
-            let mut options = options.clone();
-            options.force_cargo_build = if translate_package {
-                options.force_cargo_build
-            } else {
-                hax_types::cli_options::ForceCargoBuild::default()
-            };
-            options
-        },
+    let cache_key = {
+        let mut options = options.clone();
+        options.force_cargo_build = if translate_package {
+            options.force_cargo_build
+        } else {
+            hax_types::cli_options::ForceCargoBuild::default()
+        };
+        let mut options = serde_json::to_value(&options).unwrap();
+        if !translate_package {
+            // The command (e.g. the backend) doesn't matter for
+            // the crates hax doesn't translate: leaving it out
+            // keeps them cached across commands.
+            options.as_object_mut().unwrap().remove("command");
+        }
+        options.to_string()
     };
 
-    let exit_code = rustc_driver::catch_with_exit_code({
-        let rustc_args = rustc_args.clone();
-        move || rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
+    let exit_code = rustc_driver::catch_with_exit_code(|| {
+        let mut callbacks = CallbacksWrapper {
+            sub: &mut *callbacks,
+            cache_key: cache_key.clone(),
+        };
+        rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
     });
 
     std::process::exit(
         if !vanilla_rustc_never && translate_package && exit_code == 0 {
-            // When the hax translation is successful, we need to re-run
-            // rustc. Indeed, hax translation doesn't actually build a
-            // package: no `rlib` will be written on disk.
-            self::vanilla_rustc()
+            // The hax session stops once the `haxmeta` file is
+            // written: no `rlib` is produced. The hax session can't
+            // go on to codegen: its cfgs (`hax`, `hax_backend_*`)
+            // are fixed when the session is created and the crate is
+            // already expanded with them and with the features hax
+            // injects. A second session, in the same process,
+            // compiles the crate as plain rustc would, without the
+            // cfgs and features of hax.
+            rustc_driver::catch_with_exit_code(|| {
+                let mut callbacks = CallbacksWrapper {
+                    sub: &mut CallbacksNoop,
+                    cache_key: cache_key.clone(),
+                };
+                rustc_driver::RunCompiler::new(&vanilla_rustc_args, &mut callbacks).run()
+            })
         } else {
             exit_code
         },
     )
 }
 
-/// Re-run rustc without doing any hax translation. This ensures a
-/// `rlib` is produced (when the crate compiles correctly).
-fn vanilla_rustc() -> i32 {
-    use std::process::{Command, Stdio};
-    let output = Command::new(std::env::args().next().unwrap())
-        .args(std::env::args().skip(1))
-        .env(HAX_VANILLA_RUSTC, "1")
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .unwrap()
-        .wait_with_output()
-        .unwrap();
-    if output.status.success() {
-        0
-    } else {
-        let stdout = &std::str::from_utf8(&output.stdout).unwrap();
-        let stderr = &std::str::from_utf8(&output.stderr).unwrap();
-        println!("{stdout}");
-        eprintln!("{stderr}");
-        output.status.code().unwrap_or(1)
-    }
-}
+struct CallbacksNoop;
+impl Callbacks for CallbacksNoop {}
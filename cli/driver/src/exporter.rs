@@ -1,6 +1,8 @@
+use crate::features::Features;
 use hax_frontend_exporter::state::LocalContextS;
 use hax_frontend_exporter::SInto;
 use hax_types::cli_options::{Backend, PathOrDash, ENV_VAR_OPTIONS_FRONTEND};
+use hax_types::engine_api::ProfilingData;
 use rustc_driver::{Callbacks, Compilation};
 use rustc_interface::interface;
 use rustc_interface::{interface::Compiler, Queries};
@@ -13,7 +15,7 @@ use rustc_middle::{
 use rustc_span::symbol::Symbol;
 use serde::Serialize;
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 use std::rc::Rc;
 
 type ThirBundle<'tcx> = (Rc<rustc_middle::thir::Thir<'tcx>>, ExprId);
@@ -119,13 +121,58 @@ fn precompute_local_thir_bodies(
         .map(|(ldid, bundle)| (ldid.to_def_id(), bundle))
 }
 
+type MirBundle<'tcx> = (
+    rustc_hir::def_id::DefId,
+    std::any::TypeId,
+    Rc<rustc_middle::mir::Body<'tcx>>,
+);
+/// Precompute the MIR bodies of the flavours `flavours` for all the
+/// bodies `body_owners`. Computing a flavour of MIR steals the
+/// previous one, possibly for other bodies as well (e.g. through
+/// inlining or const evaluation): the flavours are thus computed one
+/// after the other in the order of the MIR pipeline, each one for
+/// every body.
+fn precompute_local_mir_bodies<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body_owners: &[rustc_hir::def_id::DefId],
+    flavours: &[hax_frontend_exporter_options::MirFlavour],
+) -> Vec<MirBundle<'tcx>> {
+    use hax_frontend_exporter::{mir_kinds, IsMirKind};
+    use hax_frontend_exporter_options::MirFlavour;
+    fn precompute<'tcx, K: IsMirKind + 'static>(
+        tcx: TyCtxt<'tcx>,
+        body_owners: &[rustc_hir::def_id::DefId],
+    ) -> Vec<MirBundle<'tcx>> {
+        body_owners
+            .iter()
+            .filter_map(|did| {
+                let body = K::get_mir(tcx, did.expect_local(), |body| Rc::new(body.clone()))?;
+                Some((*did, std::any::TypeId::of::<K>(), body))
+            })
+            .collect()
+    }
+    let mut flavours = flavours.to_vec();
+    flavours.sort();
+    flavours
+        .into_iter()
+        .flat_map(|flavour| match flavour {
+            MirFlavour::Built => precompute::<mir_kinds::Built>(tcx, body_owners),
+            MirFlavour::Promoted => precompute::<mir_kinds::Promoted>(tcx, body_owners),
+            MirFlavour::Elaborated => precompute::<mir_kinds::Elaborated>(tcx, body_owners),
+            MirFlavour::Optimized => precompute::<mir_kinds::Optimized>(tcx, body_owners),
+            MirFlavour::Ctfe => precompute::<mir_kinds::CTFE>(tcx, body_owners),
+        })
+        .collect()
+}
+
 /// Browse a crate and translate every item from HIR+THIR to "THIR'"
 /// (I call "THIR'" the AST described in this crate)
 #[tracing::instrument(skip_all)]
 fn convert_thir<'tcx, Body: hax_frontend_exporter::IsBody>(
     options: &hax_frontend_exporter_options::Options,
-    macro_calls: HashMap<hax_frontend_exporter::Span, hax_frontend_exporter::Span>,
+    macro_calls: HashMap<rustc_span::Span, rustc_ast::ast::MacCall>,
     tcx: TyCtxt<'tcx>,
+    profiling: &mut Vec<ProfilingData>,
 ) -> (
     Vec<rustc_span::Span>,
     Vec<hax_frontend_exporter::DefId>,
@@ -134,27 +181,88 @@ fn convert_thir<'tcx, Body: hax_frontend_exporter::IsBody>(
         hax_frontend_exporter::ImplInfos,
     )>,
     Vec<hax_frontend_exporter::Item<Body>>,
+    Vec<hax_frontend_exporter::MacroExpansion>,
+    Vec<(hax_frontend_exporter::Span, String)>,
     hax_frontend_exporter::id_table::Table,
 ) {
     use hax_frontend_exporter::WithGlobalCacheExt;
     let mut state = hax_frontend_exporter::state::State::new(tcx, options.clone());
-    state.base.macro_infos = Rc::new(macro_calls);
-    for (def_id, thir) in precompute_local_thir_bodies(tcx) {
+    state.base.macro_infos = Rc::new(
+        macro_calls
+            .iter()
+            .map(|(span, mac)| {
+                use hax_frontend_exporter::*;
+                (
+                    translate_span(*span, tcx.sess),
+                    translate_span(argument_span_of_mac_call(mac), tcx.sess),
+                )
+            })
+            .collect(),
+    );
+    let start = std::time::SystemTime::now();
+    let bodies: Vec<_> = precompute_local_thir_bodies(tcx).collect();
+    profiling.push(ProfilingData::finished(
+        "THIR precomputation",
+        start,
+        bodies.len() as u32,
+    ));
+    let body_owners: Vec<_> = bodies.iter().map(|(def_id, _)| *def_id).collect();
+    for (def_id, thir) in bodies {
         state.with_item_cache(def_id, |caches| caches.thir = Some(thir));
     }
+    if !options.mir_flavours.is_empty() {
+        let start = std::time::SystemTime::now();
+        let bodies = precompute_local_mir_bodies(tcx, &body_owners, &options.mir_flavours);
+        profiling.push(ProfilingData::finished(
+            "MIR precomputation",
+            start,
+            bodies.len() as u32,
+        ));
+        for (def_id, kind, body) in bodies {
+            state.with_item_cache(def_id, |caches| caches.mir.insert(kind, body));
+        }
+    }
 
+    let start = std::time::SystemTime::now();
     let result = hax_frontend_exporter::inline_macro_invocations(tcx.hir().items(), &state);
-    let impl_infos = hax_frontend_exporter::impl_def_ids_to_impled_types_and_bounds(&state)
-        .into_iter()
-        .collect();
+    profiling.push(ProfilingData::finished(
+        "frontend export",
+        start,
+        result.len() as u32,
+    ));
+    let macro_expansions = hax_frontend_exporter::macro_expansions(&state, &macro_calls);
+    let impl_infos = hax_frontend_exporter::impl_def_ids_to_impled_types_and_bounds(&state);
     let exported_spans = state.with_global_cache(|cache| cache.spans.keys().copied().collect());
-    let exported_def_ids = state.with_global_cache(|cache| {
-        cache
-            .per_item
-            .values()
-            .filter_map(|per_item_cache| per_item_cache.def_id.clone())
-            .collect()
+    // `per_item` is a `HashMap`: sort its entries so that the
+    // exported `def_ids` are reproducible.
+    let exported_def_ids = hax_frontend_exporter::recover_export_error(|| {
+        state.with_global_cache(|cache| {
+            let mut per_item: Vec<_> = cache.per_item.iter().collect();
+            per_item.sort_by_key(|(did, _)| tcx.def_path_hash(**did));
+            per_item
+                .into_iter()
+                .filter_map(|(_, per_item_cache)| per_item_cache.def_id.clone())
+                .collect()
+        })
+    })
+    .unwrap_or_else(|reason| {
+        tcx.dcx().warn(format!(
+            "hax: the frontend failed to export the def ids: {reason}"
+        ));
+        vec![]
     });
+    // The spans of the comments live in the ID table: collect them
+    // before taking the table.
+    let files: BTreeSet<std::path::PathBuf> = BTreeSet::from_iter(
+        result
+            .iter()
+            .flat_map(|item| item.span.filename.to_path().map(|path| path.to_path_buf())),
+    );
+    let comments = files
+        .into_iter()
+        .flat_map(|path| hax_frontend_exporter::comments::comments_of_file(&state, path).ok())
+        .flatten()
+        .collect();
     let cache_map = state.with_global_cache(|cache| cache.id_table_session.table().clone());
 
     (
@@ -162,43 +270,207 @@ fn convert_thir<'tcx, Body: hax_frontend_exporter::IsBody>(
         exported_def_ids,
         impl_infos,
         result,
+        macro_expansions,
+        comments,
         cache_map,
     )
 }
 
-/// Collect a map from spans to macro calls
+/// Collect a map from spans to macro calls, in the source of the
+/// crate root and of its out-of-line modules. `macro_rules!`
+/// definitions are collected as calls to `macro_rules`, while the
+/// code disabled by a `#[cfg]` is skipped.
 #[tracing::instrument(skip_all)]
 fn collect_macros(
-    crate_ast: &rustc_ast::ast::Crate,
+    sess: &rustc_session::Session,
+    expanded_crate: &rustc_ast::ast::Crate,
 ) -> HashMap<rustc_span::Span, rustc_ast::ast::MacCall> {
     use {rustc_ast::ast::*, rustc_ast::visit::*};
-    struct MacroCollector {
+    /// The spans of the crate root and of the out-of-line modules
+    struct ModuleFinder(Vec<rustc_span::Span>);
+    impl<'ast> Visitor<'ast> for ModuleFinder {
+        fn visit_item(&mut self, item: &'ast Item) {
+            if let ItemKind::Mod(_, ModKind::Loaded(_, Inline::No, spans)) = &item.kind {
+                self.0.push(spans.inner_span);
+            }
+            walk_item(self, item)
+        }
+    }
+    struct MacroCollector<'a> {
+        sess: &'a rustc_session::Session,
         macro_calls: HashMap<rustc_span::Span, MacCall>,
     }
-    impl<'ast> Visitor<'ast> for MacroCollector {
-        fn visit_mac_call(&mut self, mac: &'ast rustc_ast::ast::MacCall) {
+    impl MacroCollector<'_> {
+        /// Are the `#[cfg]` attributes in `attrs` all satisfied?
+        fn configured(&self, attrs: &[Attribute]) -> bool {
+            attrs
+                .iter()
+                .filter(|attr| attr.has_name(rustc_span::symbol::sym::cfg))
+                .all(|attr| match attr.meta_item_list().as_deref() {
+                    Some([cfg]) => rustc_attr::eval_condition(cfg, self.sess, None, &mut |cfg| {
+                        self.sess.psess.config.contains(&(cfg.name, cfg.value))
+                    }),
+                    _ => true,
+                })
+        }
+    }
+    impl<'ast> Visitor<'ast> for MacroCollector<'_> {
+        fn visit_item(&mut self, item: &'ast Item) {
+            if !self.configured(&item.attrs) {
+                return;
+            }
+            if let ItemKind::MacroDef(MacroDef {
+                body,
+                macro_rules: true,
+            }) = &item.kind
+            {
+                let path = Path::from_ident(rustc_span::symbol::Ident::new(
+                    rustc_span::symbol::sym::macro_rules,
+                    item.span,
+                ));
+                let args = body.clone();
+                self.macro_calls.insert(item.span, MacCall { path, args });
+            }
+            walk_item(self, item)
+        }
+        fn visit_assoc_item(&mut self, item: &'ast AssocItem, ctxt: AssocCtxt) {
+            if self.configured(&item.attrs) {
+                walk_assoc_item(self, item, ctxt)
+            }
+        }
+        fn visit_foreign_item(&mut self, item: &'ast ForeignItem) {
+            if self.configured(&item.attrs) {
+                walk_item(self, item)
+            }
+        }
+        fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+            // Items and expressions are checked when visited
+            let attrs: &[Attribute] = match &stmt.kind {
+                StmtKind::Let(local) => &local.attrs,
+                StmtKind::MacCall(mac) => &mac.attrs,
+                _ => &[],
+            };
+            if self.configured(attrs) {
+                walk_stmt(self, stmt)
+            }
+        }
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if self.configured(&expr.attrs) {
+                walk_expr(self, expr)
+            }
+        }
+        fn visit_arm(&mut self, arm: &'ast Arm) {
+            if self.configured(&arm.attrs) {
+                walk_arm(self, arm)
+            }
+        }
+        fn visit_field_def(&mut self, field: &'ast FieldDef) {
+            if self.configured(&field.attrs) {
+                walk_field_def(self, field)
+            }
+        }
+        fn visit_variant(&mut self, variant: &'ast Variant) {
+            if self.configured(&variant.attrs) {
+                walk_variant(self, variant)
+            }
+        }
+        fn visit_mac_call(&mut self, mac: &'ast MacCall) {
             self.macro_calls.insert(mac.span(), mac.clone());
         }
     }
+    // The expanded crate has no macro call left: the source files of
+    // its modules are parsed again.
+    let mut modules = ModuleFinder(vec![expanded_crate.spans.inner_span]);
+    modules.visit_crate(expanded_crate);
     let mut v = MacroCollector {
+        sess,
         macro_calls: HashMap::new(),
     };
-    v.visit_crate(crate_ast);
+    for span in modules.0 {
+        let file = sess.source_map().lookup_source_file(span.lo());
+        let rustc_span::FileName::Real(name) = &file.name else {
+            continue;
+        };
+        let Some(path) = name.local_path() else {
+            continue;
+        };
+        let mut parser = match rustc_parse::new_parser_from_file(&sess.psess, path, None) {
+            Ok(parser) => parser,
+            Err(diags) => {
+                diags.into_iter().for_each(|diag| diag.cancel());
+                continue;
+            }
+        };
+        match parser.parse_mod(&rustc_ast::token::TokenKind::Eof) {
+            Ok((_, items, _)) => items.iter().for_each(|item| v.visit_item(item)),
+            Err(diag) => diag.cancel(),
+        }
+    }
     v.macro_calls
 }
 
+/// Does the crate use one of the quoting macros of `hax-lib`
+/// (e.g. `fstar!`, `coq_expr!` or `#[fstar::before]`)? Those expand
+/// to items or expressions guarded by the `hax_backend_*` cfgs.
+fn uses_quote_macros(crate_ast: &rustc_ast::ast::Crate) -> bool {
+    use {rustc_ast::ast::*, rustc_ast::visit::*};
+    const BACKENDS: [&str; 3] = ["fstar", "coq", "proverif"];
+    fn is_quote_macro(path: &Path) -> bool {
+        let segments: Vec<_> = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.as_str().to_string())
+            .collect();
+        let Some((last, init)) = segments.split_last() else {
+            return false;
+        };
+        BACKENDS.iter().any(|backend| {
+            last == backend
+                || ["_expr", "_unsafe_expr", "_before", "_after", "_replace"]
+                    .iter()
+                    .any(|suffix| *last == format!("{backend}{suffix}"))
+                || (init.last() == Some(&backend.to_string())
+                    && ["before", "after", "replace"].contains(&last.as_str()))
+        })
+    }
+    struct QuoteMacroFinder(bool);
+    impl<'ast> Visitor<'ast> for QuoteMacroFinder {
+        fn visit_mac_call(&mut self, mac: &'ast MacCall) {
+            self.0 |= is_quote_macro(&mac.path);
+            walk_mac(self, mac)
+        }
+        fn visit_attribute(&mut self, attr: &'ast Attribute) {
+            if let AttrKind::Normal(normal) = &attr.kind {
+                self.0 |= is_quote_macro(&normal.item.path);
+            }
+            walk_attribute(self, attr)
+        }
+    }
+    let mut v = QuoteMacroFinder(false);
+    v.visit_crate(crate_ast);
+    v.0
+}
+
 /// Callback for extraction
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct ExtractionCallbacks {
     pub inline_macro_calls: Vec<hax_types::cli_options::Namespace>,
-    pub macro_calls: HashMap<hax_frontend_exporter::Span, hax_frontend_exporter::Span>,
+    pub evaluate_const_fns: bool,
     pub body_types: Vec<hax_types::cli_options::ExportBodyKind>,
+    /// The features hax needs: the ones the crate doesn't enable
+    /// itself are added to its attributes.
+    pub features: Features,
+    /// Whether the crate uses a quoting macro of `hax-lib`, decided
+    /// right after parsing.
+    pub uses_quote_macros: bool,
 }
 
 impl From<ExtractionCallbacks> for hax_frontend_exporter_options::Options {
     fn from(opts: ExtractionCallbacks) -> hax_frontend_exporter_options::Options {
         hax_frontend_exporter_options::Options {
             inline_macro_calls: opts.inline_macro_calls,
+            evaluate_const_fns: opts.evaluate_const_fns,
+            mir_flavours: hax_types::cli_options::ExportBodyKind::mir_flavours(&opts.body_types),
         }
     }
 }
@@ -209,19 +481,11 @@ impl Callbacks for ExtractionCallbacks {
         compiler: &Compiler,
         queries: &'tcx Queries<'tcx>,
     ) -> Compilation {
-        let parse_ast = queries.parse().unwrap();
-        let parse_ast = parse_ast.borrow();
-        self.macro_calls = collect_macros(&parse_ast)
-            .into_iter()
-            .map(|(k, v)| {
-                use hax_frontend_exporter::*;
-                let sess = &compiler.sess;
-                (
-                    translate_span(k, sess),
-                    translate_span(argument_span_of_mac_call(&v), sess),
-                )
-            })
-            .collect();
+        let mut parse_ast = queries.parse().unwrap();
+        let parse_ast = parse_ast.get_mut();
+        let features = self.features.clone() - Features::detect(&compiler.sess, parse_ast);
+        features.inject(&compiler.sess, parse_ast);
+        self.uses_quote_macros = uses_quote_macros(parse_ast);
         Compilation::Continue
     }
     fn after_expansion<'tcx>(
@@ -232,6 +496,8 @@ impl Callbacks for ExtractionCallbacks {
         use std::ops::{Deref, DerefMut};
 
         queries.global_ctxt().unwrap().enter(|tcx| {
+            // Before HIR lowering steals the expanded crate
+            let macro_calls = collect_macros(tcx.sess, &tcx.resolver_for_lowering().borrow().1);
             use hax_frontend_exporter::ThirBody;
             use hax_types::cli_options::Command;
             use rustc_session::config::CrateType;
@@ -253,7 +519,19 @@ impl Callbacks for ExtractionCallbacks {
                         .into_iter(),
                     _ => vec![].into_iter(),
                 })
-                .map(|path| path.with_extension("haxmeta"))
+                .map(|path| {
+                    // `rlib`s are named `lib{crate_name}-{metadata}`,
+                    // while `haxmeta` files are named
+                    // `{crate_name}-{metadata}` (see `haxmeta_path`
+                    // below).
+                    let path = path.with_extension("haxmeta");
+                    match path.file_name().and_then(|name| name.to_str()) {
+                        Some(name) if name.starts_with("lib") => {
+                            path.with_file_name(&name["lib".len()..])
+                        }
+                        _ => path,
+                    }
+                })
                 .collect();
 
             let cg_metadata = opts.cg.metadata[0].clone();
@@ -265,33 +543,40 @@ impl Callbacks for ExtractionCallbacks {
             let mut file = BufWriter::new(File::create(&haxmeta_path).unwrap());
 
             use hax_types::driver_api::{with_kind_type, HaxMeta};
+            let mut profiling = vec![];
             with_kind_type!(
                 self.body_types.clone(),
                 <Body>|| {
-                    let (spans, def_ids, impl_infos, items, cache_map) =
-                        convert_thir(&self.clone().into(), self.macro_calls.clone(), tcx);
-                    let files: HashSet<PathBuf> = HashSet::from_iter(
-                        items
-                            .iter()
-                            .flat_map(|item| item.span.filename.to_path().map(|path| path.to_path_buf()))
-                    );
+                    let (spans, def_ids, impl_infos, items, macro_expansions, comments, cache_map) =
+                        convert_thir(&self.clone().into(), macro_calls.clone(), tcx, &mut profiling);
                     let haxmeta: HaxMeta<Body> = HaxMeta {
                         crate_name,
                         cg_metadata,
                         externs,
                         impl_infos,
                         items,
-                        comments: files.into_iter()
-                            .flat_map(|path|hax_frontend_exporter::comments::comments_of_file(path).ok())
-                            .flatten()
-                            .collect(),
+                        comments,
                         def_ids,
+                        macro_expansions,
                         hax_version: hax_types::HAX_VERSION.into(),
                     };
+                    let start = std::time::SystemTime::now();
+                    let quantity = haxmeta.items.len() as u32;
                     haxmeta.write(&mut file, cache_map);
+                    profiling.push(ProfilingData::finished("haxmeta serialization", start, quantity));
                 }
             );
 
+            // The quoting macros of `hax-lib` expand to code guarded
+            // by the `hax_backend_*` cfgs, which the crate may also
+            // use directly
+            let uses_backend_cfgs = self.uses_quote_macros
+                || compiler.sess.source_map().files().iter().any(|file| {
+                    file.src
+                        .as_ref()
+                        .is_some_and(|src| src.contains("hax_backend_"))
+                });
+
             let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
             let manifest_dir = std::path::Path::new(&manifest_dir);
 
@@ -302,6 +587,8 @@ impl Callbacks for ExtractionCallbacks {
                     .to_path(rustc_span::FileNameDisplayPreference::Local)
                     .to_path_buf(),
                 path: haxmeta_path,
+                uses_backend_cfgs,
+                profiling,
             };
             eprintln!(
                 "{}{}",
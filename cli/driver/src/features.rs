@@ -1,10 +1,8 @@
 use std::collections::HashSet;
 
-use rustc_driver::{Callbacks, Compilation};
-use rustc_interface::{interface, Queries};
-use rustc_span::symbol::Symbol;
-
-use crate::callbacks_wrapper::CallbacksWrapper;
+use rustc_ast::ast;
+use rustc_session::Session;
+use rustc_span::symbol::{sym, Symbol};
 
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +14,12 @@ pub struct Features {
     pub register_tool: bool,
     pub auto_traits: bool,
     pub negative_impls: bool,
+    /// Required by the inner attributes `#![hax_lib::include]` and
+    /// `#![hax_lib::exclude]`
+    pub custom_inner_attributes: bool,
+    /// Required by attribute macros on non-inline modules (e.g.
+    /// `#[hax_lib::exclude] mod m;`)
+    pub proc_macro_hygiene: bool,
     pub registered_tools: HashSet<String>,
 }
 
@@ -27,6 +31,8 @@ impl From<&rustc_feature::Features> for Features {
             register_tool: rfeatures.register_tool,
             auto_traits: rfeatures.auto_traits,
             negative_impls: rfeatures.negative_impls,
+            custom_inner_attributes: rfeatures.custom_inner_attributes,
+            proc_macro_hygiene: rfeatures.proc_macro_hygiene,
             registered_tools: HashSet::new(),
         }
     }
@@ -44,6 +50,8 @@ impl core::ops::Sub for Features {
             register_tool: sub(self.register_tool, rhs.register_tool),
             auto_traits: sub(self.auto_traits, rhs.auto_traits),
             negative_impls: sub(self.negative_impls, rhs.negative_impls),
+            custom_inner_attributes: sub(self.custom_inner_attributes, rhs.custom_inner_attributes),
+            proc_macro_hygiene: sub(self.proc_macro_hygiene, rhs.proc_macro_hygiene),
             registered_tools: self
                 .registered_tools
                 .difference(&rhs.registered_tools)
@@ -65,6 +73,9 @@ impl Features {
             self.adt_const_params.then_some("adt_const_params"),
             self.generic_const_exprs.then_some("generic_const_exprs"),
             self.register_tool.then_some("register_tool"),
+            self.custom_inner_attributes
+                .then_some("custom_inner_attributes"),
+            self.proc_macro_hygiene.then_some("proc_macro_hygiene"),
         ]
         .into_iter()
         .flatten()
@@ -76,68 +87,53 @@ impl Features {
                 .map(|tool| format!("register_tool({})", tool)),
         )
     }
-    /// Runs Rustc with a driver that only collects which unstable
-    /// Rustc features are enabled
-    pub fn detect(options: &hax_types::cli_options::Options, rustc_args: &Vec<String>) -> Self {
-        struct CollectFeatures {
-            features: Features,
-        }
-        impl Callbacks for CollectFeatures {
-            fn after_expansion<'tcx>(
-                &mut self,
-                compiler: &interface::Compiler,
-                queries: &'tcx Queries<'tcx>,
-            ) -> Compilation {
-                queries.global_ctxt().unwrap().enter(|tcx| {
-                    self.features = tcx.features().into();
-                    self.features.registered_tools = tcx
-                        .registered_tools(())
-                        .iter()
-                        .map(|x| x.name.to_ident_string())
-                        .collect();
-                });
-                rustc_driver::Compilation::Stop
-            }
-        }
-        let mut callbacks = CollectFeatures {
-            features: Features::default(),
+    /// Collects the unstable features and the tools enabled by the
+    /// attributes of a crate (`#![feature(..)]` and
+    /// `#![register_tool(..)]`), once their `cfg_attr`s are expanded.
+    /// This runs right after parsing, before rustc computes the
+    /// features of the crate itself, and before it injects the
+    /// attributes given with `-Zcrate-attr`: those are taken into
+    /// account here as well.
+    pub fn detect(sess: &Session, krate: &ast::Crate) -> Self {
+        let mut root = ast::Crate {
+            attrs: krate.attrs.clone(),
+            items: Default::default(),
+            ..*krate
+        };
+        rustc_builtin_macros::cmdline_attrs::inject(
+            &mut root,
+            &sess.psess,
+            &sess.opts.unstable_opts.crate_attr,
+        );
+        let attrs = rustc_expand::config::pre_configure_attrs(sess, &root.attrs);
+        let names = |name: Symbol| {
+            attrs
+                .iter()
+                .filter(move |attr| attr.has_name(name))
+                .flat_map(|attr| attr.meta_item_list().unwrap_or_default())
+                .map(|item| item.name_or_empty().to_ident_string())
         };
-        let exit_code = rustc_driver::catch_with_exit_code(|| {
-            rustc_driver::RunCompiler::new(
-                rustc_args,
-                &mut CallbacksWrapper {
-                    sub: &mut callbacks,
-                    options: options.clone(),
-                },
-            )
-            .run()
-        });
-        if exit_code != 0 {
-            std::process::exit(exit_code);
+        let mut features = Features::default();
+        for feature in names(sym::feature) {
+            match feature.as_str() {
+                "adt_const_params" => features.adt_const_params = true,
+                "generic_const_exprs" => features.generic_const_exprs = true,
+                "register_tool" => features.register_tool = true,
+                "auto_traits" => features.auto_traits = true,
+                "negative_impls" => features.negative_impls = true,
+                "custom_inner_attributes" => features.custom_inner_attributes = true,
+                "proc_macro_hygiene" => features.proc_macro_hygiene = true,
+                _ => (),
+            }
         }
-        callbacks.features.clone()
+        features.registered_tools = names(sym::register_tool).collect();
+        features
     }
 
-    /// Just like `detect`, but wraps the call in a subprocess so that
-    /// we can capture `stdout` and `stderr`: we don't want the use to
-    /// see error message from Rustc twice, or Cargo to have to parse
-    /// Rustc messages twice.
-    pub fn detect_forking() -> Self {
-        use std::process::{Command, Stdio};
-        let output = Command::new(std::env::args().next().unwrap())
-            .args(std::env::args().skip(1))
-            .env("HAX_FEATURES_DETECTION_MODE", "1")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .unwrap()
-            .wait_with_output()
-            .unwrap();
-        let stderr = &std::str::from_utf8(&output.stderr).unwrap();
-        serde_json::from_str(stderr).unwrap_or_else(|e| {
-            eprintln!("{}", stderr);
-            tracing::error!("rustc emitted an error, aborting hax custom driver.");
-            std::process::exit(1);
-        })
+    /// Adds the features and tools of `self` to the attributes of a
+    /// crate, just as `-Zcrate-attr` flags would.
+    pub fn inject(&self, sess: &Session, krate: &mut ast::Crate) {
+        let attrs: Vec<String> = self.into_iter().collect();
+        rustc_builtin_macros::cmdline_attrs::inject(krate, &sess.psess, &attrs)
     }
 }
@@ -0,0 +1,204 @@
+//! Bug reports: a bundle of everything needed to reproduce a run of
+//! the engine without the original source tree. A bundle is a
+//! directory with the following layout:
+//!  - `options.json`: the options `cargo hax` was called with;
+//!  - `versions.json`: the versions of hax and of its toolchain;
+//!  - `<CRATE>/`, for each crate given to the engine:
+//!     - `engine-input.json`: the exact input given to the engine;
+//!     - `engine-stderr.txt`: what the engine printed on stderr;
+//!     - `diagnostics.json`: the diagnostics the engine emitted;
+//!     - `crate.haxmeta`: the `haxmeta` file of the crate;
+//!     - `sources/`: the source files of the crate.
+//!
+//! A bundle is replayed with `cargo hax replay <DIR>`.
+
+use hax_frontend_exporter::{Item, ThirBody};
+use hax_types::cli_options::Options;
+use hax_types::diagnostics::Diagnostics;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+pub const ENGINE_INPUT: &str = "engine-input.json";
+pub const OPTIONS: &str = "options.json";
+
+pub struct BugReport {
+    dir: PathBuf,
+}
+
+fn write_json(path: &Path, value: &impl Serialize) {
+    let file = fs::File::create(path)
+        .unwrap_or_else(|err| panic!("Could not create {}: {err}", path.display()));
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), value).unwrap()
+}
+
+impl BugReport {
+    pub fn new(dir: &Path) -> Self {
+        fs::create_dir_all(dir).unwrap_or_else(|err| {
+            panic!("Could not create the directory {}: {err}", dir.display())
+        });
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// The directory of a crate in the bundle.
+    pub fn crate_dir(&self, crate_name: &str) -> PathBuf {
+        let dir = self.dir.join(crate_name);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Saves the options of `cargo hax` and the versions of hax.
+    pub fn save_options(&self, options: &Options, engine: &Path) {
+        write_json(&self.dir.join(OPTIONS), options);
+        write_json(
+            &self.dir.join("versions.json"),
+            &serde_json::json!({
+                "hax": hax_types::HAX_VERSION,
+                "rustc": env!("HAX_RUSTC_VERSION"),
+                "toolchain": env!("HAX_TOOLCHAIN"),
+                "engine": engine,
+            }),
+        );
+    }
+
+    /// Saves the `haxmeta` file of a crate, and the source files its
+    /// items come from. Only the source files given relatively to
+    /// `working_dir` are saved.
+    pub fn save_crate(
+        &self,
+        crate_name: &str,
+        haxmeta_path: &Path,
+        items: &[Item<ThirBody>],
+        working_dir: &Path,
+    ) {
+        let dir = self.crate_dir(crate_name);
+        fs::copy(haxmeta_path, dir.join("crate.haxmeta")).unwrap();
+        let sources: BTreeSet<&Path> = items
+            .iter()
+            .filter_map(|item| item.span.filename.to_path())
+            .filter(|path| path.is_relative())
+            .collect();
+        for source in sources {
+            let dest = dir.join("sources").join(source);
+            fs::create_dir_all(dest.parent().unwrap()).unwrap();
+            // Generated sources (e.g. by build scripts) might be gone
+            let _ = fs::copy(working_dir.join(source), dest);
+        }
+    }
+
+    /// Creates the file the input of the engine should be saved to.
+    pub fn engine_input(&self, crate_name: &str) -> std::io::BufWriter<fs::File> {
+        std::io::BufWriter::new(
+            fs::File::create(self.crate_dir(crate_name).join(ENGINE_INPUT)).unwrap(),
+        )
+    }
+
+    pub fn save_diagnostics(&self, crate_name: &str, diagnostics: &[Diagnostics]) {
+        write_json(
+            &self.crate_dir(crate_name).join("diagnostics.json"),
+            &diagnostics,
+        );
+    }
+
+    /// Forwards the lines of `stderr` to the standard error, and
+    /// saves them in the bundle.
+    pub fn tee_engine_stderr(
+        &self,
+        crate_name: &str,
+        stderr: impl std::io::Read + Send + 'static,
+    ) -> std::thread::JoinHandle<()> {
+        let path = self.crate_dir(crate_name).join("engine-stderr.txt");
+        std::thread::spawn(move || {
+            let mut file = fs::File::create(path).unwrap();
+            for line in std::io::BufReader::new(stderr)
+                .lines()
+                .map_while(Result::ok)
+            {
+                eprintln!("{line}");
+                let _ = writeln!(file, "{line}");
+            }
+        })
+    }
+}
+
+/// The crates of a bundle, i.e. its subdirectories with an engine
+/// input, sorted by name.
+pub fn crates(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut crates: Vec<_> = fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("Could not read the bug report {}: {err}", dir.display()))
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.join(ENGINE_INPUT).is_file())
+        .map(|path| {
+            (
+                path.file_name().unwrap().to_string_lossy().to_string(),
+                path,
+            )
+        })
+        .collect();
+    crates.sort();
+    crates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use hax_types::cli_options::Command;
+
+    #[test]
+    fn bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = BugReport::new(dir.path());
+
+        let options = Options::parse_from(["cargo-hax", "into", "-i", "-k::f", "fstar"]);
+        report.save_options(&options, Path::new("hax-engine"));
+        let saved: Options =
+            serde_json::from_reader(fs::File::open(dir.path().join(OPTIONS)).unwrap()).unwrap();
+        let Command::Backend(backend) = saved.command else {
+            panic!("expected the options of `cargo hax into`")
+        };
+        assert_eq!(backend.backend.to_string(), "fstar");
+        assert_eq!(backend.translation_options.include_namespaces.len(), 1);
+
+        for crate_name in ["b", "a"] {
+            report
+                .engine_input(crate_name)
+                .write_all(crate_name.as_bytes())
+                .unwrap();
+        }
+        report.save_diagnostics("a", &[]);
+        report
+            .tee_engine_stderr("a", std::io::Cursor::new(b"one\ntwo\n".to_vec()))
+            .join()
+            .unwrap();
+        // A crate without engine input is not part of the bundle
+        report.crate_dir("c");
+
+        let crates: Vec<_> = crates(dir.path())
+            .into_iter()
+            .map(|(name, path)| (name, path.strip_prefix(dir.path()).unwrap().to_path_buf()))
+            .collect();
+        assert_eq!(
+            crates,
+            [
+                ("a".to_string(), PathBuf::from("a")),
+                ("b".to_string(), PathBuf::from("b"))
+            ]
+        );
+        let crate_dir = dir.path().join("a");
+        assert_eq!(fs::read(crate_dir.join(ENGINE_INPUT)).unwrap(), b"a");
+        assert_eq!(
+            fs::read_to_string(crate_dir.join("engine-stderr.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+        assert_eq!(
+            fs::read_to_string(crate_dir.join("diagnostics.json")).unwrap(),
+            "[]"
+        );
+    }
+}
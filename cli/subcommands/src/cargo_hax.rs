@@ -7,14 +7,23 @@ use hax_types::driver_api::*;
 use hax_types::engine_api::*;
 use is_terminal::IsTerminal;
 use serde_jsonlines::BufReadExt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::BufRead;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process;
 
+mod bug_report;
+mod chrome_trace;
 mod engine_debug_webapp;
+mod incremental;
+mod init;
+mod item_graph;
+mod items;
+mod progress;
+mod reduce;
+mod stats;
 use hax_frontend_exporter::id_table;
 
 /// Return a toolchain argument to pass to `cargo`: when the correct nightly is
@@ -203,14 +212,41 @@ impl HaxMessage {
                 );
                 eprintln!("{}", renderer.render(Level::Info.title(&title)));
             }
-            Self::Stats { errors_per_item } => {
+            Self::Stats {
+                errors_per_item,
+                per_module,
+                per_kind,
+            } => {
                 let success_items = errors_per_item.iter().filter(|(_, n)| *n == 0).count();
                 let total = errors_per_item.len();
                 let title = format!(
                     "hax: {}/{} items were successfully translated ({}% success rate)",
                     success_items,
                     total,
-                    (success_items * 100) / total
+                    stats::percent(success_items, total)
+                );
+                eprintln!("{}", renderer.render(Level::Info.title(&title)));
+                let width = per_module.iter().map(|m| m.module.len()).max().unwrap_or(0);
+                for module in per_module {
+                    eprintln!(
+                        "  {:width$}  {}/{} ({}%)",
+                        module.module,
+                        module.successful,
+                        module.items,
+                        stats::percent(module.successful, module.items)
+                    );
+                }
+                if !per_kind.is_empty() {
+                    eprintln!("  diagnostics per kind:");
+                }
+                for kind in per_kind {
+                    eprintln!("  {} {}: {}", kind.code, kind.name, kind.count);
+                }
+            }
+            Self::UnchangedModules { unchanged, total } => {
+                let title = format!(
+                    "hax: {}/{} modules are unchanged since the last extraction, skipping them",
+                    unchanged, total
                 );
                 eprintln!("{}", renderer.render(Level::Info.title(&title)));
             }
@@ -219,6 +255,10 @@ impl HaxMessage {
                     "hax: running `cargo build` was not successful, continuing anyway.".to_string();
                 eprintln!("{}", renderer.render(Level::Warning.title(&title)));
             }
+            Self::PhaseStarted { .. } | Self::PhaseFinished { .. } | Self::ItemsProgress { .. } => {
+                // Phase events are rendered by the live progress
+                // indicator (see `progress`)
+            }
             Self::WarnExperimentalBackend { backend } => {
                 let title = format!(
                     "hax: Experimental backend \"{}\" is work in progress.",
@@ -230,205 +270,419 @@ impl HaxMessage {
     }
 }
 
-/// Runs `hax-engine`
+/// Reads the `haxmeta` files of the dependencies of a crate, given
+/// its `externs`. Only the `haxmeta` files in `available` (i.e. the
+/// ones produced by the current `cargo build`) are considered: a
+/// dependency is thus available only when hax ran on it as well (see
+/// the `--deps` flag). The ID tables of the dependencies are merged
+/// into `id_table`.
+fn read_dependencies(
+    externs: &[PathBuf],
+    available: &HashSet<PathBuf>,
+    id_table: &mut id_table::Table,
+) -> Vec<DependencyContext> {
+    externs
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .filter(|path| available.contains(path))
+        .map(|path| {
+            let (haxmeta, table): (HaxMeta<hax_frontend_exporter::ThirBody>, _) =
+                HaxMeta::read_with_id_offset(fs::File::open(&path).unwrap(), id_table.next_id());
+            id_table.merge(table);
+            DependencyContext {
+                crate_name: haxmeta.crate_name,
+                items: haxmeta.items,
+                impl_infos: haxmeta.impl_infos,
+            }
+        })
+        .collect()
+}
+
+/// Runs `hax-engine`. Returns whether an error occurred, with the
+/// statistics of the translation when they were requested.
 fn run_engine(
-    haxmeta: HaxMeta<hax_frontend_exporter::ThirBody>,
-    id_table: id_table::Table,
-    working_dir: PathBuf,
-    manifest_dir: PathBuf,
+    (haxmeta, mut id_table): (HaxMeta<hax_frontend_exporter::ThirBody>, id_table::Table),
+    available_haxmeta_files: &HashSet<PathBuf>,
+    EmitHaxMetaMessage {
+        working_dir,
+        manifest_dir,
+        path: haxmeta_path,
+        ..
+    }: EmitHaxMetaMessage,
     backend: &BackendOptions<()>,
+    bug_report: Option<&bug_report::BugReport>,
     message_format: MessageFormat,
-) -> bool {
+    profiling: &mut Vec<ProfilingData>,
+) -> (bool, Option<stats::CrateReport>) {
+    let out_dir = backend.output_dir.clone().unwrap_or({
+        let relative_path: PathBuf = [
+            "proofs",
+            format!("{}", backend.backend).as_str(),
+            "extraction",
+        ]
+        .iter()
+        .collect();
+        manifest_dir.join(&relative_path)
+    });
+
+    let mut dependencies =
+        read_dependencies(&haxmeta.externs, available_haxmeta_files, &mut id_table);
+    let mut input = haxmeta.items;
+    if let Some(bug_report) = bug_report {
+        bug_report.save_crate(&haxmeta.crate_name, &haxmeta_path, &input, &working_dir);
+    }
+    let with_stats = backend.stats || backend.stats_html.is_some();
+    // The kind and span of each item, for statistics
+    let item_infos: HashMap<_, _> = if with_stats {
+        input
+            .iter()
+            .map(|item| {
+                let kind = stats::variant_name(&item.kind);
+                (item.owner_id.clone(), (kind, item.span.clone()))
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    let plan = (backend.incremental && !backend.dry_run).then(|| {
+        let cache_path = haxmeta_path.with_extension(format!("{}.cache", backend.backend));
+        let plan = incremental::Plan::new(&input, backend, cache_path, &out_dir);
+        let (dirty, clean) = plan.partition(std::mem::take(&mut input));
+        input = dirty;
+        dependencies.push(DependencyContext {
+            crate_name: haxmeta.crate_name.clone(),
+            items: clean,
+            impl_infos: vec![],
+        });
+        plan
+    });
+    if let Some(plan) = &plan {
+        let unchanged = plan.total_modules() - plan.dirty_modules();
+        if unchanged > 0 {
+            HaxMessage::UnchangedModules {
+                unchanged,
+                total: plan.total_modules(),
+            }
+            .report(message_format, None);
+        }
+        if plan.dirty_modules() == 0 {
+            return (false, None);
+        }
+    }
+
     let engine_options = EngineOptions {
         hax_version: haxmeta.hax_version,
         backend: backend.clone(),
-        input: haxmeta.items,
+        input,
         impl_infos: haxmeta.impl_infos,
+        dependencies,
     };
-    let mut engine_subprocess = find_hax_engine(message_format)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .inspect_err(|e| {
-            if let std::io::ErrorKind::NotFound = e.kind() {
-                panic!(
-                    "The binary [{}] was not found in your [PATH].",
-                    ENGINE_BINARY_NAME
-                )
-            }
-        })
-        .unwrap();
-
-    let mut error = false;
-    let mut output = Output {
-        diagnostics: vec![],
-        files: vec![],
-        debug_json: None,
-    };
-    {
-        let mut rctx = hax_types::diagnostics::report::ReportCtx::default();
-        let mut stdin = std::io::BufWriter::new(
-            engine_subprocess
-                .stdin
-                .as_mut()
-                .expect("Could not write on stdin"),
-        );
+    let crate_name = haxmeta.crate_name;
+    EngineRun {
+        crate_name: crate_name.clone(),
+        working_dir,
+        out_dir,
+        backend,
+        bug_report,
+        message_format,
+        item_infos,
+        plan,
+    }
+    .run(
+        |stdin| {
+            id_table::WithTable::run(id_table, engine_options, |with_table| {
+                if let Some(bug_report) = bug_report {
+                    serde_json::to_writer(bug_report.engine_input(&crate_name), with_table)
+                        .unwrap();
+                }
+                serde_json::to_writer(stdin, with_table).unwrap();
+            })
+        },
+        profiling,
+    )
+}
 
-        macro_rules! send {
-            ($value:expr) => {
-                serde_json::to_writer(&mut stdin, $value).unwrap();
-                stdin.write_all(b"\n").unwrap();
-                stdin.flush().unwrap();
-            };
-        }
+/// Pretty prints Rust code on behalf of the engine.
+fn pretty_print_rust(code: &str) -> Result<String, String> {
+    match syn::parse_file(code) {
+        Ok(file) => match std::panic::catch_unwind(|| prettyplease::unparse(&file)) {
+            Ok(pp) => Ok(pp),
+            Err(err) => Err(format!("prettyplease panicked with: {:#?}", err)),
+        },
+        Err(err) => Err(format!("{}", err)),
+    }
+}
 
-        id_table::WithTable::run(id_table, engine_options, |with_table| {
-            send!(with_table);
-        });
+/// A run of the engine on the items of a crate.
+struct EngineRun<'a> {
+    crate_name: String,
+    working_dir: PathBuf,
+    out_dir: PathBuf,
+    backend: &'a BackendOptions<()>,
+    /// Where the engine's stderr and diagnostics are saved, if any.
+    bug_report: Option<&'a bug_report::BugReport>,
+    message_format: MessageFormat,
+    /// The kind and span of each item, for statistics.
+    item_infos: HashMap<hax_frontend_exporter::DefId, (String, hax_frontend_exporter::Span)>,
+    plan: Option<incremental::Plan>,
+}
 
-        let out_dir = backend.output_dir.clone().unwrap_or({
-            let relative_path: PathBuf = [
-                "proofs",
-                format!("{}", backend.backend).as_str(),
-                "extraction",
-            ]
-            .iter()
-            .collect();
-            manifest_dir.join(&relative_path)
+impl EngineRun<'_> {
+    /// Spawns the engine, gives it its input with `send_input`, and
+    /// handles its messages. Returns whether an error occurred, with
+    /// the statistics of the translation when they were requested.
+    fn run(
+        self,
+        send_input: impl FnOnce(&mut dyn Write),
+        profiling: &mut Vec<ProfilingData>,
+    ) -> (bool, Option<stats::CrateReport>) {
+        let Self {
+            crate_name,
+            working_dir,
+            out_dir,
+            backend,
+            bug_report,
+            message_format,
+            item_infos,
+            plan,
+        } = self;
+        let with_stats = backend.stats || backend.stats_html.is_some();
+        let mut engine_subprocess = find_hax_engine(message_format)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(if bug_report.is_some() {
+                std::process::Stdio::piped()
+            } else {
+                std::process::Stdio::inherit()
+            })
+            .spawn()
+            .inspect_err(|e| {
+                if let std::io::ErrorKind::NotFound = e.kind() {
+                    panic!(
+                        "The binary [{}] was not found in your [PATH].",
+                        ENGINE_BINARY_NAME
+                    )
+                }
+            })
+            .unwrap();
+        let stderr_thread = bug_report.map(|bug_report| {
+            bug_report.tee_engine_stderr(&crate_name, engine_subprocess.stderr.take().unwrap())
         });
 
-        let stdout = std::io::BufReader::new(engine_subprocess.stdout.take().unwrap());
+        let mut error = false;
+        let mut output = Output {
+            diagnostics: vec![],
+            files: vec![],
+            debug_json: None,
+        };
+        let mut produced_files = vec![];
         let mut errors_per_item: HashMap<_, usize> = HashMap::new();
-        for msg in stdout.json_lines() {
-            let msg = msg.expect(
-                "Hax engine sent an invalid json value. \
+        let mut diagnostics = vec![];
+        let mut report = None;
+        {
+            let mut rctx = hax_types::diagnostics::report::ReportCtx::default();
+            let mut stdin = std::io::BufWriter::new(
+                engine_subprocess
+                    .stdin
+                    .as_mut()
+                    .expect("Could not write on stdin"),
+            );
+
+            macro_rules! send {
+                ($value:expr) => {
+                    serde_json::to_writer(&mut stdin, $value).unwrap();
+                    stdin.write_all(b"\n").unwrap();
+                    stdin.flush().unwrap();
+                };
+            }
+
+            send_input(&mut stdin);
+            stdin.write_all(b"\n").unwrap();
+            stdin.flush().unwrap();
+
+            let mut progress = progress::Progress::new(message_format == MessageFormat::Human);
+            let stdout = std::io::BufReader::new(engine_subprocess.stdout.take().unwrap());
+            for msg in stdout.json_lines() {
+                let msg = msg.expect(
+                    "Hax engine sent an invalid json value. \
             This might be caused by debug messages on stdout, \
             which is reserved for JSON communication with cargo-hax",
-            );
-            use protocol::*;
-            match msg {
-                FromEngine::Exit => break,
-                FromEngine::Diagnostic(diagnostic) => {
-                    error = true;
-                    if backend.dry_run {
-                        output.diagnostics.push(diagnostic.clone())
+                );
+                use protocol::*;
+                match msg {
+                    FromEngine::Exit => break,
+                    FromEngine::Diagnostic(diagnostic) => {
+                        error = true;
+                        if backend.dry_run {
+                            output.diagnostics.push(diagnostic.clone())
+                        }
+                        if let Some(owner_id) = &diagnostic.owner_id {
+                            *errors_per_item.entry(owner_id.clone()).or_default() += 1;
+                        }
+                        if with_stats || bug_report.is_some() {
+                            diagnostics.push(diagnostic.clone());
+                        }
+                        progress.clear();
+                        HaxMessage::Diagnostic {
+                            diagnostic,
+                            working_dir: working_dir.clone(),
+                        }
+                        .report(message_format, Some(&mut rctx));
                     }
-                    if let Some(owner_id) = &diagnostic.owner_id {
-                        *errors_per_item.entry(owner_id.clone()).or_default() += 1;
+                    FromEngine::File(file) => {
+                        if backend.dry_run {
+                            output.files.push(file)
+                        } else {
+                            produced_files.push(PathBuf::from(&file.path));
+                            let path = out_dir.join(&file.path);
+                            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                            let mut wrote = false;
+                            if fs::read_to_string(&path).as_ref().ok() != Some(&file.contents) {
+                                std::fs::write(&path, file.contents).unwrap();
+                                wrote = true;
+                            }
+                            if let Some(mut sourcemap) = file.sourcemap.clone() {
+                                sourcemap.sourcesContent = sourcemap
+                                    .sources
+                                    .iter()
+                                    .map(PathBuf::from)
+                                    .map(|path| {
+                                        if path.is_absolute() {
+                                            path
+                                        } else {
+                                            working_dir.join(path).to_path_buf()
+                                        }
+                                    })
+                                    .map(|path| fs::read_to_string(path).ok())
+                                    .collect();
+                                let f = std::fs::File::create(path.with_file_name(format!(
+                                    "{}.map",
+                                    path.file_name().unwrap().to_string_lossy()
+                                )))
+                                .unwrap();
+                                serde_json::to_writer(std::io::BufWriter::new(f), &sourcemap)
+                                    .unwrap()
+                            }
+                            progress.clear();
+                            HaxMessage::ProducedFile { path, wrote }.report(message_format, None)
+                        }
                     }
-                    HaxMessage::Diagnostic {
-                        diagnostic,
-                        working_dir: working_dir.clone(),
+                    FromEngine::DebugString(debug) => {
+                        output.debug_json = Some(debug);
                     }
-                    .report(message_format, Some(&mut rctx));
-                }
-                FromEngine::File(file) => {
-                    if backend.dry_run {
-                        output.files.push(file)
-                    } else {
-                        let path = out_dir.join(&file.path);
-                        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
-                        let mut wrote = false;
-                        if fs::read_to_string(&path).as_ref().ok() != Some(&file.contents) {
-                            std::fs::write(&path, file.contents).unwrap();
-                            wrote = true;
+                    FromEngine::PrettyPrintDiagnostic(diag) => {
+                        send!(&ToEngine::PrettyPrintedDiagnostic(format!("{}", diag)));
+                    }
+                    FromEngine::PrettyPrintRust(code) => {
+                        send!(&ToEngine::PrettyPrintedRust(pretty_print_rust(&code)));
+                    }
+                    FromEngine::ProfilingData(profiling_data) => {
+                        if backend.profile {
+                            progress.clear();
+                            HaxMessage::ProfilingData(profiling_data.clone())
+                                .report(message_format, None)
                         }
-                        if let Some(mut sourcemap) = file.sourcemap.clone() {
-                            sourcemap.sourcesContent = sourcemap
-                                .sources
-                                .iter()
-                                .map(PathBuf::from)
-                                .map(|path| {
-                                    if path.is_absolute() {
-                                        path
-                                    } else {
-                                        working_dir.join(path).to_path_buf()
-                                    }
-                                })
-                                .map(|path| fs::read_to_string(path).ok())
-                                .collect();
-                            let f = std::fs::File::create(path.with_file_name(format!(
-                                "{}.map",
-                                path.file_name().unwrap().to_string_lossy()
-                            )))
-                            .unwrap();
-                            serde_json::to_writer(std::io::BufWriter::new(f), &sourcemap).unwrap()
+                        profiling.push(profiling_data);
+                    }
+                    FromEngine::ItemProcessed(items) => {
+                        for item in items {
+                            errors_per_item.insert(item, 0);
                         }
-                        HaxMessage::ProducedFile { path, wrote }.report(message_format, None)
                     }
-                }
-                FromEngine::DebugString(debug) => {
-                    output.debug_json = Some(debug);
-                }
-                FromEngine::PrettyPrintDiagnostic(diag) => {
-                    send!(&ToEngine::PrettyPrintedDiagnostic(format!("{}", diag)));
-                }
-                FromEngine::PrettyPrintRust(code) => {
-                    let code = match syn::parse_file(&code) {
-                        Ok(file) => match std::panic::catch_unwind(|| prettyplease::unparse(&file))
-                        {
-                            Ok(pp) => Ok(pp),
-                            Err(err) => Err(format!("prettyplease panicked with: {:#?}", err)),
-                        },
-                        Err(err) => Err(format!("{}", err)),
-                    };
-                    send!(&ToEngine::PrettyPrintedRust(code));
-                }
-                FromEngine::ProfilingData(profiling_data) => {
-                    HaxMessage::ProfilingData(profiling_data).report(message_format, None)
-                }
-                FromEngine::ItemProcessed(items) => {
-                    for item in items {
-                        errors_per_item.insert(item, 0);
+                    FromEngine::PhaseStarted { phase, quantity } => match message_format {
+                        MessageFormat::Human => progress.phase_started(phase, quantity),
+                        MessageFormat::Json => HaxMessage::PhaseStarted { phase, quantity }
+                            .report(message_format, None),
+                    },
+                    FromEngine::PhaseFinished { phase, errored } => match message_format {
+                        MessageFormat::Human => progress.phase_finished(),
+                        MessageFormat::Json => HaxMessage::PhaseFinished { phase, errored }
+                            .report(message_format, None),
+                    },
+                    FromEngine::ItemsProgress { processed, total } => match message_format {
+                        MessageFormat::Human => progress.items_progress(processed, total),
+                        MessageFormat::Json => HaxMessage::ItemsProgress { processed, total }
+                            .report(message_format, None),
+                    },
+                    FromEngine::Ping => {
+                        send!(&ToEngine::Pong);
                     }
                 }
-                FromEngine::Ping => {
-                    send!(&ToEngine::Pong);
+            }
+            drop(progress);
+            if let Some(bug_report) = bug_report {
+                bug_report.save_diagnostics(&crate_name, &diagnostics);
+            }
+            if with_stats {
+                let crate_report = stats::CrateReport::new(
+                    crate_name.clone(),
+                    &item_infos,
+                    &errors_per_item,
+                    diagnostics,
+                    &working_dir,
+                    produced_files
+                        .iter()
+                        .map(|file| out_dir.join(file))
+                        .collect(),
+                );
+                if backend.stats {
+                    HaxMessage::Stats {
+                        errors_per_item: errors_per_item.clone().into_iter().collect(),
+                        per_module: crate_report.per_module(),
+                        per_kind: crate_report.per_kind(),
+                    }
+                    .report(message_format, None)
                 }
+                report = Some(crate_report);
             }
+            drop(stdin);
         }
-        if backend.stats {
-            HaxMessage::Stats {
-                errors_per_item: errors_per_item.into_iter().collect(),
+
+        let exit_status = engine_subprocess.wait().unwrap();
+        if let Some(thread) = stderr_thread {
+            let _ = thread.join();
+        }
+        if !exit_status.success() {
+            HaxMessage::HaxEngineFailure {
+                exit_code: exit_status.code().unwrap_or(-1),
             }
-            .report(message_format, None)
+            .report(message_format, None);
+            std::process::exit(1);
         }
-        drop(stdin);
-    }
 
-    let exit_status = engine_subprocess.wait().unwrap();
-    if !exit_status.success() {
-        HaxMessage::HaxEngineFailure {
-            exit_code: exit_status.code().unwrap_or(-1),
+        if let Some(plan) = plan {
+            let failing_items = errors_per_item
+                .iter()
+                .filter(|(_, n)| **n > 0)
+                .map(|(item, _)| item);
+            plan.save(produced_files.into_iter(), failing_items);
         }
-        .report(message_format, None);
-        std::process::exit(1);
-    }
 
-    if backend.dry_run {
-        serde_json::to_writer(std::io::BufWriter::new(std::io::stdout()), &output).unwrap()
-    }
-    if let Some(debug_json) = &output.debug_json {
-        use DebugEngineMode;
-        match &backend.debug_engine {
-            Some(DebugEngineMode::Interactive) => {
-                eprintln!("----------------------------------------------");
-                eprintln!("----------------------------------------------");
-                eprintln!("----------------------------------------------");
-                eprintln!("-- Engine debug mode. Press CTRL+C to exit. --");
-                eprintln!("----------------------------------------------");
-                eprintln!("----------------------------------------------");
-                eprintln!("----------------------------------------------");
-                engine_debug_webapp::run(|| debug_json.clone())
-            }
-            Some(DebugEngineMode::File(_file)) if !backend.dry_run => {
-                println!("{}", debug_json)
+        if backend.dry_run {
+            serde_json::to_writer(std::io::BufWriter::new(std::io::stdout()), &output).unwrap()
+        }
+        if let Some(debug_json) = &output.debug_json {
+            use DebugEngineMode;
+            match &backend.debug_engine {
+                Some(DebugEngineMode::Interactive) => {
+                    eprintln!("----------------------------------------------");
+                    eprintln!("----------------------------------------------");
+                    eprintln!("----------------------------------------------");
+                    eprintln!("-- Engine debug mode. Press CTRL+C to exit. --");
+                    eprintln!("----------------------------------------------");
+                    eprintln!("----------------------------------------------");
+                    eprintln!("----------------------------------------------");
+                    engine_debug_webapp::run(|| debug_json.clone())
+                }
+                Some(DebugEngineMode::File(_file)) if !backend.dry_run => {
+                    println!("{}", debug_json)
+                }
+                _ => (),
             }
-            _ => (),
         }
-    }
 
-    error
+        (error, report)
+    }
 }
 
 /// Uses `cargo metadata` to compute a derived target directory.
@@ -464,8 +718,9 @@ fn get_hax_version() -> String {
 
 /// Calls `cargo` with a custom driver which computes `haxmeta` files
 /// in `TARGET`. One `haxmeta` file is produced by crate. Each
-/// `haxmeta` file contains the full AST of one crate.
-fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
+/// `haxmeta` file contains the full AST of one crate. When `quiet` is
+/// set, the output of `cargo` is discarded.
+fn compute_haxmeta_files(options: &Options, quiet: bool) -> (Vec<EmitHaxMetaMessage>, i32) {
     let mut cmd = {
         let mut cmd = process::Command::new("cargo");
         if let Some(toolchain) = toolchain() {
@@ -515,7 +770,7 @@ fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
                     match msg {
                         HaxDriverMessage::EmitHaxMeta(data) => haxmeta_files.push(data),
                     }
-                } else {
+                } else if !quiet {
                     eprintln!("{}", line);
                 }
             }
@@ -528,7 +783,9 @@ fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
         .expect("`driver-hax-frontend-exporter`: could not start?");
 
     let exit_code = if !status.success() {
-        HaxMessage::CargoBuildFailure.report(options.message_format, None);
+        if !quiet {
+            HaxMessage::CargoBuildFailure.report(options.message_format, None);
+        }
         status.code().unwrap_or(254)
     } else {
         0
@@ -537,9 +794,48 @@ fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
     (haxmeta_files, exit_code)
 }
 
+/// Replays the bug report `dir` (see `bug_report`): the saved inputs
+/// of the engine are given to the engine again.
+fn replay(dir: &std::path::Path, message_format: MessageFormat) -> bool {
+    let options: Options = fs::File::open(dir.join(bug_report::OPTIONS))
+        .ok()
+        .and_then(|file| serde_json::from_reader(std::io::BufReader::new(file)).ok())
+        .unwrap_or_else(|| panic!("{} is not a valid bug report", dir.display()));
+    let Command::Backend(mut backend) = options.command else {
+        panic!("{} is not a bug report of `cargo hax into`", dir.display())
+    };
+    backend.incremental = false;
+    backend.output_dir = None;
+    let mut error = false;
+    let mut profiling = vec![];
+    for (crate_name, crate_dir) in bug_report::crates(dir) {
+        let input = fs::read(crate_dir.join(bug_report::ENGINE_INPUT)).unwrap();
+        let (crate_error, _) = EngineRun {
+            crate_name,
+            working_dir: crate_dir.join("sources"),
+            out_dir: crate_dir.join("output"),
+            backend: &backend,
+            bug_report: None,
+            message_format,
+            item_infos: HashMap::new(),
+            plan: None,
+        }
+        .run(|stdin| stdin.write_all(&input).unwrap(), &mut profiling);
+        error = error || crate_error;
+    }
+    error
+}
+
 /// Run the command given by the user
 fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> bool {
     match options.command.clone() {
+        Command::Replay { dir } => replay(&dir, options.message_format),
+        Command::Init {
+            backends,
+            no_hax_lib,
+            force,
+        } => init::init(&backends, no_hax_lib, force),
+        Command::Reduce { .. } => reduce::reduce(options, haxmeta_files),
         Command::JSON {
             output_file,
             kind,
@@ -558,6 +854,7 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
                             impl_infos: haxmeta.impl_infos,
                             items: haxmeta.items,
                             comments: haxmeta.comments,
+                            macro_expansions: haxmeta.macro_expansions,
                         };
                         if use_ids {
                             id_table::WithTable::run(id_table, data, |with_table| {
@@ -581,36 +878,184 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
             });
             false
         }
+        Command::Items {
+            translation_options,
+            json,
+        } => {
+            let statuses: Vec<_> = haxmeta_files
+                .into_iter()
+                .flat_map(|EmitHaxMetaMessage { path, .. }| {
+                    let (haxmeta, _): (HaxMeta<hax_frontend_exporter::ThirBody>, _) =
+                        HaxMeta::read(fs::File::open(&path).unwrap());
+                    items::statuses(&haxmeta.items, &translation_options.include_namespaces)
+                })
+                .collect();
+            if json {
+                serde_json::to_writer(std::io::stdout(), &statuses).unwrap()
+            } else {
+                items::print_plain(&statuses)
+            }
+            false
+        }
+        Command::Why {
+            item,
+            translation_options,
+            dot,
+        } => {
+            let analyses: Vec<_> = haxmeta_files
+                .into_iter()
+                .map(|EmitHaxMetaMessage { path, .. }| {
+                    let (haxmeta, _): (HaxMeta<hax_frontend_exporter::ThirBody>, _) =
+                        HaxMeta::read(fs::File::open(&path).unwrap());
+                    items::Analysis::new(&haxmeta.items, &translation_options.include_namespaces)
+                })
+                .collect();
+            if let Some(dot) = dot {
+                let mut dest = dot.open_or_stdout();
+                for analysis in &analyses {
+                    dest.write_all(analysis.to_dot().as_bytes()).unwrap();
+                }
+            }
+            let Some(item) = item else {
+                return false;
+            };
+            let matches: Vec<(&items::Analysis, usize)> = analyses
+                .iter()
+                .flat_map(|analysis| {
+                    (0..analysis.graph.nodes.len())
+                        .filter(|i| analysis.graph.nodes[*i].matched_by(&item))
+                        .map(move |i| (analysis, i))
+                })
+                .collect();
+            // An unknown item is an error, while an item that is
+            // not extracted is an answer
+            if matches.is_empty() {
+                eprintln!(
+                    "No item matches `{}`: `cargo hax items` lists the paths of the items.",
+                    item.to_string()
+                );
+                return true;
+            }
+            for (analysis, i) in matches {
+                let status = &analysis.statuses[i];
+                print!("{} is {}", status.path, status.status);
+                match &status.decided_by {
+                    Some(decision) => println!(" by `{}`", decision.clause),
+                    None => println!(" by default"),
+                }
+                if status.status == items::Status::Excluded {
+                    continue;
+                }
+                match analysis.why(i) {
+                    Some(chain) => {
+                        let chain: Vec<_> = chain
+                            .iter()
+                            .map(|j| analysis.statuses[*j].path.as_str())
+                            .collect();
+                        println!("  {}", chain.join(" -> "));
+                    }
+                    None => println!("  no selected item depends on it"),
+                }
+            }
+            false
+        }
         Command::Backend(backend) => {
             use hax_frontend_exporter::ThirBody as Body;
             use Backend;
 
-            if matches!(backend.backend, Backend::Easycrypt | Backend::ProVerif(..)) {
-                HaxMessage::WarnExperimentalBackend {
-                    backend: backend.backend.clone(),
+            let backends = backend.per_backend();
+            let several = backends.len() > 1;
+            // The crates were built for the first backend: they are
+            // built again for the other backends only if their items
+            // may depend on the backend
+            let rebuild = haxmeta_files.iter().any(|msg| msg.uses_backend_cfgs);
+            let mut error = false;
+            let mut profiling = vec![];
+            let mut reports = vec![];
+            for (i, backend) in backends.into_iter().enumerate() {
+                if matches!(backend.backend, Backend::Easycrypt | Backend::ProVerif(..)) {
+                    HaxMessage::WarnExperimentalBackend {
+                        backend: backend.backend.clone(),
+                    }
+                    .report(options.message_format, None);
                 }
-                .report(options.message_format, None);
-            }
 
-            let mut error = false;
-            for EmitHaxMetaMessage {
-                working_dir,
-                manifest_dir,
-                path,
-            } in haxmeta_files
-            {
-                let (haxmeta, id_table): (HaxMeta<Body>, _) =
-                    HaxMeta::read(fs::File::open(&path).unwrap());
-
-                error = error
-                    || run_engine(
-                        haxmeta,
-                        id_table,
-                        working_dir,
-                        manifest_dir,
-                        &backend,
-                        options.message_format,
-                    );
+                let backend_options = Options {
+                    command: Command::Backend(backend.clone()),
+                    ..options.clone()
+                };
+                let bug_report = backend
+                    .bug_report
+                    .as_deref()
+                    .map(bug_report::BugReport::new);
+                if let Some(bug_report) = &bug_report {
+                    let engine = find_hax_engine(options.message_format);
+                    bug_report
+                        .save_options(&backend_options, std::path::Path::new(engine.get_program()));
+                }
+                let haxmeta_files = if i > 0 && rebuild {
+                    let (haxmeta_files, exit_code) = compute_haxmeta_files(&backend_options, false);
+                    error = error || exit_code != 0;
+                    haxmeta_files
+                } else {
+                    haxmeta_files.clone()
+                };
+                let label = |crate_name: String| {
+                    if several {
+                        format!("{crate_name} ({})", backend.backend)
+                    } else {
+                        crate_name
+                    }
+                };
+                let available_haxmeta_files: HashSet<PathBuf> = haxmeta_files
+                    .iter()
+                    .filter_map(|msg| msg.path.canonicalize().ok())
+                    .collect();
+                let mut backend_error = false;
+                for message in haxmeta_files {
+                    let (haxmeta, id_table): (HaxMeta<Body>, _) =
+                        HaxMeta::read(fs::File::open(&message.path).unwrap());
+
+                    let crate_name = haxmeta.crate_name.clone();
+                    let mut crate_profiling = message.profiling.clone();
+                    if backend.profile {
+                        for data in &crate_profiling {
+                            HaxMessage::ProfilingData(data.clone())
+                                .report(options.message_format, None)
+                        }
+                    }
+                    if !backend_error {
+                        let (crate_error, report) = run_engine(
+                            (haxmeta, id_table),
+                            &available_haxmeta_files,
+                            message,
+                            &backend,
+                            bug_report.as_ref(),
+                            options.message_format,
+                            &mut crate_profiling,
+                        );
+                        backend_error = crate_error;
+                        reports.extend(report.map(|report| stats::CrateReport {
+                            crate_name: label(report.crate_name.clone()),
+                            ..report
+                        }));
+                    }
+                    profiling.push((label(crate_name), crate_profiling));
+                }
+                error = error || backend_error;
+            }
+            if let Some(path) = &backend.stats_html {
+                fs::write(path, stats::render_html(&reports)).unwrap_or_else(|err| {
+                    panic!("Could not write the report to {}: {err}", path.display())
+                });
+            }
+            if let Some(path) = &backend.profile_output {
+                chrome_trace::write(path, &profiling).unwrap_or_else(|err| {
+                    panic!(
+                        "Could not write profiling data to {}: {err}",
+                        path.display()
+                    )
+                });
             }
             error
         }
@@ -638,7 +1083,12 @@ fn main() {
     };
     options.normalize_paths();
 
-    let (haxmeta_files, exit_code) = compute_haxmeta_files(&options);
+    let (haxmeta_files, exit_code) = match options.command {
+        // Replaying a bug report or setting up a package doesn't
+        // build anything
+        Command::Replay { .. } | Command::Init { .. } => (vec![], 0),
+        _ => compute_haxmeta_files(&options, false),
+    };
     let error = run_command(&options, haxmeta_files);
 
     std::process::exit(if exit_code == 0 && error {
@@ -0,0 +1,114 @@
+//! Writes profiling data in the Chrome trace event format (see
+//! https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+//! which is understood by Perfetto, speedscope or `chrome://tracing`.
+
+use hax_types::engine_api::ProfilingData;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Writes the profiling data of each crate to `path` (see `trace`).
+pub fn write(path: &Path, profiling: &[(String, Vec<ProfilingData>)]) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer(std::io::BufWriter::new(file), &trace(profiling))?;
+    Ok(())
+}
+
+/// The trace of the profiling data of each crate. Each crate gets its
+/// own track: the events of a crate are nested according to their
+/// timestamps, which gives the nesting of the phases of the engine.
+fn trace(profiling: &[(String, Vec<ProfilingData>)]) -> Value {
+    const PID: u32 = 1;
+    let mut events: Vec<Value> = vec![json!({
+        "name": "process_name", "ph": "M", "pid": PID, "tid": 0,
+        "args": {"name": "hax"},
+    })];
+    for (tid, (crate_name, data)) in profiling.iter().enumerate() {
+        events.push(json!({
+            "name": "thread_name", "ph": "M", "pid": PID, "tid": tid,
+            "args": {"name": crate_name},
+        }));
+        // Enclosing events should come first for events that start at
+        // the same time.
+        let mut data: Vec<_> = data.iter().collect();
+        data.sort_by_key(|data| (data.start_ns, std::cmp::Reverse(data.time_ns)));
+        events.extend(data.into_iter().map(|data| {
+            json!({
+                "name": data.context,
+                "cat": if data.errored { "errored" } else { "hax" },
+                "ph": "X",
+                "pid": PID,
+                "tid": tid,
+                "ts": data.start_ns as f64 / 1000.0,
+                "dur": data.time_ns as f64 / 1000.0,
+                "args": {
+                    "quantity": data.quantity,
+                    "memory": data.memory,
+                    "errored": data.errored,
+                },
+            })
+        }));
+    }
+    json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(context: &str, start_ns: u64, time_ns: u64) -> ProfilingData {
+        ProfilingData {
+            context: context.to_string(),
+            start_ns,
+            time_ns,
+            memory: 0,
+            quantity: 1,
+            errored: false,
+        }
+    }
+
+    #[test]
+    fn two_crates_with_nested_phases() {
+        let profiling = [
+            (
+                "a".to_string(),
+                vec![
+                    data("a::phase", 1_500, 500),
+                    data("a::engine", 1_000, 2_000),
+                ],
+            ),
+            ("b".to_string(), vec![data("b::engine", 4_000, 1_000)]),
+        ];
+        let trace = trace(&profiling);
+        let events = trace["traceEvents"].as_array().unwrap();
+        let names = |tid: usize| -> Vec<&Value> {
+            events
+                .iter()
+                .filter(|event| event["ph"] == "M" && event["tid"] == tid)
+                .map(|event| &event["args"]["name"])
+                .collect()
+        };
+        assert_eq!(names(0), [&json!("hax"), &json!("a")]);
+        assert_eq!(names(1), [&json!("b")]);
+        let spans: Vec<_> = events
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .map(|event| {
+                (
+                    event["tid"].as_u64().unwrap(),
+                    event["name"].as_str().unwrap(),
+                    event["ts"].as_f64().unwrap(),
+                    event["dur"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+        // The enclosing phase comes first, and times are in µs
+        assert_eq!(
+            spans,
+            [
+                (0, "a::engine", 1.0, 2.0),
+                (0, "a::phase", 1.5, 0.5),
+                (1, "b::engine", 4.0, 1.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Incremental extraction: this module computes a key for each module
+//! of a crate, out of the exported items of the module, the signatures
+//! of the items they depend on and the backend options. Modules whose
+//! key didn't change since the last extraction are clean: their items
+//! are given to the engine as a read-only context only, and their
+//! previously generated files are kept. When a module disappears, the
+//! whole crate is extracted again and the files that are not produced
+//! anymore are removed.
+
+use crate::item_graph::{path_of, ItemGraph};
+use hax_frontend_exporter::deterministic_hash::deterministic_hash;
+use hax_frontend_exporter::{DefId, DefKind, Item, ThirBody};
+use hax_types::cli_options::BackendOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The cache of a crate for a given backend, stored next to its
+/// `haxmeta` file.
+#[derive(Default, Serialize, Deserialize)]
+struct Cache {
+    /// Maps module names to their keys. Modules with errors have no
+    /// key: they are always extracted again.
+    modules: BTreeMap<String, Option<u64>>,
+    /// Every file produced by the engine, relative to the output
+    /// directory.
+    files: BTreeSet<PathBuf>,
+}
+
+/// The name of the module an item belongs to.
+pub fn module_of(def_id: &DefId) -> String {
+    let module = std::iter::successors(Some(def_id), |def_id| def_id.parent.as_ref())
+        .find(|def_id| matches!(def_id.kind, DefKind::Mod))
+        .unwrap_or(def_id);
+    path_of(module).join("::")
+}
+
+/// The decision of which items should be re-extracted.
+pub struct Plan {
+    cache_path: PathBuf,
+    out_dir: PathBuf,
+    previous: Cache,
+    /// The key of each module of the crate.
+    keys: HashMap<String, u64>,
+    /// Modules that need to be extracted again.
+    dirty: BTreeSet<String>,
+    /// Whether some modules of the previous extraction were deleted
+    /// or renamed: every module is then dirty, and the files of the
+    /// previous extraction that are not produced again are removed.
+    full: bool,
+}
+
+/// A hash of the options of `backend` that change the output of the
+/// engine, and of the version `version` of hax: the output of another
+/// version of the engine cannot be reused.
+fn options_hash(backend: &BackendOptions<()>, version: &str) -> u64 {
+    let mut options = serde_json::to_value(backend).unwrap();
+    if let Value::Object(map) = &mut options {
+        for key in [
+            "dry_run",
+            "verbose",
+            "stats",
+            "stats_html",
+            "profile",
+            "profile_output",
+            "debug_engine",
+            "incremental",
+            "bug_report",
+        ] {
+            map.remove(key);
+        }
+    }
+    deterministic_hash(&(version, options.to_string()))
+}
+
+impl Plan {
+    /// Computes the modules to re-extract for `items`, given the
+    /// cache stored at `cache_path` and the output directory
+    /// `out_dir` of the previous extraction.
+    pub fn new(
+        items: &[Item<ThirBody>],
+        backend: &BackendOptions<()>,
+        cache_path: PathBuf,
+        out_dir: &Path,
+    ) -> Self {
+        Self::from_graph(
+            &ItemGraph::new(items),
+            options_hash(backend, hax_types::HAX_VERSION),
+            cache_path,
+            out_dir,
+        )
+    }
+
+    /// Like `Plan::new`, given the dependency graph of the items and
+    /// the hash of the options (see `options_hash`).
+    fn from_graph(
+        graph: &ItemGraph,
+        options_hash: u64,
+        cache_path: PathBuf,
+        out_dir: &Path,
+    ) -> Self {
+        let modules: Vec<String> = graph
+            .nodes
+            .iter()
+            .map(|node| module_of(&node.def_id))
+            .collect();
+        let mut per_module: BTreeMap<&String, (Vec<u64>, BTreeSet<u64>)> = BTreeMap::new();
+        for (i, node) in graph.nodes.iter().enumerate() {
+            let (contents, dependencies) = per_module.entry(&modules[i]).or_default();
+            contents.push(node.content_hash);
+            dependencies.extend(
+                node.deps
+                    .iter()
+                    .filter(|j| modules[**j] != modules[i])
+                    .map(|j| graph.nodes[*j].signature_hash),
+            );
+        }
+        let keys: HashMap<String, u64> = per_module
+            .into_iter()
+            .map(|(module, (mut contents, dependencies))| {
+                contents.sort();
+                let key = deterministic_hash(&(options_hash, contents, dependencies));
+                (module.clone(), key)
+            })
+            .collect();
+
+        let previous: Cache = std::fs::read(&cache_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .filter(|cache: &Cache| cache.files.iter().all(|file| out_dir.join(file).exists()))
+            .unwrap_or_default();
+        let full = previous
+            .modules
+            .keys()
+            .any(|module| !keys.contains_key(module));
+        let dirty = keys
+            .iter()
+            .filter(|(module, key)| full || previous.modules.get(*module) != Some(&Some(**key)))
+            .map(|(module, _)| module.clone())
+            .collect();
+        Self {
+            cache_path,
+            out_dir: out_dir.to_path_buf(),
+            previous,
+            keys,
+            dirty,
+            full,
+        }
+    }
+
+    /// Number of modules that need to be extracted again.
+    pub fn dirty_modules(&self) -> usize {
+        self.dirty.len()
+    }
+
+    /// Total number of modules.
+    pub fn total_modules(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Splits `items` into the items that belongs to a dirty module
+    /// and the others.
+    pub fn partition(
+        &self,
+        items: Vec<Item<ThirBody>>,
+    ) -> (Vec<Item<ThirBody>>, Vec<Item<ThirBody>>) {
+        items
+            .into_iter()
+            .partition(|item| self.dirty.contains(&module_of(&item.owner_id)))
+    }
+
+    /// Records the result of a successful extraction: `files` were
+    /// produced, and `failing_items` had errors. The modules of the
+    /// failing items are not cached, so that their errors are
+    /// reported again on the next extraction.
+    pub fn save<'a>(
+        self,
+        files: impl Iterator<Item = PathBuf>,
+        failing_items: impl Iterator<Item = &'a DefId>,
+    ) {
+        let failing_modules: BTreeSet<String> = failing_items.map(module_of).collect();
+        let mut cache = self.previous;
+        cache.modules = self
+            .keys
+            .into_iter()
+            .map(|(module, key)| {
+                let key = (!failing_modules.contains(&module)).then_some(key);
+                (module, key)
+            })
+            .collect();
+        if self.full {
+            let files: BTreeSet<PathBuf> = files.collect();
+            for stale in cache.files.difference(&files) {
+                let path = self.out_dir.join(stale);
+                let _ = std::fs::remove_file(&path);
+                let _ = std::fs::remove_file(path.with_file_name(format!(
+                    "{}.map",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                )));
+            }
+            cache.files = files;
+        } else {
+            cache.files.extend(files);
+        }
+        if let Ok(json) = serde_json::to_vec(&cache) {
+            let _ = std::fs::write(&self.cache_path, json);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item_graph::tests::node;
+    use clap::Parser;
+
+    /// `a::f`, `b::g` that calls `a::f`, and `c::h`
+    fn graph() -> ItemGraph {
+        ItemGraph {
+            nodes: vec![
+                node("a::f", "Fn", &[]),
+                node("b::g", "Fn", &[0]),
+                node("c::h", "Fn", &[]),
+            ],
+        }
+    }
+
+    /// Extracts `graph`, writing a file per module in `out_dir`, and
+    /// returns the dirty modules
+    fn extract(graph: &ItemGraph, options_hash: u64, out_dir: &Path) -> Vec<String> {
+        let plan = Plan::from_graph(graph, options_hash, out_dir.join("cache.json"), out_dir);
+        let dirty: Vec<String> = plan.dirty.iter().cloned().collect();
+        let files: Vec<PathBuf> = plan
+            .keys
+            .keys()
+            .map(|module| PathBuf::from(format!("{module}.fst")))
+            .collect();
+        for file in &files {
+            std::fs::write(out_dir.join(file), "").unwrap();
+        }
+        plan.save(files.into_iter(), std::iter::empty());
+        dirty
+    }
+
+    #[test]
+    fn unchanged_input() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(extract(&graph(), 0, dir.path()), ["k::a", "k::b", "k::c"]);
+        assert!(extract(&graph(), 0, dir.path()).is_empty());
+    }
+
+    #[test]
+    fn body_change() {
+        let dir = tempfile::tempdir().unwrap();
+        extract(&graph(), 0, dir.path());
+        let mut graph = graph();
+        graph.nodes[0].content_hash += 1;
+        assert_eq!(extract(&graph, 0, dir.path()), ["k::a"]);
+    }
+
+    #[test]
+    fn signature_change() {
+        let dir = tempfile::tempdir().unwrap();
+        extract(&graph(), 0, dir.path());
+        let mut graph = graph();
+        graph.nodes[0].content_hash += 1;
+        graph.nodes[0].signature_hash += 1;
+        assert_eq!(extract(&graph, 0, dir.path()), ["k::a", "k::b"]);
+    }
+
+    #[test]
+    fn options_change() {
+        let dir = tempfile::tempdir().unwrap();
+        extract(&graph(), 0, dir.path());
+        assert_eq!(extract(&graph(), 1, dir.path()).len(), 3);
+
+        let backend = |args: &[&str]| {
+            BackendOptions::<()>::try_parse_from(["into"].iter().chain(args)).unwrap()
+        };
+        let fstar = options_hash(&backend(&["fstar"]), "1.0.0");
+        assert_eq!(fstar, options_hash(&backend(&["-vv", "fstar"]), "1.0.0"));
+        assert_ne!(fstar, options_hash(&backend(&["fstar"]), "1.0.1"));
+        assert_ne!(fstar, options_hash(&backend(&["coq"]), "1.0.0"));
+    }
+
+    #[test]
+    fn failing_items_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = graph();
+        let plan = Plan::from_graph(&graph, 0, dir.path().join("cache.json"), dir.path());
+        plan.save(std::iter::empty(), std::iter::once(&graph.nodes[1].def_id));
+        assert_eq!(extract(&graph, 0, dir.path()), ["k::b"]);
+        assert_eq!(extract(&graph, 0, dir.path()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn removed_module() {
+        let dir = tempfile::tempdir().unwrap();
+        extract(&graph(), 0, dir.path());
+        std::fs::write(dir.path().join("k::c.fst.map"), "").unwrap();
+        let mut graph = graph();
+        graph.nodes.pop();
+        // Every module is extracted again
+        assert_eq!(extract(&graph, 0, dir.path()), ["k::a", "k::b"]);
+        assert!(dir.path().join("k::a.fst").exists());
+        assert!(!dir.path().join("k::c.fst").exists());
+        assert!(!dir.path().join("k::c.fst.map").exists());
+    }
+}
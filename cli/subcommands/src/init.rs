@@ -0,0 +1,352 @@
+//! `cargo hax init`: sets up hax for the package of the current
+//! directory. The package is inspected with `cargo metadata`, then:
+//!  - `hax-lib` is added to its dependencies, with `cargo add`;
+//!  - the `hax` cfgs are allowed in its `[lints.rust]`;
+//!  - a starter file of inclusion clauses, `hax-include.clauses`, is
+//!    created at its root;
+//!  - for each backend, `proofs/<BACKEND>/extraction` is created with
+//!    the build files of the backend (see `templates/`), which refer
+//!    to the proof libraries of hax in `$HAX_HOME`.
+
+use colored::Colorize;
+use hax_types::cli_options::Backend;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// The file of inclusion clauses, relative to the root of the package.
+const CLAUSES: &str = "hax-include.clauses";
+
+/// The `check-cfg` values of the `hax` cfgs.
+const CHECK_CFG: &str = r#"check-cfg = ['cfg(hax)', 'cfg(hax_backend_fstar)', 'cfg(hax_backend_coq)', 'cfg(hax_backend_ssprove)', 'cfg(hax_backend_easycrypt)', 'cfg(hax_backend_proverif)']"#;
+
+/// The build file of the backends without a dedicated template.
+const GENERIC_MAKEFILE: &str = "\
+# Extracts the crate with hax. This file was generated by `cargo hax init`.
+
+.PHONY: extract
+
+extract:
+\tcd ../../.. && cargo hax into -i @hax-include.clauses {{BACKEND}}
+";
+
+/// The files to create in `proofs/<BACKEND>/extraction` for a backend.
+fn templates(backend: &str) -> Vec<(&'static str, &'static str)> {
+    match backend {
+        "fstar" => vec![
+            ("Makefile", include_str!("../templates/fstar/Makefile")),
+            (
+                "hax.fst.config.json",
+                include_str!("../templates/fstar/hax.fst.config.json"),
+            ),
+            (".gitignore", ".cache\n.depend\n"),
+        ],
+        "coq" => vec![
+            ("Makefile", include_str!("../templates/coq/Makefile")),
+            ("_CoqProject", include_str!("../templates/coq/_CoqProject")),
+            (
+                ".gitignore",
+                "Makefile.coq\nMakefile.coq.conf\n.Makefile.coq.d\n*.vo*\n*.glob\n.*.aux\n",
+            ),
+        ],
+        "pro-verif" => vec![
+            ("Makefile", include_str!("../templates/proverif/Makefile")),
+            (
+                "analysis.pv",
+                include_str!("../templates/proverif/analysis.pv"),
+            ),
+        ],
+        _ => vec![("Makefile", GENERIC_MAKEFILE)],
+    }
+}
+
+/// Fills the placeholders of `template`: `{{HAX_HOME}}`, `{{CRATE}}`
+/// (the name of the crate, capitalized) and `{{BACKEND}}`.
+fn render(template: &str, hax_home: &str, coq_name: &str, backend: &str) -> String {
+    template
+        .replace("{{HAX_HOME}}", hax_home)
+        .replace("{{CRATE}}", coq_name)
+        .replace("{{BACKEND}}", backend)
+}
+
+/// The root of the sources of hax, where the proof libraries are:
+/// `$HAX_HOME` if set, otherwise the sources `cargo hax` was built
+/// from, if they are still around.
+fn hax_home() -> Option<PathBuf> {
+    std::env::var_os("HAX_HOME").map(PathBuf::from).or_else(|| {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../.."))
+            .canonicalize()
+            .ok()
+            .filter(|path| path.join("proof-libs").is_dir())
+    })
+}
+
+/// The package of the current directory.
+fn current_package() -> Result<cargo_metadata::Package, String> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .map_err(|err| format!("could not inspect the package: {err}"))?;
+    let cwd = std::env::current_dir().unwrap().canonicalize().unwrap();
+    let is_current = |package: &&cargo_metadata::Package| {
+        package
+            .manifest_path
+            .parent()
+            .and_then(|dir| dir.canonicalize().ok())
+            == Some(cwd.clone())
+    };
+    metadata
+        .packages
+        .iter()
+        .find(is_current)
+        .or(match &metadata.workspace_members[..] {
+            [id] => metadata.packages.iter().find(|package| &package.id == id),
+            _ => None,
+        })
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "{} is a workspace: run `cargo hax init` in the directory of one of its packages",
+                metadata.workspace_root
+            )
+        })
+}
+
+/// Adds `hax-lib` to the dependencies of the package. This is a
+/// normal dependency: its macros (e.g. `#[hax_lib::requires]`) are
+/// used outside of `cfg(hax)` too, where they expand to nothing.
+fn add_hax_lib(manifest: &Path) -> Result<(), String> {
+    let args = [
+        "add",
+        "--manifest-path",
+        &manifest.to_string_lossy(),
+        "--git",
+        "https://github.com/hacspec/hax",
+        "hax-lib",
+    ];
+    let ok = process::Command::new("cargo")
+        .args(args)
+        .status()
+        .is_ok_and(|status| status.success());
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "could not add `hax-lib` to the dependencies, please run `cargo {}`",
+            args.map(|arg| if arg.contains(' ') || arg.contains('(') {
+                format!("'{arg}'")
+            } else {
+                arg.to_string()
+            })
+            .join(" ")
+        ))
+    }
+}
+
+/// Allows the `hax` cfgs in the `[lints.rust]` table of the manifest.
+/// Returns `false` if the manifest was left untouched.
+fn allow_hax_cfgs(manifest: &Path) -> Result<bool, String> {
+    let contents = fs::read_to_string(manifest)
+        .map_err(|err| format!("could not read {}: {err}", manifest.display()))?;
+    let Some(contents) = with_hax_cfgs(&contents) else {
+        return Ok(false);
+    };
+    fs::write(manifest, contents)
+        .map_err(|err| format!("could not write {}: {err}", manifest.display()))?;
+    Ok(true)
+}
+
+/// The manifest `contents` with the `hax` cfgs allowed in its
+/// `[lints.rust]` table, or `None` if its lints are already
+/// configured.
+fn with_hax_cfgs(contents: &str) -> Option<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let is_table = |line: &&str, name: &str| line.trim() == format!("[{name}]");
+    if contents.contains("unexpected_cfgs")
+        || lines.iter().any(|line| {
+            is_table(line, "lints") || line.replace(' ', "").starts_with("lints.workspace=")
+        })
+    {
+        // The lints are already configured (maybe for the whole
+        // workspace): we don't try to edit them
+        return None;
+    }
+    let entry = format!("unexpected_cfgs = {{ level = \"warn\", {CHECK_CFG} }}");
+    let contents = match lines.iter().position(|line| is_table(line, "lints.rust")) {
+        Some(i) => [&lines[..=i], &[entry.as_str()], &lines[i + 1..]]
+            .concat()
+            .join("\n"),
+        None => format!("{}\n\n[lints.rust]\n{entry}", contents.trim_end()),
+    } + "\n";
+    Some(contents)
+}
+
+/// Writes `contents` to `path`, unless `path` exists and `force` is
+/// not set. Reports what was done.
+fn create(path: &Path, contents: &str, force: bool) {
+    let existed = path.exists();
+    if existed && !force {
+        eprintln!("  {} {}", "Skipping".yellow().bold(), path.display());
+        return;
+    }
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents)
+        .unwrap_or_else(|err| panic!("Could not write {}: {err}", path.display()));
+    let verb = if existed { "Overwrote" } else { "Created" };
+    eprintln!("  {} {}", verb.green().bold(), path.display());
+}
+
+/// Runs `cargo hax init`. Returns `true` on error.
+pub fn init(backends: &[String], no_hax_lib: bool, force: bool) -> bool {
+    let package = match current_package() {
+        Ok(package) => package,
+        Err(err) => {
+            eprintln!("{}: {err}", "error".red().bold());
+            return true;
+        }
+    };
+    let manifest = package.manifest_path.as_std_path();
+    let root = manifest.parent().unwrap();
+    let crate_name = package
+        .targets
+        .iter()
+        .find(|target| target.kind.iter().any(|kind| kind == "lib"))
+        .map(|target| target.name.clone())
+        .unwrap_or_else(|| package.name.clone())
+        .replace('-', "_");
+    eprintln!(
+        "{} hax for {} ({})",
+        "Setting up".green().bold(),
+        package.name,
+        root.display()
+    );
+
+    let mut error = false;
+    let mut report = |result: Result<(), String>| {
+        if let Err(err) = result {
+            eprintln!("{}: {err}", "warning".yellow().bold());
+            error = true;
+        }
+    };
+    if !no_hax_lib {
+        if package.dependencies.iter().any(|dep| dep.name == "hax-lib") {
+            eprintln!(
+                "  {} `hax-lib` is already a dependency",
+                "Skipping".yellow().bold()
+            );
+        } else {
+            report(add_hax_lib(manifest));
+        }
+    }
+    report(allow_hax_cfgs(manifest).map(|edited| {
+        let (verb, what) = if edited {
+            ("Allowed".green().bold(), "the `hax` cfgs in")
+        } else {
+            ("Skipping".yellow().bold(), "the lints of")
+        };
+        eprintln!("  {verb} {what} {}", manifest.display());
+    }));
+
+    create(
+        &root.join(CLAUSES),
+        &format!(
+            "\
+# The items `cargo hax into -i @{CLAUSES}` extracts: one inclusion
+# clause per line (see `cargo hax into --help`). By default, every
+# item of the crate is extracted. For instance, to exclude a module:
+# -{crate_name}::some_module::**
++{crate_name}::**
+"
+        ),
+        force,
+    );
+
+    let hax_home = hax_home();
+    if hax_home.is_none() {
+        eprintln!(
+            "{}: could not find the sources of hax, set `HAX_HOME` to use the generated build files",
+            "warning".yellow().bold()
+        );
+    }
+    let hax_home = hax_home
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "$(HAX_HOME)".to_string());
+    let coq_name = {
+        let mut chars = crate_name.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    for name in backends {
+        let backend = Backend::from_name(name).to_string();
+        let dir = root.join("proofs").join(&backend).join("extraction");
+        for (file, template) in templates(name) {
+            let contents = render(template, &hax_home, &coq_name, name);
+            create(&dir.join(file), &contents, force);
+        }
+        eprintln!(
+            "{} `make -C {}` to extract the crate to {backend}",
+            "Run".cyan().bold(),
+            dir.strip_prefix(std::env::current_dir().unwrap())
+                .unwrap_or(&dir)
+                .display()
+        );
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE: &str = "[package]\nname = \"k\"\n";
+
+    fn entry() -> String {
+        format!("unexpected_cfgs = {{ level = \"warn\", {CHECK_CFG} }}")
+    }
+
+    #[test]
+    fn no_lints() {
+        assert_eq!(
+            with_hax_cfgs(PACKAGE),
+            Some(format!("{PACKAGE}\n[lints.rust]\n{}\n", entry()))
+        );
+    }
+
+    #[test]
+    fn existing_rust_lints() {
+        let manifest = format!("{PACKAGE}\n[lints.rust]\nunsafe_code = \"forbid\"\n");
+        assert_eq!(
+            with_hax_cfgs(&manifest),
+            Some(format!(
+                "{PACKAGE}\n[lints.rust]\n{}\nunsafe_code = \"forbid\"\n",
+                entry()
+            ))
+        );
+    }
+
+    #[test]
+    fn configured_lints() {
+        for lints in [
+            "[lints]\nworkspace = true\n",
+            "lints.workspace = true\n",
+            "[lints.rust]\nunexpected_cfgs = \"allow\"\n",
+        ] {
+            assert_eq!(with_hax_cfgs(&format!("{PACKAGE}{lints}")), None);
+        }
+    }
+
+    #[test]
+    fn templates_are_filled() {
+        for backend in ["fstar", "coq", "ssprove", "easycrypt", "pro-verif"] {
+            for (file, template) in templates(backend) {
+                let contents = render(template, "/hax", "K", backend);
+                assert!(
+                    !contents.contains("{{"),
+                    "`{file}` of {backend} has placeholders left: {contents}"
+                );
+            }
+        }
+    }
+}
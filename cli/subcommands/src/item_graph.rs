@@ -0,0 +1,422 @@
+//! A dependency graph between the items of a crate. The dependencies
+//! of an item are the local `DefId`s that appear in its exported
+//! representation: this is computed on the JSON representation of
+//! the items, which contains every `DefId` an item refers to.
+
+use hax_frontend_exporter::deterministic_hash::deterministic_hash;
+use hax_frontend_exporter::{
+    AttrArgs, AttrItem, AttrKind, Attribute, DefId, DefPathItem, DisambiguatedDefPathItem, Item,
+    ItemKind, ThirBody, Visibility,
+};
+use hax_lib_macros_types::{AttrPayload, ItemStatus};
+use hax_types::cli_options::Namespace;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Renders a chunk of a path. Chunks without names (e.g. `impl`
+/// blocks) are rendered between braces.
+fn path_chunk(item: &DisambiguatedDefPathItem) -> String {
+    match &item.data {
+        DefPathItem::TypeNs(s)
+        | DefPathItem::ValueNs(s)
+        | DefPathItem::MacroNs(s)
+        | DefPathItem::LifetimeNs(s) => s.clone(),
+        data => format!("{{{data:?}#{}}}", item.disambiguator),
+    }
+}
+
+/// The path of a `DefId`, starting with its crate name. This is the
+/// path matched by namespaces (see `Node::matched_by`).
+pub fn path_of(def_id: &DefId) -> Vec<String> {
+    std::iter::once(def_id.krate.clone())
+        .chain(def_id.path.iter().map(path_chunk))
+        .collect()
+}
+
+/// Normalizes the JSON representation of an exported value so that it
+/// is stable accross runs of rustc: the IDs of the ID table and the
+/// session-specific indexes of `DefId`s are dropped. If `signature`
+/// is set, bodies and spans are dropped as well.
+fn normalize(value: &mut Value, signature: bool) {
+    match value {
+        Value::Object(map) => {
+            // A `Node` (see `id_table`) is represented as `{id, value}`
+            if map.len() == 2 && map.contains_key("id") && map.contains_key("value") {
+                *value = map.remove("value").unwrap();
+                return normalize(value, signature);
+            }
+            if map.contains_key("krate") && map.contains_key("index") {
+                map.remove("index");
+            }
+            if signature {
+                map.retain(|key, _| key != "body" && !key.ends_with("span"));
+            }
+            map.values_mut().for_each(|v| normalize(v, signature));
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| normalize(v, signature)),
+        _ => (),
+    }
+}
+
+/// Collects the paths of the local `DefId`s found in a normalized
+/// JSON value. The parents of `DefId`s are not collected: an item
+/// doesn't depend on its parent.
+fn local_paths<'a>(value: &'a Value, krate: &str, acc: &mut Vec<&'a [Value]>) {
+    match value {
+        Value::Object(map) => {
+            if let (Some(Value::String(k)), Some(Value::Array(path))) =
+                (map.get("krate"), map.get("path"))
+            {
+                if k == krate {
+                    acc.push(path.as_slice());
+                }
+                map.iter()
+                    .filter(|(key, _)| *key != "parent")
+                    .for_each(|(_, v)| local_paths(v, krate, acc));
+            } else {
+                map.values().for_each(|v| local_paths(v, krate, acc));
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|v| local_paths(v, krate, acc)),
+        _ => (),
+    }
+}
+
+/// The non-doc-comment attributes among `attrs`.
+fn normal_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &AttrItem> {
+    attrs.iter().filter_map(|attr| match &attr.kind {
+        AttrKind::Normal(normal) => Some(&normal.item),
+        AttrKind::DocComment(..) => None,
+    })
+}
+
+/// The hax payload of an attribute, if it is a `_hax::json` attribute.
+fn payload(attr: &AttrItem) -> Option<AttrPayload> {
+    match &attr.args {
+        AttrArgs::Delimited(args) if attr.path == "_hax::json" => {
+            serde_json::from_str::<String>(&args.tokens)
+                .ok()
+                .and_then(|json| serde_json::from_str::<AttrPayload>(&json).ok())
+        }
+        _ => None,
+    }
+}
+
+/// The paths of the attributes of an item, split on `::`. The
+/// attributes `hax_lib::include` and `hax_lib::exclude` are expanded
+/// into `_hax::json` attributes: they are given back their names.
+fn attribute_paths(item: &Item<ThirBody>) -> Vec<Vec<String>> {
+    normal_attributes(item.attributes.attributes())
+        .map(|attr| {
+            let path = match payload(attr) {
+                Some(AttrPayload::ItemStatus(ItemStatus::Included { .. })) => "hax_lib::include",
+                Some(AttrPayload::ItemStatus(ItemStatus::Excluded { .. })) => "hax_lib::exclude",
+                _ => &attr.path,
+            };
+            path.split("::")
+                .map(|chunk| chunk.trim().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// Is an item excluded by a `hax_lib::exclude` attribute? Like in the
+/// engine, the first status found wins: the status of the item itself
+/// overrides the one of its innermost module, and so on.
+fn excluded_by_attribute(item: &Item<ThirBody>) -> bool {
+    normal_attributes(item.attributes.attributes())
+        .chain(normal_attributes(item.attributes.parent_attributes()))
+        .find_map(|attr| match payload(attr) {
+            Some(AttrPayload::ItemStatus(status)) => Some(status),
+            _ => None,
+        })
+        .is_some_and(|status| matches!(status, ItemStatus::Excluded { .. }))
+}
+
+/// An item of the graph.
+pub struct Node {
+    pub def_id: DefId,
+    /// See `path_of`.
+    pub path: Vec<String>,
+    /// The kind of the item (e.g. `Fn` or `Struct`).
+    pub kind: String,
+    /// Is the item public?
+    pub public: bool,
+    /// See `attribute_paths`.
+    pub attributes: Vec<Vec<String>>,
+    /// Is the item, or one of its modules, marked with
+    /// `hax_lib::exclude`? See `excluded_by_attribute`.
+    pub excluded_by_attribute: bool,
+    /// For trait implementations, the path of the trait.
+    pub implemented_trait: Option<Vec<String>>,
+    /// A hash of the item, stable accross runs of rustc.
+    pub content_hash: u64,
+    /// A hash of the signature of the item (i.e. the item without
+    /// bodies or spans), stable accross runs of rustc.
+    pub signature_hash: u64,
+    /// The items this item depends on.
+    pub deps: BTreeSet<usize>,
+    /// The items the signature of this item depends on.
+    pub signature_deps: BTreeSet<usize>,
+}
+
+impl Node {
+    /// Does `namespace` match the path of this node? Like in the
+    /// engine, chunks without names can only be matched by globs.
+    pub fn matched_by(&self, namespace: &Namespace) -> bool {
+        let path: Vec<Option<&str>> = self
+            .path
+            .iter()
+            .map(|chunk| Some(chunk.as_str()).filter(|chunk| !chunk.starts_with('{')))
+            .collect();
+        namespace.matches_path(&path)
+    }
+}
+
+/// The dependency graph of the items of a crate. Modules are not
+/// part of the graph: their items are nodes on their own.
+pub struct ItemGraph {
+    pub nodes: Vec<Node>,
+}
+
+impl ItemGraph {
+    pub fn new(items: &[Item<ThirBody>]) -> Self {
+        let items: Vec<_> = items
+            .iter()
+            .filter(|item| !matches!(item.kind, ItemKind::Mod(..)))
+            .map(|item| {
+                let mut content = serde_json::to_value(item).unwrap();
+                normalize(&mut content, false);
+                let mut signature = content.clone();
+                normalize(&mut signature, true);
+                (item, content, signature)
+            })
+            .collect();
+
+        // Maps the JSON path of each item to its index
+        let by_path: HashMap<String, usize> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, content, _))| {
+                let path = content.get("owner_id")?.get("contents")?.get("path")?;
+                Some((path.to_string(), i))
+            })
+            .collect();
+        let deps_of = |i: usize, value: &Value, krate: &str| {
+            let mut paths = vec![];
+            local_paths(value, krate, &mut paths);
+            paths
+                .into_iter()
+                .filter_map(|path| {
+                    // `path` might point to a sub-item (e.g. a field or
+                    // an associated item): we look for the longest
+                    // prefix of `path` that is an item.
+                    (1..=path.len())
+                        .rev()
+                        .find_map(|n| by_path.get(&Value::Array(path[..n].to_vec()).to_string()))
+                        .copied()
+                })
+                .filter(|j| *j != i)
+                .collect()
+        };
+
+        let nodes = items
+            .iter()
+            .enumerate()
+            .map(|(i, (item, content, signature))| {
+                let krate = &item.owner_id.krate;
+                Node {
+                    def_id: item.owner_id.clone(),
+                    path: path_of(&item.owner_id),
+                    kind: match serde_json::to_value(&item.kind) {
+                        Ok(Value::Object(map)) => map.keys().next().cloned(),
+                        Ok(Value::String(kind)) => Some(kind),
+                        _ => None,
+                    }
+                    .unwrap_or_default(),
+                    public: matches!(item.visibility, Visibility::Public),
+                    attributes: attribute_paths(item),
+                    excluded_by_attribute: excluded_by_attribute(item),
+                    implemented_trait: match &item.kind {
+                        ItemKind::Impl(hax_frontend_exporter::Impl {
+                            of_trait: Some(trait_ref),
+                            ..
+                        }) => Some(path_of(&trait_ref.def_id)),
+                        _ => None,
+                    },
+                    content_hash: deterministic_hash(&content.to_string()),
+                    signature_hash: deterministic_hash(&signature.to_string()),
+                    deps: deps_of(i, content, krate),
+                    signature_deps: deps_of(i, signature, krate),
+                }
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Computes the items `roots` depend on, transitively, `roots`
+    /// included. Only the signature of the items in `signature_only`
+    /// is considered.
+    pub fn transitive_dependencies(
+        &self,
+        roots: impl IntoIterator<Item = usize>,
+        signature_only: &BTreeSet<usize>,
+    ) -> BTreeSet<usize> {
+        let mut seen: BTreeSet<usize> = roots.into_iter().collect();
+        let mut todo: Vec<usize> = seen.iter().copied().collect();
+        while let Some(i) = todo.pop() {
+            let node = &self.nodes[i];
+            let deps = if signature_only.contains(&i) {
+                &node.signature_deps
+            } else {
+                &node.deps
+            };
+            todo.extend(deps.iter().copied().filter(|j| seen.insert(*j)));
+        }
+        seen
+    }
+
+    /// Finds a shortest chain of dependencies from one of the items
+    /// `from` to `to`. Only the signature of the items in
+    /// `signature_only` is considered.
+    pub fn shortest_path(
+        &self,
+        from: &BTreeSet<usize>,
+        to: usize,
+        signature_only: &BTreeSet<usize>,
+    ) -> Option<Vec<usize>> {
+        let mut predecessors: HashMap<usize, usize> = HashMap::new();
+        let mut queue: VecDeque<usize> = from.iter().copied().collect();
+        while let Some(i) = queue.pop_front() {
+            if i == to {
+                let mut path = vec![to];
+                while let Some(&pred) = predecessors.get(path.last().unwrap()) {
+                    path.push(pred);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let node = &self.nodes[i];
+            let deps = if signature_only.contains(&i) {
+                &node.signature_deps
+            } else {
+                &node.deps
+            };
+            for &j in deps {
+                if !from.contains(&j) && !predecessors.contains_key(&j) {
+                    predecessors.insert(j, i);
+                    queue.push_back(j);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The `DefId` of the item `path` (e.g. `a::f`) of the crate `k`,
+    /// of kind `kind`. The chunks of `path` but the last are modules.
+    pub(crate) fn def_id(path: &str, kind: &str) -> DefId {
+        let chunks: Vec<&str> = path.split("::").filter(|c| !c.is_empty()).collect();
+        let make = |n: usize, parent: Value, kind: &str| {
+            let path: Vec<Value> = chunks[..n]
+                .iter()
+                .map(|chunk| json!({"data": {"TypeNs": chunk}, "disambiguator": 0}))
+                .collect();
+            json!({"contents": {"id": 0, "value": {
+                "krate": "k",
+                "path": path,
+                "parent": parent,
+                "index": [0, 0],
+                "is_local": true,
+                "kind": kind,
+            }}})
+        };
+        let mut def_id = make(0, Value::Null, "Mod");
+        for n in 1..=chunks.len() {
+            def_id = make(n, def_id, if n == chunks.len() { kind } else { "Mod" });
+        }
+        serde_json::from_value(def_id).unwrap()
+    }
+
+    /// A public item `path` of kind `kind` that depends on the items
+    /// `deps`, by its body as well as by its signature.
+    pub(crate) fn node(path: &str, kind: &str, deps: &[usize]) -> Node {
+        let def_id = def_id(path, kind);
+        Node {
+            path: path_of(&def_id),
+            def_id,
+            kind: kind.to_string(),
+            public: true,
+            attributes: vec![],
+            excluded_by_attribute: false,
+            implemented_trait: None,
+            content_hash: deterministic_hash(&(path, "content")),
+            signature_hash: deterministic_hash(&(path, "signature")),
+            deps: deps.iter().copied().collect(),
+            signature_deps: deps.iter().copied().collect(),
+        }
+    }
+
+    /// `a` and `b` call each other, `b` calls `c`, and `d` calls `c`
+    /// and `e`, which is unreachable from `a`
+    fn graph() -> ItemGraph {
+        ItemGraph {
+            nodes: vec![
+                node("a", "Fn", &[1]),
+                node("b", "Fn", &[0, 2]),
+                node("c", "Fn", &[]),
+                node("d", "Fn", &[2, 4]),
+                node("e", "Fn", &[]),
+            ],
+        }
+    }
+
+    #[test]
+    fn transitive_dependencies() {
+        let graph = graph();
+        let none = BTreeSet::new();
+        let deps = |roots: &[usize], signature_only: &BTreeSet<usize>| {
+            Vec::from_iter(graph.transitive_dependencies(roots.iter().copied(), signature_only))
+        };
+        assert_eq!(deps(&[0], &none), [0, 1, 2]);
+        assert_eq!(deps(&[1], &none), [0, 1, 2]);
+        assert_eq!(deps(&[2], &none), [2]);
+        assert_eq!(deps(&[0, 4], &none), [0, 1, 2, 4]);
+        // Only the signature of `b` is considered: it has no dependency
+        let mut graph = self::graph();
+        graph.nodes[1].signature_deps.clear();
+        let deps = graph.transitive_dependencies([0], &BTreeSet::from([1]));
+        assert_eq!(Vec::from_iter(deps), [0, 1]);
+    }
+
+    #[test]
+    fn shortest_path() {
+        let graph = graph();
+        let none = BTreeSet::new();
+        let path = |from: &[usize], to| {
+            graph.shortest_path(&BTreeSet::from_iter(from.iter().copied()), to, &none)
+        };
+        assert_eq!(path(&[0], 2), Some(vec![0, 1, 2]));
+        assert_eq!(path(&[0], 0), Some(vec![0]));
+        // Through the cycle between `a` and `b`
+        assert_eq!(path(&[1], 0), Some(vec![1, 0]));
+        assert_eq!(path(&[0], 4), None);
+        assert_eq!(path(&[0, 3], 2), Some(vec![3, 2]));
+        // Ties are broken by the order of the items
+        let graph = ItemGraph {
+            nodes: vec![
+                node("f", "Fn", &[2, 1]),
+                node("g", "Fn", &[3]),
+                node("h", "Fn", &[3]),
+                node("i", "Fn", &[]),
+            ],
+        };
+        let path = graph.shortest_path(&BTreeSet::from([0]), 3, &none);
+        assert_eq!(path, Some(vec![0, 1, 3]));
+    }
+}
@@ -0,0 +1,431 @@
+//! The `items` subcommand: computes the inclusion status of the items
+//! of a crate for a list of inclusion clauses. This mirrors the
+//! selection of items done by the engine (see
+//! `Dependencies.filter_by_inclusion_clauses` in the engine).
+
+use crate::item_graph::{ItemGraph, Node};
+use hax_frontend_exporter::{Item, ThirBody};
+use hax_types::cli_options::{
+    DepsKind, InclusionClause, InclusionKind, ItemKindSelector, ItemSelector,
+};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Included,
+    SignatureOnly,
+    Excluded,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Status::Included => "included",
+            Status::SignatureOnly => "signature-only",
+            Status::Excluded => "excluded",
+        })
+    }
+}
+
+/// The clause that decided the status of an item.
+#[derive(Serialize, Clone)]
+pub struct Decision {
+    pub clause: String,
+    /// Was the item selected as a dependency of an item matched by
+    /// the clause?
+    pub as_dependency: bool,
+}
+
+#[derive(Serialize)]
+pub struct ItemStatus {
+    pub path: String,
+    pub kind: String,
+    pub status: Status,
+    /// `None` when no clause applies: items are included by default.
+    pub decided_by: Option<Decision>,
+}
+
+/// Does `clause` select `node`, i.e. does its namespace match `node`
+/// and does `node` satisfy its predicates?
+fn clause_matches(clause: &InclusionClause, node: &Node) -> bool {
+    let holds = |selector: &ItemSelector| match selector {
+        ItemSelector::Kind(kind) => {
+            node.kind
+                == match kind {
+                    ItemKindSelector::Fn => "Fn",
+                    ItemKindSelector::Struct => "Struct",
+                    ItemKindSelector::Enum => "Enum",
+                    ItemKindSelector::Impl => "Impl",
+                    ItemKindSelector::Trait => "Trait",
+                    ItemKindSelector::Const => "Const",
+                }
+        }
+        ItemSelector::Public => node.public,
+        ItemSelector::Attribute(namespace) => {
+            node.attributes.iter().any(|path| namespace.matches(path))
+        }
+        ItemSelector::ImplOf(namespace) => node
+            .implemented_trait
+            .as_ref()
+            .is_some_and(|path| namespace.matches(path)),
+    };
+    node.matched_by(&clause.namespace)
+        && clause
+            .predicates
+            .iter()
+            .all(|predicate| predicate.negated != holds(&predicate.selector))
+}
+
+/// The result of applying every clause on a set of items.
+struct Selection {
+    selected: BTreeSet<usize>,
+    /// Items whose bodies are dropped because they are shallow
+    /// dependencies.
+    drop_bodies: BTreeSet<usize>,
+    decisions: HashMap<usize, Decision>,
+}
+
+fn select(
+    graph: &ItemGraph,
+    clauses: &[InclusionClause],
+    universe: &BTreeSet<usize>,
+    signature_only: &BTreeSet<usize>,
+) -> Selection {
+    let mut selected = universe.clone();
+    let mut drop_bodies = BTreeSet::new();
+    let mut decisions = HashMap::new();
+    for clause in clauses {
+        let matched0: BTreeSet<usize> = universe
+            .iter()
+            .copied()
+            .filter(|i| clause_matches(clause, &graph.nodes[*i]))
+            .collect();
+        let (with_deps, drop) = match clause.kind {
+            InclusionKind::Included(DepsKind::Transitive) => (true, false),
+            InclusionKind::Included(DepsKind::Shallow) => (true, true),
+            InclusionKind::Included(DepsKind::None) => (false, false),
+            InclusionKind::SignatureOnly => (false, true),
+            InclusionKind::Excluded => (false, false),
+        };
+        let matched: BTreeSet<usize> = if with_deps {
+            graph
+                .transitive_dependencies(matched0.iter().copied(), signature_only)
+                .intersection(universe)
+                .copied()
+                .collect()
+        } else {
+            matched0.clone()
+        };
+        if drop {
+            drop_bodies.extend(matched.iter().copied());
+            drop_bodies.retain(|i| !matched0.contains(i));
+        }
+        for i in &matched {
+            decisions.insert(
+                *i,
+                Decision {
+                    clause: clause.to_string(),
+                    as_dependency: !matched0.contains(i),
+                },
+            );
+        }
+        match clause.kind {
+            InclusionKind::Excluded => selected.retain(|i| !matched.contains(i)),
+            _ => selected.extend(matched),
+        }
+    }
+    Selection {
+        selected,
+        drop_bodies,
+        decisions,
+    }
+}
+
+/// The inclusion status of the items of a crate, with the dependency
+/// graph it was computed from.
+pub struct Analysis {
+    pub graph: ItemGraph,
+    /// The status of each node of `graph`.
+    pub statuses: Vec<ItemStatus>,
+    /// The items whose bodies are dropped.
+    signature_only: BTreeSet<usize>,
+}
+
+impl Analysis {
+    /// Computes the status of each item of `items`.
+    pub fn new(items: &[Item<ThirBody>], clauses: &[InclusionClause]) -> Self {
+        Self::from_graph(ItemGraph::new(items), clauses)
+    }
+
+    /// Computes the status of each node of `graph`.
+    fn from_graph(graph: ItemGraph, clauses: &[InclusionClause]) -> Self {
+        // Items whose most precise clause is `+:` are imported without
+        // their bodies
+        let type_only: BTreeSet<usize> = (0..graph.nodes.len())
+            .filter(|i| {
+                clauses
+                    .iter()
+                    .filter(|clause| clause_matches(clause, &graph.nodes[*i]))
+                    .last()
+                    .is_some_and(|clause| matches!(clause.kind, InclusionKind::SignatureOnly))
+            })
+            .collect();
+        let all = (0..graph.nodes.len()).collect();
+        let first = select(&graph, clauses, &all, &type_only);
+        // Like the engine, we select items a second time, dropping the
+        // bodies of shallow dependencies.
+        let signature_only = type_only.union(&first.drop_bodies).copied().collect();
+        let second = select(&graph, clauses, &first.selected, &signature_only);
+
+        let statuses = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                // Like the engine, we drop the items excluded by an
+                // attribute after having applied the clauses
+                if node.excluded_by_attribute && second.selected.contains(&i) {
+                    return ItemStatus {
+                        path: node.path.join("::"),
+                        kind: node.kind.clone(),
+                        status: Status::Excluded,
+                        decided_by: Some(Decision {
+                            clause: "#[hax_lib::exclude]".to_string(),
+                            as_dependency: false,
+                        }),
+                    };
+                }
+                ItemStatus {
+                    path: node.path.join("::"),
+                    kind: node.kind.clone(),
+                    status: if !second.selected.contains(&i) {
+                        Status::Excluded
+                    } else if signature_only.contains(&i) {
+                        Status::SignatureOnly
+                    } else {
+                        Status::Included
+                    },
+                    decided_by: second
+                        .decisions
+                        .get(&i)
+                        .or(first.decisions.get(&i))
+                        .cloned(),
+                }
+            })
+            .collect();
+        Self {
+            graph,
+            statuses,
+            signature_only,
+        }
+    }
+
+    /// The items that are selected on their own, not as a dependency
+    /// of another item.
+    fn roots(&self) -> BTreeSet<usize> {
+        (0..self.statuses.len())
+            .filter(|i| {
+                let status = &self.statuses[*i];
+                status.status != Status::Excluded
+                    && !status
+                        .decided_by
+                        .as_ref()
+                        .is_some_and(|decision| decision.as_dependency)
+            })
+            .collect()
+    }
+
+    /// Finds a shortest chain of dependencies from a root to `item`.
+    pub fn why(&self, item: usize) -> Option<Vec<usize>> {
+        self.graph
+            .shortest_path(&self.roots(), item, &self.signature_only)
+    }
+
+    /// Renders the dependency graph with the inclusion status of each
+    /// item in the DOT format. Dashed edges are dependencies that
+    /// come from bodies only.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph items {\n  node [shape=box, style=filled];\n");
+        for (i, (node, status)) in self.graph.nodes.iter().zip(&self.statuses).enumerate() {
+            let color = match status.status {
+                Status::Included => "palegreen",
+                Status::SignatureOnly => "lightgoldenrod",
+                Status::Excluded => "lightgray",
+            };
+            dot += &format!(
+                "  n{i} [label=\"{}\\n{} ({})\", fillcolor={color}];\n",
+                status.path.replace('"', "\\\""),
+                node.kind,
+                status.status
+            );
+        }
+        for (i, node) in self.graph.nodes.iter().enumerate() {
+            for j in &node.deps {
+                let style = if node.signature_deps.contains(j) {
+                    ""
+                } else {
+                    " [style=dashed]"
+                };
+                dot += &format!("  n{i} -> n{j}{style};\n");
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+/// Computes the status of each item of `items`, sorted by path.
+pub fn statuses(items: &[Item<ThirBody>], clauses: &[InclusionClause]) -> Vec<ItemStatus> {
+    let mut statuses = Analysis::new(items, clauses).statuses;
+    statuses.sort_by(|a, b| a.path.cmp(&b.path));
+    statuses
+}
+
+/// Prints a list of item statuses as a human-readable table.
+pub fn print_plain(statuses: &[ItemStatus]) {
+    let width = |f: fn(&ItemStatus) -> usize| statuses.iter().map(f).max().unwrap_or(0);
+    let status_width = width(|item| item.status.to_string().len());
+    let kind_width = width(|item| item.kind.len());
+    let path_width = width(|item| item.path.len());
+    for item in statuses {
+        let decision = match &item.decided_by {
+            Some(Decision {
+                clause,
+                as_dependency: false,
+            }) => format!("(by `{clause}`)"),
+            Some(Decision {
+                clause,
+                as_dependency: true,
+            }) => format!("(by `{clause}`, as a dependency)"),
+            None => "(default)".to_string(),
+        };
+        println!(
+            "{:status_width$}  {:kind_width$}  {:path_width$}  {decision}",
+            item.status.to_string(),
+            item.kind,
+            item.path,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item_graph::tests::node;
+    use hax_types::cli_options::parse_inclusion_clause;
+
+    /// `f` calls `g`, whose body calls `h`; `s` is a struct and `t` a
+    /// private function whose signature mentions `s`
+    fn graph() -> ItemGraph {
+        let mut g = node("g", "Fn", &[2]);
+        g.signature_deps.clear();
+        let mut t = node("m::t", "Fn", &[3]);
+        t.public = false;
+        ItemGraph {
+            nodes: vec![
+                node("f", "Fn", &[1]),
+                g,
+                node("h", "Fn", &[]),
+                node("m::s", "Struct", &[]),
+                t,
+            ],
+        }
+    }
+
+    /// The items that are not excluded for the clauses `clauses`, with
+    /// their statuses
+    fn selected(clauses: &str) -> Vec<String> {
+        let clauses: Vec<InclusionClause> = clauses
+            .split_whitespace()
+            .map(|clause| parse_inclusion_clause(clause).unwrap())
+            .collect();
+        Analysis::from_graph(graph(), &clauses)
+            .statuses
+            .into_iter()
+            .filter(|item| item.status != Status::Excluded)
+            .map(|item| match item.status {
+                Status::SignatureOnly => format!("{}:", item.path),
+                _ => item.path,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(selected(""), ["k::f", "k::g", "k::h", "k::m::s", "k::m::t"]);
+    }
+
+    /// The examples of the documentation of `-i`, which describe the
+    /// selection of the engine
+    #[test]
+    fn modifiers() {
+        // `+` includes the dependencies, transitively
+        assert_eq!(selected("-** +k::f"), ["k::f", "k::g", "k::h"]);
+        // `+~` includes the direct dependencies, without their bodies
+        assert_eq!(selected("-** +~k::f"), ["k::f", "k::g:"]);
+        // `+!` includes no dependency
+        assert_eq!(selected("-** +!k::f"), ["k::f"]);
+        // `+:` includes the signature only
+        assert_eq!(selected("-** +:k::f"), ["k::f:"]);
+        // `-` excludes, dependencies of the other items included
+        assert_eq!(selected("-k::m::*"), ["k::f", "k::g", "k::h"]);
+        assert_eq!(selected("-k::m::s"), ["k::f", "k::g", "k::h", "k::m::t"]);
+    }
+
+    #[test]
+    fn ordering() {
+        assert_eq!(selected("-** +k::m::** -k::m::t"), ["k::m::s"]);
+        assert_eq!(selected("-** -k::m::t +k::m::**"), ["k::m::s", "k::m::t"]);
+        assert_eq!(selected("+k::m::t -**"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(selected("-** +!k::**/kind=fn,!pub"), ["k::m::t"]);
+        assert_eq!(selected("-** +!k::**/kind=struct"), ["k::m::s"]);
+        assert_eq!(selected("-k::**/!pub"), ["k::f", "k::g", "k::h", "k::m::s"]);
+        // The dependencies of a selected item don't need to satisfy
+        // the predicates
+        assert_eq!(selected("-** +k::**/!pub"), ["k::m::s", "k::m::t"]);
+    }
+
+    #[test]
+    fn why() {
+        let clauses = ["-**", "+k::f"].map(|clause| parse_inclusion_clause(clause).unwrap());
+        let analysis = Analysis::from_graph(graph(), &clauses);
+        assert_eq!(analysis.why(0), Some(vec![0]));
+        assert_eq!(analysis.why(2), Some(vec![0, 1, 2]));
+        // `s` is neither selected nor a dependency of a selected item
+        assert_eq!(analysis.why(3), None);
+    }
+
+    #[test]
+    fn to_dot() {
+        let clauses = ["-**", "+~k::f"].map(|clause| parse_inclusion_clause(clause).unwrap());
+        let dot = Analysis::from_graph(graph(), &clauses).to_dot();
+        assert!(dot.starts_with("digraph items {\n"));
+        assert!(dot.contains("  n0 [label=\"k::f\\nFn (included)\", fillcolor=palegreen];\n"));
+        assert!(dot
+            .contains("  n1 [label=\"k::g\\nFn (signature-only)\", fillcolor=lightgoldenrod];\n"));
+        assert!(dot.contains("  n2 [label=\"k::h\\nFn (excluded)\", fillcolor=lightgray];\n"));
+        assert!(dot.contains("  n0 -> n1;\n"));
+        // `g` calls `h` in its body only
+        assert!(dot.contains("  n1 -> n2 [style=dashed];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn decisions() {
+        let clauses = ["-**", "+k::f"].map(|clause| parse_inclusion_clause(clause).unwrap());
+        let analysis = Analysis::from_graph(graph(), &clauses);
+        let decision = |i: usize| {
+            let decision = analysis.statuses[i].decided_by.as_ref().unwrap();
+            (decision.clause.as_str(), decision.as_dependency)
+        };
+        assert_eq!(decision(0), ("+k::f", false));
+        assert_eq!(decision(2), ("+k::f", true));
+        assert_eq!(decision(3), ("-**", false));
+    }
+}
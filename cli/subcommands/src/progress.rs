@@ -0,0 +1,134 @@
+//! A live progress indicator for the engine, rendered on a single
+//! line of stderr. It shows the current phase of the engine and, when
+//! the phase reports it, how many items were processed so far.
+
+use is_terminal::IsTerminal;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// The minimal delay between two redraws of the indicator.
+const REDRAW_DELAY: Duration = Duration::from_millis(100);
+
+pub struct Progress {
+    /// The indicator is only drawn when stderr is a terminal.
+    enabled: bool,
+    start: Instant,
+    last_draw: Option<Instant>,
+    /// The current phase, with the number of items it processes.
+    phase: Option<(String, u32)>,
+    /// Items processed by the current phase, out of a total.
+    items: Option<(u32, u32)>,
+    /// Number of finished phases.
+    finished_phases: usize,
+    /// Is the indicator currently drawn?
+    drawn: bool,
+}
+
+impl Progress {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: enabled && std::io::stderr().is_terminal(),
+            start: Instant::now(),
+            last_draw: None,
+            phase: None,
+            items: None,
+            finished_phases: 0,
+            drawn: false,
+        }
+    }
+
+    pub fn phase_started(&mut self, phase: String, quantity: u32) {
+        self.phase = Some((phase, quantity));
+        self.items = None;
+        self.draw(false);
+    }
+
+    pub fn phase_finished(&mut self) {
+        self.finished_phases += 1;
+        self.draw(false);
+    }
+
+    pub fn items_progress(&mut self, processed: u32, total: u32) {
+        self.items = Some((processed, total));
+        self.draw(processed == total);
+    }
+
+    /// Erases the indicator, so that other messages can be printed
+    /// on stderr. The indicator is drawn again on the next event.
+    pub fn clear(&mut self) {
+        if self.drawn {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+            self.drawn = false;
+        }
+    }
+
+    fn draw(&mut self, force: bool) {
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        if !force
+            && self
+                .last_draw
+                .is_some_and(|last| now.duration_since(last) < REDRAW_DELAY)
+        {
+            return;
+        }
+        let Some(line) = self.line(self.start.elapsed()) else {
+            return;
+        };
+        eprint!("\r\x1b[2K{line}");
+        let _ = std::io::stderr().flush();
+        self.last_draw = Some(now);
+        self.drawn = true;
+    }
+
+    /// The text of the indicator, `elapsed` after the start.
+    fn line(&self, elapsed: Duration) -> Option<String> {
+        let (phase, quantity) = self.phase.as_ref()?;
+        let items = match self.items {
+            Some((processed, total)) => format!("{processed}/{total} items"),
+            None => format!("{quantity} items"),
+        };
+        Some(format!(
+            "hax: [{:>4}s] {phase} ({items}, {} phases done)",
+            elapsed.as_secs(),
+            self.finished_phases
+        ))
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line() {
+        let mut progress = Progress::new(false);
+        let elapsed = Duration::from_secs(12);
+        assert_eq!(progress.line(elapsed), None);
+        progress.phase_started("import_thir".to_string(), 7);
+        assert_eq!(
+            progress.line(elapsed).unwrap(),
+            "hax: [  12s] import_thir (7 items, 0 phases done)"
+        );
+        progress.items_progress(3, 7);
+        assert_eq!(
+            progress.line(elapsed).unwrap(),
+            "hax: [  12s] import_thir (3/7 items, 0 phases done)"
+        );
+        progress.phase_finished();
+        progress.phase_started("reject_unsafe".to_string(), 5);
+        assert_eq!(
+            progress.line(elapsed).unwrap(),
+            "hax: [  12s] reject_unsafe (5 items, 1 phases done)"
+        );
+    }
+}
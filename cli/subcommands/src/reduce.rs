@@ -0,0 +1,645 @@
+//! Test-case reduction (`cargo hax reduce`): shrinks a crate while a
+//! problem of the engine still reproduces. The reduction happens in
+//! two phases:
+//!  1. items are excluded with inclusion clauses: this only requires
+//!     running the engine again, not rebuilding the crate;
+//!  2. a copy of the crate is edited: items, then statements, are
+//!     removed from its sources, and the copy is rebuilt after each
+//!     edit.
+//!
+//! Both phases look for a large set of removable elements by trying
+//! to remove chunks of decreasing sizes (see `reduce_set`).
+
+use crate::{compute_haxmeta_files, find_hax_engine, pretty_print_rust, read_dependencies};
+use hax_frontend_exporter::{id_table, Item, ThirBody};
+use hax_types::cli_options::*;
+use hax_types::diagnostics::Diagnostics;
+use hax_types::driver_api::*;
+use hax_types::engine_api::protocol::*;
+use hax_types::engine_api::*;
+use serde_jsonlines::BufReadExt;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use syn::visit_mut::VisitMut;
+
+/// The name of the file listing the inclusion clauses of a reduced
+/// crate.
+const CLAUSES: &str = "hax-reduce.clauses";
+
+/// What the engine did on an input.
+struct Outcome {
+    exit_code: i32,
+    diagnostics: Vec<Diagnostics>,
+    /// The standard error of the engine, and the contents of the
+    /// files it produced.
+    output: String,
+}
+
+/// Describes the problem being reduced: every given condition should
+/// hold.
+struct Predicate {
+    diagnostic: Option<String>,
+    exit_code: Option<i32>,
+    pattern: Option<String>,
+}
+
+impl Predicate {
+    fn holds(&self, outcome: &Outcome) -> bool {
+        self.diagnostic.as_ref().map_or(true, |code| {
+            outcome
+                .diagnostics
+                .iter()
+                .any(|diagnostic| &diagnostic.kind.code() == code)
+        }) && self
+            .exit_code
+            .map_or(true, |exit_code| outcome.exit_code == exit_code)
+            && self.pattern.as_ref().map_or(true, |pattern| {
+                outcome.output.contains(pattern)
+                    || outcome
+                        .diagnostics
+                        .iter()
+                        .any(|diagnostic| diagnostic.to_string().contains(pattern))
+            })
+    }
+}
+
+/// A crate, as given to the engine.
+struct CrateInput {
+    crate_name: String,
+    hax_version: String,
+    items: Vec<Item<ThirBody>>,
+    impl_infos: Vec<(
+        hax_frontend_exporter::DefId,
+        hax_frontend_exporter::ImplInfos,
+    )>,
+    dependencies: Vec<DependencyContext>,
+    id_table: id_table::Table,
+}
+
+impl CrateInput {
+    fn read(message: &EmitHaxMetaMessage, available: &HashSet<PathBuf>) -> Self {
+        let (haxmeta, mut id_table): (HaxMeta<ThirBody>, _) =
+            HaxMeta::read(fs::File::open(&message.path).unwrap());
+        let dependencies = read_dependencies(&haxmeta.externs, available, &mut id_table);
+        Self {
+            crate_name: haxmeta.crate_name,
+            hax_version: haxmeta.hax_version,
+            items: haxmeta.items,
+            impl_infos: haxmeta.impl_infos,
+            dependencies,
+            id_table,
+        }
+    }
+
+    /// The input of the engine for this crate.
+    fn engine_input(&self, backend: &BackendOptions<()>) -> Vec<u8> {
+        let engine_options = EngineOptions {
+            hax_version: self.hax_version.clone(),
+            backend: backend.clone(),
+            input: self.items.clone(),
+            impl_infos: self.impl_infos.clone(),
+            dependencies: self.dependencies.clone(),
+        };
+        id_table::WithTable::run(self.id_table.clone(), engine_options, |with_table| {
+            serde_json::to_vec(with_table).unwrap()
+        })
+    }
+}
+
+/// Runs the engine on `input`, without reporting anything.
+fn run_engine_quietly(input: &[u8], message_format: MessageFormat) -> Outcome {
+    let mut engine = find_hax_engine(message_format)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = engine.stderr.take().unwrap();
+    let stderr_thread = std::thread::spawn(move || {
+        let mut contents = String::new();
+        let _ = stderr.read_to_string(&mut contents);
+        contents
+    });
+
+    let mut outcome = Outcome {
+        exit_code: 0,
+        diagnostics: vec![],
+        output: String::new(),
+    };
+    {
+        // The engine might crash at any point: errors on its standard
+        // input are thus ignored.
+        let mut stdin = std::io::BufWriter::new(engine.stdin.take().unwrap());
+        let _ = stdin
+            .write_all(input)
+            .and_then(|_| stdin.write_all(b"\n"))
+            .and_then(|_| stdin.flush());
+        macro_rules! send {
+            ($value:expr) => {
+                let _ = serde_json::to_writer(&mut stdin, $value)
+                    .map_err(std::io::Error::from)
+                    .and_then(|_| stdin.write_all(b"\n"))
+                    .and_then(|_| stdin.flush());
+            };
+        }
+        let stdout = std::io::BufReader::new(engine.stdout.take().unwrap());
+        for msg in stdout.json_lines::<FromEngine>() {
+            let Ok(msg) = msg else { break };
+            match msg {
+                FromEngine::Exit => break,
+                FromEngine::Diagnostic(diagnostic) => outcome.diagnostics.push(diagnostic),
+                FromEngine::File(file) => outcome.output += &file.contents,
+                FromEngine::PrettyPrintDiagnostic(diag) => {
+                    send!(&ToEngine::PrettyPrintedDiagnostic(format!("{}", diag)));
+                }
+                FromEngine::PrettyPrintRust(code) => {
+                    send!(&ToEngine::PrettyPrintedRust(pretty_print_rust(&code)));
+                }
+                FromEngine::Ping => {
+                    send!(&ToEngine::Pong);
+                }
+                _ => (),
+            }
+        }
+    }
+    outcome.exit_code = engine.wait().unwrap().code().unwrap_or(-1);
+    outcome.output += &stderr_thread.join().unwrap_or_default();
+    outcome
+}
+
+/// Finds a large subset of `candidates` that can be removed, i.e. for
+/// which `removable` holds. Chunks of `candidates` are removed
+/// greedily, starting with the whole set, and halving the size of the
+/// chunks until single elements are tried. `removable` is given every
+/// element removed so far.
+fn reduce_set<T: Clone>(mut candidates: Vec<T>, mut removable: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut removed: Vec<T> = vec![];
+    let mut chunk_size = candidates.len();
+    while chunk_size > 0 {
+        let mut i = 0;
+        while i < candidates.len() {
+            let end = (i + chunk_size).min(candidates.len());
+            let attempt: Vec<T> = removed.iter().chain(&candidates[i..end]).cloned().collect();
+            if removable(&attempt) {
+                removed = attempt;
+                candidates.drain(i..end);
+            } else {
+                i = end;
+            }
+        }
+        chunk_size = if chunk_size == 1 {
+            0
+        } else {
+            chunk_size.div_ceil(2)
+        };
+    }
+    removed
+}
+
+/// The kind of the syntax nodes that can be removed from a file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    /// An item, an associated item, or an item of a module.
+    Item,
+    /// A statement of a block.
+    Statement,
+}
+
+/// Removes syntax nodes from a file. Removable nodes are numbered in
+/// pre-order: every node is visited, removed or not, so that the
+/// numbering doesn't depend on the removed nodes.
+#[derive(Default)]
+struct Remover {
+    removed: BTreeSet<usize>,
+    /// The kind of each node, with the nearest removable node it
+    /// belongs to.
+    nodes: Vec<(NodeKind, Option<usize>)>,
+    parent: Option<usize>,
+}
+
+impl Remover {
+    fn retain<T>(
+        &mut self,
+        nodes: &mut Vec<T>,
+        kind: NodeKind,
+        mut visit: impl FnMut(&mut Self, &mut T),
+    ) {
+        let mut keep = vec![];
+        for node in nodes.iter_mut() {
+            let id = self.nodes.len();
+            self.nodes.push((kind, self.parent));
+            let parent = self.parent.replace(id);
+            visit(self, node);
+            self.parent = parent;
+            keep.push(!self.removed.contains(&id));
+        }
+        let mut keep = keep.into_iter();
+        nodes.retain(|_| keep.next().unwrap());
+    }
+
+    /// Is the node `id`, or one of the nodes it belongs to, removed?
+    fn is_removed(&self, mut id: usize) -> bool {
+        loop {
+            if self.removed.contains(&id) {
+                return true;
+            }
+            match self.nodes[id].1 {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+impl VisitMut for Remover {
+    fn visit_file_mut(&mut self, file: &mut syn::File) {
+        self.retain(&mut file.items, NodeKind::Item, Self::visit_item_mut)
+    }
+    fn visit_item_mod_mut(&mut self, module: &mut syn::ItemMod) {
+        if let Some((_, items)) = &mut module.content {
+            self.retain(items, NodeKind::Item, Self::visit_item_mut)
+        }
+    }
+    fn visit_item_impl_mut(&mut self, item: &mut syn::ItemImpl) {
+        self.retain(&mut item.items, NodeKind::Item, Self::visit_impl_item_mut)
+    }
+    fn visit_item_trait_mut(&mut self, item: &mut syn::ItemTrait) {
+        self.retain(&mut item.items, NodeKind::Item, Self::visit_trait_item_mut)
+    }
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        self.retain(&mut block.stmts, NodeKind::Statement, Self::visit_stmt_mut)
+    }
+}
+
+/// A source file of the crate being reduced.
+struct SourceFile {
+    path: PathBuf,
+    contents: String,
+    syntax: syn::File,
+}
+
+/// The sources of a copy of the crate being reduced. A node is
+/// designated by the index of its file and its number in that file
+/// (see `Remover`).
+struct Sources {
+    files: Vec<SourceFile>,
+}
+
+impl Sources {
+    /// Reads the files `paths` that can be parsed.
+    fn read(paths: impl Iterator<Item = PathBuf>) -> Self {
+        let files = paths
+            .filter_map(|path| {
+                let contents = fs::read_to_string(&path).ok()?;
+                let syntax = syn::parse_file(&contents).ok()?;
+                Some(SourceFile {
+                    path,
+                    contents,
+                    syntax,
+                })
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// The removable nodes of the files of kind `kind`, except the
+    /// ones that belong to a node of `removed`.
+    fn nodes(&self, kind: NodeKind, removed: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        self.files
+            .iter()
+            .enumerate()
+            .flat_map(|(i, file)| {
+                let mut remover = Remover {
+                    removed: removed
+                        .iter()
+                        .filter(|(j, _)| *j == i)
+                        .map(|(_, id)| *id)
+                        .collect(),
+                    ..Remover::default()
+                };
+                remover.visit_file_mut(&mut file.syntax.clone());
+                (0..remover.nodes.len())
+                    .filter(|id| remover.nodes[*id].0 == kind && !remover.is_removed(*id))
+                    .map(|id| (i, id))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Writes the files with the nodes `removed` removed. Files
+    /// without removed nodes are written as they were.
+    fn write(&self, removed: &[(usize, usize)]) {
+        for (i, file) in self.files.iter().enumerate() {
+            let removed: BTreeSet<usize> = removed
+                .iter()
+                .filter(|(j, _)| *j == i)
+                .map(|(_, id)| *id)
+                .collect();
+            let contents = if removed.is_empty() {
+                file.contents.clone()
+            } else {
+                let mut syntax = file.syntax.clone();
+                Remover {
+                    removed,
+                    ..Remover::default()
+                }
+                .visit_file_mut(&mut syntax);
+                prettyplease::unparse(&syntax)
+            };
+            fs::write(&file.path, contents).unwrap();
+        }
+    }
+}
+
+/// Copies the package directory `from` to `to`, leaving out the
+/// target directory and hidden files. The copy is made a workspace
+/// on its own, and the lock file of the original workspace is reused.
+fn copy_package(from: &Path, to: &Path, working_dir: &Path) {
+    fn copy_dir(from: &Path, to: &Path, skip: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap().filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name();
+            if name == "target" || name.to_string_lossy().starts_with('.') || path == skip {
+                continue;
+            }
+            if path.is_dir() {
+                copy_dir(&path, &to.join(&name), skip);
+            } else {
+                fs::copy(&path, to.join(&name)).unwrap();
+            }
+        }
+    }
+    copy_dir(from, to, to);
+    let manifest = to.join("Cargo.toml");
+    let contents = fs::read_to_string(&manifest).unwrap();
+    if !contents.lines().any(|line| line.trim() == "[workspace]") {
+        fs::write(&manifest, format!("{contents}\n[workspace]\n")).unwrap();
+    }
+    let lock = working_dir.join("Cargo.lock");
+    if lock.is_file() && !to.join("Cargo.lock").exists() {
+        fs::copy(lock, to.join("Cargo.lock")).unwrap();
+    }
+}
+
+/// Runs `cargo hax reduce`. Returns whether an error occurred.
+pub fn reduce(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> bool {
+    let Command::Reduce {
+        diagnostic,
+        exit_code,
+        pattern,
+        reduced_dir,
+        items_only,
+        backend,
+    } = options.command.clone()
+    else {
+        unreachable!()
+    };
+    if diagnostic.is_none() && exit_code.is_none() && pattern.is_none() {
+        eprintln!("hax: reduce: please describe the problem with `--diagnostic`, `--exit-code` or `--pattern`.");
+        return true;
+    }
+    let predicate = Predicate {
+        diagnostic,
+        exit_code,
+        pattern,
+    };
+    let backend = BackendOptions {
+        stats: false,
+        stats_html: None,
+        profile: false,
+        profile_output: None,
+        bug_report: None,
+        incremental: false,
+        debug_engine: None,
+        ..backend
+    };
+    let base_clauses = backend.translation_options.include_namespaces.clone();
+    let with_clauses = |clauses: Vec<InclusionClause>| BackendOptions {
+        translation_options: TranslationOptions {
+            include_namespaces: clauses,
+        },
+        ..backend.clone()
+    };
+    let mut runs = 0;
+    let mut check = |input: &CrateInput, backend: &BackendOptions<()>| {
+        runs += 1;
+        predicate.holds(&run_engine_quietly(
+            &input.engine_input(backend),
+            options.message_format,
+        ))
+    };
+
+    // Looks for a crate on which the problem reproduces
+    let available: HashSet<PathBuf> = haxmeta_files
+        .iter()
+        .filter_map(|msg| msg.path.canonicalize().ok())
+        .collect();
+    let Some((message, input)) = haxmeta_files.into_iter().find_map(|message| {
+        let input = CrateInput::read(&message, &available);
+        check(&input, &backend).then_some((message, input))
+    }) else {
+        eprintln!("hax: reduce: the problem does not reproduce.");
+        return true;
+    };
+
+    // Phase 1: excludes items with inclusion clauses. Items without
+    // names (e.g. `impl` blocks) cannot be designated by clauses.
+    let graph = crate::item_graph::ItemGraph::new(&input.items);
+    let candidates: Vec<String> = graph
+        .nodes
+        .iter()
+        .filter(|node| !node.excluded_by_attribute)
+        .filter(|node| !node.path.iter().any(|chunk| chunk.starts_with('{')))
+        .map(|node| node.path.join("::"))
+        .collect();
+    let exclusions = |paths: &[String]| -> Vec<InclusionClause> {
+        base_clauses
+            .iter()
+            .cloned()
+            .chain(paths.iter().map(|path| InclusionClause {
+                kind: InclusionKind::Excluded,
+                namespace: path.parse().unwrap(),
+                predicates: vec![],
+            }))
+            .collect()
+    };
+    let total = candidates.len();
+    let excluded = reduce_set(candidates, |paths| {
+        check(&input, &with_clauses(exclusions(paths)))
+    });
+    let clauses = exclusions(&excluded);
+    eprintln!(
+        "hax: reduce: {}/{total} items were excluded with inclusion clauses",
+        excluded.len()
+    );
+
+    let package_dir = &message.manifest_dir;
+    let reduced_dir = reduced_dir.unwrap_or_else(|| {
+        let mut name = package_dir.file_name().unwrap().to_os_string();
+        name.push("-reduced");
+        package_dir.with_file_name(name)
+    });
+    if reduced_dir.exists() {
+        eprintln!(
+            "hax: reduce: {} already exists, please remove it first.",
+            reduced_dir.display()
+        );
+        return true;
+    }
+    copy_package(package_dir, &reduced_dir, &message.working_dir);
+    fs::write(
+        reduced_dir.join(CLAUSES),
+        std::iter::once("# Inclusion clauses computed by `cargo hax reduce`".to_string())
+            .chain(clauses.iter().map(|clause| clause.to_string()))
+            .map(|line| line + "\n")
+            .collect::<String>(),
+    )
+    .unwrap();
+
+    if !items_only {
+        // Phase 2: edits the sources of a copy of the crate. The
+        // copy is rebuilt with the same options, except for its
+        // manifest.
+        let rebuild_options = Options {
+            cargo_flags: ["--manifest-path".to_string()]
+                .into_iter()
+                .chain([reduced_dir.join("Cargo.toml").display().to_string()])
+                .chain(options.cargo_flags.iter().cloned())
+                .collect(),
+            message_format: MessageFormat::Human,
+            ..options.clone()
+        };
+        let backend = with_clauses(clauses);
+        let crate_name = input.crate_name.clone();
+        let mut test = |sources: &Sources, removed: &[(usize, usize)]| {
+            sources.write(removed);
+            let (haxmeta_files, exit_code) = compute_haxmeta_files(&rebuild_options, true);
+            if exit_code != 0 {
+                return false;
+            }
+            let available: HashSet<PathBuf> = haxmeta_files
+                .iter()
+                .filter_map(|msg| msg.path.canonicalize().ok())
+                .collect();
+            haxmeta_files.iter().any(|message| {
+                let input = CrateInput::read(message, &available);
+                input.crate_name == crate_name && check(&input, &backend)
+            })
+        };
+
+        let source_files: BTreeSet<PathBuf> = input
+            .items
+            .iter()
+            .filter_map(|item| item.span.filename.to_path())
+            .filter_map(|path| {
+                let path = message.working_dir.join(path);
+                Some(reduced_dir.join(path.strip_prefix(package_dir).ok()?))
+            })
+            .collect();
+        let sources = Sources::read(source_files.into_iter());
+        if !test(&sources, &[]) {
+            eprintln!(
+                "hax: reduce: the problem does not reproduce on the copy of the crate, its sources are left untouched."
+            );
+        } else {
+            let items = sources.nodes(NodeKind::Item, &[]);
+            let total = items.len();
+            let removed = reduce_set(items, |removed| test(&sources, removed));
+            eprintln!(
+                "hax: reduce: {}/{total} items were removed from the sources",
+                removed.len()
+            );
+            let statements = sources.nodes(NodeKind::Statement, &removed);
+            let total = statements.len();
+            let mut all_removed = removed.clone();
+            let removed = reduce_set(statements, |statements| {
+                let removed: Vec<_> = removed.iter().chain(statements).cloned().collect();
+                test(&sources, &removed)
+            });
+            eprintln!(
+                "hax: reduce: {}/{total} statements were removed from the sources",
+                removed.len()
+            );
+            all_removed.extend(removed);
+            sources.write(&all_removed);
+        }
+    }
+
+    eprintln!(
+        "hax: reduce: the reduced crate was written to {} ({runs} runs of the engine), translate it with `cargo hax into -i @{CLAUSES} ...`",
+        reduced_dir.display()
+    );
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduce_set_keeps_needed_elements() {
+        let mut calls = 0;
+        let mut removed = reduce_set((0..16).collect(), |removed| {
+            calls += 1;
+            !removed.contains(&3) && !removed.contains(&11)
+        });
+        removed.sort();
+        assert_eq!(removed, [0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 15]);
+        assert!(calls < 40, "{calls} calls");
+        assert_eq!(reduce_set((0..4).collect(), |_| true), [0, 1, 2, 3]);
+        assert!(reduce_set((0..4).collect(), |_| false).is_empty());
+    }
+
+    #[test]
+    fn predicate() {
+        let outcome = Outcome {
+            exit_code: 1,
+            diagnostics: vec![],
+            output: "thread panicked: boom".to_string(),
+        };
+        let predicate = |exit_code, pattern: Option<&str>| Predicate {
+            diagnostic: None,
+            exit_code,
+            pattern: pattern.map(String::from),
+        };
+        assert!(predicate(None, None).holds(&outcome));
+        assert!(predicate(Some(1), Some("boom")).holds(&outcome));
+        assert!(!predicate(Some(0), Some("boom")).holds(&outcome));
+        assert!(!predicate(Some(1), Some("bang")).holds(&outcome));
+        let predicate = Predicate {
+            diagnostic: Some("HAX0001".to_string()),
+            exit_code: None,
+            pattern: None,
+        };
+        assert!(!predicate.holds(&outcome));
+    }
+
+    #[test]
+    fn remove_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "fn f() { let x = 1; g(x); }\nmod m { fn g() {} fn h() {} }\n",
+        )
+        .unwrap();
+        let sources = Sources::read(std::iter::once(path.clone()));
+        // `f`, `m`, `g` and `h`, numbered in pre-order
+        let items = sources.nodes(NodeKind::Item, &[]);
+        assert_eq!(items, [(0, 0), (0, 3), (0, 4), (0, 5)]);
+        assert_eq!(sources.nodes(NodeKind::Statement, &[]), [(0, 1), (0, 2)]);
+        // The statements of a removed item are not removable anymore
+        assert!(sources.nodes(NodeKind::Statement, &[(0, 0)]).is_empty());
+
+        sources.write(&[(0, 2), (0, 4)]);
+        let syntax = syn::parse_file(&fs::read_to_string(&path).unwrap()).unwrap();
+        let expected = syn::parse_file("fn f() { let x = 1; } mod m { fn h() {} }").unwrap();
+        assert_eq!(
+            prettyplease::unparse(&syntax),
+            prettyplease::unparse(&expected)
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Translation statistics: how many items of a crate were translated
+//! successfully by the engine, per module and per kind of diagnostic.
+//! Those statistics can also be rendered as a self-contained HTML
+//! report, which lists every item with its errors.
+
+use crate::incremental::module_of;
+use crate::item_graph::path_of;
+use hax_frontend_exporter::{DefId, Span};
+use hax_types::diagnostics::message::{KindStats, ModuleStats};
+use hax_types::diagnostics::Diagnostics;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The name of the variant of an enum value, e.g. `Unimplemented`.
+pub fn variant_name(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::Object(map)) => map.keys().next().cloned(),
+        Ok(serde_json::Value::String(name)) => Some(name),
+        _ => None,
+    }
+    .unwrap_or_default()
+}
+
+/// Removes the ANSI escape sequences (colors) from a string.
+fn strip_ansi(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Percentage of `n` out of `total`.
+pub fn percent(n: usize, total: usize) -> usize {
+    if total == 0 {
+        100
+    } else {
+        n * 100 / total
+    }
+}
+
+/// An item processed by the engine.
+pub struct ItemReport {
+    pub path: String,
+    pub module: String,
+    /// The kind of the item (e.g. `Fn`), if known.
+    pub kind: String,
+    /// The source file of the item and the line it starts at.
+    pub source: Option<(PathBuf, usize)>,
+    /// The errors reported on the item, as plain text.
+    pub errors: Vec<(String, String)>,
+}
+
+/// The items of a crate processed by the engine, with the files the
+/// engine produced.
+pub struct CrateReport {
+    pub crate_name: String,
+    pub items: Vec<ItemReport>,
+    pub diagnostics: Vec<Diagnostics>,
+    /// The files produced by the engine.
+    pub files: Vec<PathBuf>,
+}
+
+impl CrateReport {
+    /// Builds the report of a crate. `infos` gives the kind and span
+    /// of the items given to the engine; `errors_per_item` lists the
+    /// items processed by the engine.
+    pub fn new(
+        crate_name: String,
+        infos: &HashMap<DefId, (String, Span)>,
+        errors_per_item: &HashMap<DefId, usize>,
+        diagnostics: Vec<Diagnostics>,
+        working_dir: &Path,
+        files: Vec<PathBuf>,
+    ) -> Self {
+        let mut items: Vec<ItemReport> = errors_per_item
+            .keys()
+            .map(|def_id| {
+                let info = infos.get(def_id);
+                ItemReport {
+                    path: path_of(def_id).join("::"),
+                    module: module_of(def_id),
+                    kind: info.map(|(kind, _)| kind.clone()).unwrap_or_default(),
+                    source: info.and_then(|(_, span)| {
+                        let path = span.filename.to_path()?;
+                        Some((working_dir.join(path), span.lo.line))
+                    }),
+                    errors: diagnostics
+                        .iter()
+                        .filter(|diagnostic| diagnostic.owner_id.as_ref() == Some(def_id))
+                        .map(|diagnostic| {
+                            (diagnostic.kind.code(), strip_ansi(&diagnostic.to_string()))
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        Self {
+            crate_name,
+            items,
+            diagnostics,
+            files,
+        }
+    }
+
+    /// Number of items, and of items translated without errors, per
+    /// module.
+    pub fn per_module(&self) -> Vec<ModuleStats> {
+        let mut modules: BTreeMap<&str, ModuleStats> = BTreeMap::new();
+        for item in &self.items {
+            let stats = modules.entry(&item.module).or_insert_with(|| ModuleStats {
+                module: item.module.clone(),
+                items: 0,
+                successful: 0,
+            });
+            stats.items += 1;
+            if item.errors.is_empty() {
+                stats.successful += 1;
+            }
+        }
+        modules.into_values().collect()
+    }
+
+    /// Number of diagnostics per kind, most frequent first.
+    pub fn per_kind(&self) -> Vec<KindStats> {
+        let mut kinds: BTreeMap<String, KindStats> = BTreeMap::new();
+        for diagnostic in &self.diagnostics {
+            kinds
+                .entry(diagnostic.kind.code())
+                .or_insert_with(|| KindStats {
+                    code: diagnostic.kind.code(),
+                    name: variant_name(&diagnostic.kind),
+                    count: 0,
+                })
+                .count += 1;
+        }
+        let mut kinds: Vec<_> = kinds.into_values().collect();
+        kinds.sort_by_key(|kind| std::cmp::Reverse(kind.count));
+        kinds
+    }
+
+    /// The generated file of a module, if any. Backends name files
+    /// after modules (e.g. `Mycrate.Some_module.fst`): a file matches
+    /// a module when their names agree up to case and separators.
+    fn file_of_module(&self, module: &str) -> Option<&PathBuf> {
+        let normalize = |s: &str| s.to_lowercase().replace("::", "_").replace(['.', '-'], "_");
+        let module = normalize(module);
+        self.files.iter().find(|file| {
+            file.file_stem()
+                .is_some_and(|stem| normalize(&stem.to_string_lossy()) == module)
+        })
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn file_url(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    escape(&format!("file://{}", path.display()))
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; }
+.ok { color: #1a7f37; }
+.ko { color: #cf222e; }
+.bar { width: 10em; height: 0.8em; background: #f3d0d0; }
+.bar > div { height: 100%; background: #7cc48a; }
+pre { white-space: pre-wrap; margin: 0.3em 0; }
+";
+
+/// Renders the reports of some crates as a self-contained HTML page.
+pub fn render_html(reports: &[CrateReport]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>hax translation report</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>hax translation report</h1>\n"
+    );
+    for report in reports {
+        let total = report.items.len();
+        let successful = report.items.iter().filter(|i| i.errors.is_empty()).count();
+        html += &format!(
+            "<h2>Crate <code>{}</code></h2>\n<p>{successful}/{total} items were successfully translated ({}%).</p>\n",
+            escape(&report.crate_name),
+            percent(successful, total)
+        );
+
+        html += "<h3>Modules</h3>\n<table>\n<tr><th>Module</th><th>Items</th><th>Translated</th><th></th><th>Generated file</th></tr>\n";
+        for module in report.per_module() {
+            let percent = percent(module.successful, module.items);
+            let file = match report.file_of_module(&module.module) {
+                Some(file) => format!(
+                    "<a href=\"{}\">{}</a>",
+                    file_url(file),
+                    escape(&file.display().to_string())
+                ),
+                None => String::new(),
+            };
+            html += &format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{} ({percent}%)</td><td><div class=\"bar\"><div style=\"width: {percent}%\"></div></div></td><td>{file}</td></tr>\n",
+                escape(&module.module),
+                module.items,
+                module.successful
+            );
+        }
+        html += "</table>\n";
+
+        let per_kind = report.per_kind();
+        if !per_kind.is_empty() {
+            html += "<h3>Diagnostics</h3>\n<table>\n<tr><th>Code</th><th>Kind</th><th>Count</th></tr>\n";
+            for kind in per_kind {
+                html += &format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape(&kind.code),
+                    escape(&kind.name),
+                    kind.count
+                );
+            }
+            html += "</table>\n";
+        }
+
+        html += "<h3>Items</h3>\n<table>\n<tr><th>Item</th><th>Kind</th><th>Status</th><th>Source</th><th>Errors</th></tr>\n";
+        for item in &report.items {
+            let status = if item.errors.is_empty() {
+                "<span class=\"ok\">translated</span>".to_string()
+            } else {
+                format!(
+                    "<span class=\"ko\">{} error{}</span>",
+                    item.errors.len(),
+                    if item.errors.len() > 1 { "s" } else { "" }
+                )
+            };
+            let source = match &item.source {
+                Some((path, line)) => format!(
+                    "<a href=\"{}\">{}:{line}</a>",
+                    file_url(path),
+                    escape(&path.display().to_string())
+                ),
+                None => String::new(),
+            };
+            let errors = item
+                .errors
+                .iter()
+                .map(|(code, message)| format!("<pre>[{}] {}</pre>", escape(code), escape(message)))
+                .collect::<Vec<_>>()
+                .concat();
+            html += &format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{status}</td><td>{source}</td><td>{errors}</td></tr>\n",
+                escape(&item.path),
+                escape(&item.kind)
+            );
+        }
+        html += "</table>\n";
+    }
+    html += "</body>\n</html>\n";
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hax_types::diagnostics::Kind;
+
+    fn item(path: &str, module: &str, errors: usize) -> ItemReport {
+        ItemReport {
+            path: path.to_string(),
+            module: module.to_string(),
+            kind: "Fn".to_string(),
+            source: None,
+            errors: (0..errors)
+                .map(|_| ("HAX0001".to_string(), "<unimplemented>".to_string()))
+                .collect(),
+        }
+    }
+
+    fn diagnostic(kind: Kind) -> Diagnostics {
+        Diagnostics {
+            kind,
+            span: vec![],
+            context: "test".to_string(),
+            owner_id: None,
+        }
+    }
+
+    fn report() -> CrateReport {
+        CrateReport {
+            crate_name: "k".to_string(),
+            items: vec![
+                item("k::f", "k", 0),
+                item("k::m::g", "k::m", 2),
+                item("k::m::h", "k::m", 0),
+            ],
+            diagnostics: vec![
+                diagnostic(Kind::UnsafeBlock),
+                diagnostic(Kind::UnallowedMutRef),
+                diagnostic(Kind::UnallowedMutRef),
+            ],
+            files: vec![
+                PathBuf::from("proofs/K.fst"),
+                PathBuf::from("proofs/K.M.fst"),
+            ],
+        }
+    }
+
+    #[test]
+    fn helpers() {
+        assert_eq!(strip_ansi("\x1b[1;31merror\x1b[0m: x"), "error: x");
+        assert_eq!(percent(1, 3), 33);
+        assert_eq!(percent(0, 0), 100);
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+        assert_eq!(variant_name(&Kind::UnsafeBlock), "UnsafeBlock");
+        let kind = Kind::AssertionFailure {
+            details: String::new(),
+        };
+        assert_eq!(variant_name(&kind), "AssertionFailure");
+    }
+
+    #[test]
+    fn per_module() {
+        let modules: Vec<_> = report()
+            .per_module()
+            .into_iter()
+            .map(|stats| (stats.module, stats.items, stats.successful))
+            .collect();
+        assert_eq!(
+            modules,
+            [("k".to_string(), 1, 1), ("k::m".to_string(), 2, 1)]
+        );
+    }
+
+    #[test]
+    fn per_kind() {
+        let kinds: Vec<_> = report()
+            .per_kind()
+            .into_iter()
+            .map(|stats| (stats.name, stats.count))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("UnallowedMutRef".to_string(), 2),
+                ("UnsafeBlock".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn file_of_module() {
+        let report = report();
+        assert_eq!(
+            report.file_of_module("k::m"),
+            Some(&PathBuf::from("proofs/K.M.fst"))
+        );
+        assert_eq!(report.file_of_module("k::n"), None);
+    }
+
+    #[test]
+    fn html() {
+        let html = render_html(&[report()]);
+        assert!(html.contains("2/3 items were successfully translated (66%)"));
+        assert!(html.contains("<pre>[HAX0001] &lt;unimplemented&gt;</pre>"));
+        assert!(html.contains("<span class=\"ko\">2 errors</span>"));
+    }
+}
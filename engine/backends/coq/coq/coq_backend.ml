@@ -98,6 +98,24 @@ struct
   let default_string_for s = "TODO: please implement the method `" ^ s ^ "`"
   let default_document_for = default_string_for >> string
 
+  (** Prints the plain comments out of a list of attributes, each on its
+      own lines *)
+  let pcomments (attrs : attrs) : document =
+    concat_map
+      (fun (attr : attr) ->
+        match attr.kind with
+        | Comment { body; _ } ->
+            (* Coq comments nest: their delimiters are broken apart *)
+            let body =
+              String.substr_replace_all ~pattern:"(*" ~with_:"( *" body
+              |> String.substr_replace_all ~pattern:"*)" ~with_:"* )"
+            in
+            separate_map hardline string
+              (String.split_lines ("(*" ^ body ^ "*)"))
+            ^^ hardline
+        | _ -> empty)
+      attrs
+
   module CoqNotation = struct
     let definition_struct keyword n name generics params typ body =
       keyword ^^ space ^^ name ^^ generics
@@ -136,7 +154,8 @@ struct
         | LogicalOp op -> (
             match op with And -> string "andb" | Or -> string "orb")
 
-      method arm ~arm ~span:_ = arm#p
+      method arm ~arm ~span:_ ~attrs =
+        pcomments attrs#v ^^ string "|" ^^ space ^^ arm#p
 
       method arm' ~super:_ ~arm_pat ~body ~guard:_ =
         arm_pat#p ^^ space ^^ string "=>" ^^ nest 2 (break 1 ^^ body#p)
@@ -239,9 +258,10 @@ struct
         ^^ nest 2
              (break 1 ^^ match else_ with Some x -> x#p | None -> string "tt")
 
-      method expr'_Let ~super:_ ~monadic:_ ~lhs ~rhs ~body =
-        string "let" ^^ space ^^ lhs#p ^^ space ^^ string ":=" ^^ space ^^ rhs#p
-        ^^ space ^^ string "in" ^^ break 1 ^^ body#p
+      method expr'_Let ~super:_ ~monadic:_ ~lhs ~rhs ~body ~attrs =
+        pcomments attrs#v ^^ string "let" ^^ space ^^ lhs#p ^^ space
+        ^^ string ":=" ^^ space ^^ rhs#p ^^ space ^^ string "in" ^^ break 1
+        ^^ body#p
 
       method expr'_Literal ~super:_ x2 = x2#p
       method expr'_LocalVar ~super:_ x2 = x2#p
@@ -263,7 +283,7 @@ struct
       method expr'_Match ~super:_ ~scrutinee ~arms =
         string "match" ^^ space ^^ scrutinee#p ^^ space ^^ string "with"
         ^^ break 1
-        ^^ concat_map (fun x -> string "|" ^^ space ^^ x#p ^^ break 1) arms
+        ^^ concat_map (fun x -> x#p ^^ break 1) arms
         ^^ string "end"
 
       method expr'_QuestionMark ~super:_ ~e:_ ~return_typ:_ ~witness =
@@ -337,8 +357,9 @@ struct
       method impl_expr_kind_Self = default_document_for "impl_expr_kind_Self"
       method impl_ident ~goal ~name:_ = goal#p
 
-      method impl_item ~ii_span:_ ~ii_generics:_ ~ii_v ~ii_ident ~ii_attrs:_ =
-        ii_ident#p ^^ space ^^ string ":=" ^^ space ^^ ii_v#p ^^ semi
+      method impl_item ~ii_span:_ ~ii_generics:_ ~ii_v ~ii_ident ~ii_attrs =
+        pcomments ii_attrs#v ^^ ii_ident#p ^^ space ^^ string ":=" ^^ space
+        ^^ ii_v#p ^^ semi
 
       method impl_item'_IIFn ~body ~params =
         if List.length params == 0 then body#p
@@ -349,7 +370,7 @@ struct
           ^^ nest 2 (break 1 ^^ body#p)
 
       method impl_item'_IIType ~typ ~parent_bounds:_ = typ#p
-      method item ~v ~span:_ ~ident:_ ~attrs:_ = v#p ^^ break 1
+      method item ~v ~span:_ ~ident:_ ~attrs = pcomments attrs#v ^^ v#p ^^ break 1
 
       method item'_Alias ~super:_ ~name ~item =
         string "Notation" ^^ space ^^ string "\"'" ^^ name#p ^^ string "'\""
@@ -623,7 +644,7 @@ struct
       method trait_goal ~trait ~args =
         trait#p ^^ concat_map (fun x -> space ^^ x#p) args
 
-      method trait_item ~ti_span:_ ~ti_generics ~ti_v ~ti_ident ~ti_attrs:_ =
+      method trait_item ~ti_span:_ ~ti_generics ~ti_v ~ti_ident ~ti_attrs =
         let _, params, constraints = ti_generics#v in
         let generic_params = concat_map (fun x -> space ^^ x#p) params in
         let filter_constraints = function
@@ -652,7 +673,8 @@ struct
                ~f:(fun x -> not (filter_constraints x))
                (List.map ~f:(fun x -> x#v) constraints))
         in
-        ti_ident#p ^^ generic_params ^^ generic_constraints_other ^^ space
+        pcomments ti_attrs#v ^^ ti_ident#p ^^ generic_params
+        ^^ generic_constraints_other ^^ space
         ^^ (match ti_v#v with TIDefault _ -> string ":=" | _ -> colon)
         ^^ space ^^ ti_v#p ^^ semi ^^ generic_constraints_self
 
@@ -139,8 +139,53 @@ struct
   let pat_to_string : F.AST.pattern -> string =
     FStar_Parser_ToDocument.pat_to_document >> doc_to_string
 
-  let decl_to_string : F.AST.decl -> string =
-    FStar_Parser_ToDocument.decl_to_document >> doc_to_string
+  (** Comments of the statements and match arms of the item. The F*
+      printer emits each of them right before the first term starting
+      on or after its line, see [with_comments]. *)
+  let term_comments : (string * F.Range.range) list ref = ref []
+
+  let decl_to_string (d : F.AST.decl) : string =
+    FStar_Parser_ToDocument.comment_stack := !term_comments;
+    let s = FStar_Parser_ToDocument.decl_to_document d |> doc_to_string in
+    FStar_Parser_ToDocument.comment_stack := [];
+    s
+
+  (** Prints the doc comments and comments of [attrs] as F* comments *)
+  let pcomments (attrs : attrs) : string list =
+    attrs
+    |> List.filter_map ~f:(fun (attr : attr) ->
+           match attr.kind with
+           | DocComment { kind; body } -> Some (kind, body, true)
+           | Comment { kind; body } -> Some (kind, body, false)
+           | _ -> None)
+    |> List.map ~f:(fun (kind, string, is_doc) ->
+           match kind with
+           | DCKLine ->
+               let prefix = if is_doc then "///" else "//" in
+               String.split_lines string
+               |> List.map ~f:(fun s -> prefix ^ s)
+               |> String.concat_lines
+           | DCKBlock ->
+               let string =
+                 String.substr_replace_all ~pattern:"*)" ~with_:"* )" string
+               in
+               if is_doc then "(**" ^ string ^ "*)" else "(*" ^ string ^ "*)")
+
+  (** Attaches the comments of [attrs] to the term built by [term], by
+      giving it a fake range on a line after the ones of the previous
+      comments. The comments are registered before [term] runs, so that
+      the comments of nested terms come after them. *)
+  let with_comments (attrs : attrs) (term : unit -> F.AST.term) : F.AST.term
+      =
+    match List.concat_map ~f:String.split_lines (pcomments attrs) with
+    | [] -> term ()
+    | comments ->
+        let line = Z.of_int (List.length !term_comments + 1) in
+        let pos = F.Range.mk_pos line Z.zero in
+        let range = F.Range.mk_range "comments" pos pos in
+        term_comments :=
+          !term_comments @ List.map ~f:(fun c -> (c, range)) comments;
+        { (term ()) with F.AST.range }
 
   let pprim_ident (span : span) (id : primitive_ident) =
     match id with
@@ -657,7 +702,8 @@ struct
              ( NoLetQualifier,
                [ (None, (pat, body)) ],
                F.term @@ F.AST.Seq (assertion, array) )
-    | Let { lhs; rhs; body; monadic = Some (monad, _) } ->
+    | Let { lhs; rhs; body; monadic = Some (monad, _); attrs } ->
+        with_comments attrs @@ fun () ->
         let p =
           F.pat @@ F.AST.PatAscribed (ppat lhs, (pty lhs.span lhs.typ, None))
         in
@@ -669,8 +715,10 @@ struct
           | MOption -> "?"
           | MException _ -> "!"
         in
-        F.term @@ F.AST.LetOperator ([ (F.id op, p, pexpr rhs) ], pexpr body)
-    | Let { lhs; rhs; body; monadic = None } ->
+        let rhs = pexpr rhs in
+        F.term @@ F.AST.LetOperator ([ (F.id op, p, rhs) ], pexpr body)
+    | Let { lhs; rhs; body; monadic = None; attrs } ->
+        with_comments attrs @@ fun () ->
         let p =
           (* TODO: temp patch that remove annotation when we see an associated type *)
           if [%matches? TAssociatedType _] @@ U.remove_tuple1 lhs.typ then
@@ -678,12 +726,12 @@ struct
           else
             F.pat @@ F.AST.PatAscribed (ppat lhs, (pty lhs.span lhs.typ, None))
         in
-        F.term
-        @@ F.AST.Let (NoLetQualifier, [ (None, (p, pexpr rhs)) ], pexpr body)
+        let rhs = pexpr rhs in
+        F.term @@ F.AST.Let (NoLetQualifier, [ (None, (p, rhs)) ], pexpr body)
     | EffectAction _ -> .
     | Match { scrutinee; arms } ->
-        F.term
-        @@ F.AST.Match (pexpr scrutinee, None, None, List.map ~f:parm arms)
+        let scrutinee = pexpr scrutinee in
+        F.term @@ F.AST.Match (scrutinee, None, None, List.map ~f:parm arms)
     | Ascription { e; typ } ->
         F.term @@ F.AST.Ascribed (pexpr e, pty e.span typ, None, false)
     | Construct { constructor = `TupleCons 1; fields = [ (_, e') ]; base } ->
@@ -761,7 +809,8 @@ struct
       contents
     |> String.concat
 
-  and parm { arm = { arm_pat; body } } = (ppat arm_pat, None, pexpr body)
+  and parm { arm = { arm_pat; body }; attrs } =
+    (ppat arm_pat, None, with_comments attrs @@ fun () -> pexpr body)
 
   module FStarBinder = struct
     type kind = Implicit | Tcresolve | Explicit
@@ -910,21 +959,9 @@ struct
         in
         F.mk_e_app effect (if is_lemma then args else typ :: args)
 
-  (** Prints doc comments out of a list of attributes *)
+  (** Prints doc comments and plain comments out of a list of attributes *)
   let pdoc_comments attrs =
-    attrs
-    |> List.filter_map ~f:(fun (attr : attr) ->
-           match attr.kind with
-           | DocComment { kind; body } -> Some (kind, body)
-           | _ -> None)
-    |> List.map ~f:(fun (kind, string) ->
-           match kind with
-           | DCKLine ->
-               String.split_lines string
-               |> List.map ~f:(fun s -> "///" ^ s)
-               |> String.concat_lines
-           | DCKBlock -> "(**" ^ string ^ "*)")
-    |> List.map ~f:(fun s -> `VerbatimIntf (s, `NoNewline))
+    pcomments attrs |> List.map ~f:(fun s -> `VerbatimIntf (s, `NoNewline))
 
   let rec pitem (e : item) :
       [> `Impl of F.AST.decl
@@ -1645,7 +1682,7 @@ let strings_of_item (bo : BackendOptions.t) m items (item : item) :
                chunks = namespace.chunks @ [ Glob One; Glob Many ];
              }
            in
-           Concrete_ident.matches_namespace namespace item.ident)
+           Concrete_ident.matches_clause { clause with namespace } item.ident)
     |> Option.map ~f:(fun (clause : Types.inclusion_clause) -> clause.kind)
     |> Option.value ~default:(Types.Excluded : Types.inclusion_kind)
   in
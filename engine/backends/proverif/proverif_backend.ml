@@ -475,8 +475,7 @@ module Make (Options : OPTS) : MAKE = struct
           let assume_item =
             List.rev Options.options.assume_items
             |> List.find ~f:(fun (clause : Types.inclusion_clause) ->
-                   let namespace = clause.namespace in
-                   Concrete_ident.matches_namespace namespace item.ident)
+                   Concrete_ident.matches_clause clause item.ident)
             |> Option.map ~f:(fun (clause : Types.inclusion_clause) ->
                    match clause.kind with Types.Excluded -> false | _ -> true)
             |> Option.value ~default:false
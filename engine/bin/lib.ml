@@ -24,15 +24,20 @@ module Attrs = Attr_payloads.MakeBase (Error)
 let import_thir_items (include_clauses : Types.inclusion_clause list)
     (items : Types.item_for__decorated_for__expr_kind list) : Ast.Rust.item list
     =
+  List.iter
+    ~f:(fun (item : Types.item_for__decorated_for__expr_kind) ->
+      Concrete_ident.ItemFactsStore.register item.owner_id
+        (Import_thir.item_facts item))
+    items;
+  let total = List.length items in
   let imported_items =
-    List.map
-      ~f:(fun item ->
+    List.mapi
+      ~f:(fun i item ->
         let ident = Concrete_ident.(of_def_id ~value:true item.owner_id) in
         let most_precise_clause =
           (* Computes the include clause that apply to `item`, if any *)
           List.filter
-            ~f:(fun clause ->
-              Concrete_ident.matches_namespace clause.Types.namespace ident)
+            ~f:(fun clause -> Concrete_ident.matches_clause clause ident)
             include_clauses
           |> List.last
         in
@@ -43,7 +48,9 @@ let import_thir_items (include_clauses : Types.inclusion_clause list)
             most_precise_clause
           |> Option.value ~default:false
         in
-        Import_thir.import_item ~type_only item)
+        let imported = Import_thir.import_item ~type_only item in
+        Profiling.items_progress ~processed:(i + 1) ~total;
+        imported)
       items
     |> List.map ~f:snd
   in
@@ -96,7 +103,10 @@ let run (options : Types.engine_options) : Types.output =
       (backend_options : options_type) : Types.file list =
     let open M in
     Concrete_ident.ImplInfoStore.init
-      (Concrete_ident_generated.impl_infos @ options.impl_infos);
+      (Concrete_ident_generated.impl_infos @ options.impl_infos
+      @ List.concat_map
+          ~f:(fun (dep : Types.dependency_context) -> dep.impl_infos)
+          options.dependencies);
     let include_clauses =
       options.backend.translation_options.include_namespaces
     in
@@ -115,7 +125,28 @@ let run (options : Types.engine_options) : Types.output =
         m "Applying phase for backend %s"
           ([%show: Diagnostics.Backend.t] M.backend));
     let items = apply_phases backend_options items in
-    let with_items = Attrs.with_items items in
+    let dependency_items : AST.item list =
+      (* The items of the dependencies are only a read-only context:
+         they are imported without bodies, and the errors they raise
+         are not reported. *)
+      let import () =
+        List.concat_map
+          ~f:(fun (dep : Types.dependency_context) -> dep.items)
+          options.dependencies
+        |> List.concat_map ~f:(fun item ->
+               Import_thir.import_item ~type_only:true item |> snd |> fst)
+        |> apply_phases backend_options
+      in
+      Profiling.profile (Other "dependencies")
+        (List.length options.dependencies) (fun _ ->
+          try Diagnostics.Core.capture import |> fst
+          with e ->
+            Logs.info (fun m ->
+                m "Could not import the items of the dependencies: %s"
+                  (Exn.to_string e));
+            [])
+    in
+    let with_items = Attrs.with_items (items @ dependency_items) in
     let bundles, _ =
       let module DepGraph = Dependencies.Make (InputLanguage) in
       DepGraph.recursive_bundles items
@@ -204,7 +235,8 @@ Please reinstall hax.
   |> List.iter ~f:(fun (id, json) ->
          Hashtbl.add_exn Types.cache_map ~key:id ~data:(`JSON json));
   let options = [%of_yojson: Types.engine_options] json in
-  Profiling.enabled := options.backend.profile;
+  Profiling.enabled :=
+    options.backend.profile || Option.is_some options.backend.profile_output;
   options
 
 (** Entrypoint of the engine. Assumes `Hax_io.init` was called. *)
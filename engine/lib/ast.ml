@@ -46,6 +46,9 @@ type global_ident = Global_ident.t
 type attr_kind =
   | Tool of { path : string; tokens : string }
   | DocComment of { kind : doc_comment_kind; body : string }
+  | Comment of { kind : doc_comment_kind; body : string }
+      (** A non-doc comment, written right before the item, field,
+          match arm or statement *)
 
 and attr = { kind : attr_kind; span : span }
 and doc_comment_kind = DCKLine | DCKBlock
@@ -276,6 +279,7 @@ functor
           lhs : pat;
           rhs : expr;
           body : expr;
+          attrs : attrs;
         }
       | Block of { e : expr; safety_mode : safety_kind; witness : F.block }
         (* Corresponds to `{e}`: this is important for places *)
@@ -393,7 +397,7 @@ functor
     (* OCaml + visitors is not happy with `pat`... hence `arm_pat`... *)
     and arm' = { arm_pat : pat; body : expr; guard : guard option }
 
-    and arm = { arm : arm'; span : span }
+    and arm = { arm : arm'; span : span; attrs : attrs }
     [@@deriving show, yojson, hash, compare, sexp, hash, eq]
 
     type generic_param = {
@@ -63,7 +63,7 @@ module Make (F : Features.T) = struct
     let pat_PBinding ~typ = pat_PBinding ~inner_typ:typ ~typ
 
     let arm ~span arm_pat ?(guard = None) body =
-      { arm = { arm_pat; body; guard }; span }
+      { arm = { arm_pat; body; guard }; span; attrs = [] }
 
     let pat_Constructor_CF ~(span : span) ~(typ : ty)
         (cf : [ `Break | `Continue ]) (pat : pat) =
@@ -444,7 +444,7 @@ module Make (F : Features.T) = struct
 
           method! visit_expr' env e =
             match e with
-            | Let { monadic = _; lhs; rhs; body } ->
+            | Let { monadic = _; lhs; rhs; body; _ } ->
                 super#visit_expr env rhs
                 ++ shadows ~env [ lhs ] body super#visit_expr
             | Loop { kind; state; body; _ } ->
@@ -783,9 +783,9 @@ module Make (F : Features.T) = struct
         | Unequal_lengths -> false)
     | _ -> false
 
-  let make_let (lhs : pat) (rhs : expr) (body : expr) =
+  let make_let ?(attrs = []) (lhs : pat) (rhs : expr) (body : expr) =
     if pat_is_expr lhs body then rhs
-    else { body with e = Let { monadic = None; lhs; rhs; body } }
+    else { body with e = Let { monadic = None; lhs; rhs; body; attrs } }
 
   let make_lets (lbs : (pat * expr) list) (body : expr) =
     List.fold_right ~init:body
@@ -1114,7 +1114,7 @@ module Make (F : Features.T) = struct
 
   let rec collect_let_bindings' (e : expr) : (pat * expr * ty) list * expr =
     match e.e with
-    | Let { monadic = _; lhs; rhs; body } ->
+    | Let { monadic = _; lhs; rhs; body; _ } ->
         let bindings, body = collect_let_bindings' body in
         ((lhs, rhs, e.typ) :: bindings, body)
     | _ -> ([], e)
@@ -1138,10 +1138,18 @@ module Make (F : Features.T) = struct
 
   let rec map_body_of_nested_lets (f : expr -> expr) (e : expr) : expr =
     match e.e with
-    | Let { monadic; lhs; rhs; body } ->
+    | Let { monadic; lhs; rhs; body; attrs } ->
         {
           e with
-          e = Let { monadic; lhs; rhs; body = map_body_of_nested_lets f body };
+          e =
+            Let
+              {
+                monadic;
+                lhs;
+                rhs;
+                body = map_body_of_nested_lets f body;
+                attrs;
+              };
         }
     | _ -> f e
 
@@ -660,27 +660,173 @@ let map_path_strings ~(f : string -> string) (did : t) : t =
 
 let is_constructor (did : t) : bool = Explicit_def_id.is_constructor did.def_id
 
-let matches_namespace (ns : Types.namespace) (did : t) : bool =
-  let did = Explicit_def_id.to_def_id did.def_id in
-  let path : string option list =
-    [ Some did.krate ]
-    @ List.map
-        ~f:(fun (chunk : Types.disambiguated_def_path_item) ->
-          match chunk.data with
-          | TypeNs s | ValueNs s | MacroNs s | LifetimeNs s -> Some s
-          | _ -> None)
-        did.path
+(** The Unicode scalar values of the UTF-8 string [s], as Rust's
+    [str::chars]. *)
+let uchars (s : string) : Uchar.t list =
+  let rec go i =
+    if i >= String.length s then []
+    else
+      let d = Stdlib.String.get_utf_8_uchar s i in
+      Stdlib.Uchar.utf_decode_uchar d
+      :: go (i + Stdlib.Uchar.utf_decode_length d)
+  in
+  go 0
+
+(** Matches a string against the parts of a chunk pattern. The pattern is
+    compiled to an automaton that is simulated on the string: this takes a
+    time linear in the length of the string. Characters are Unicode scalar
+    values, as in the frontend. *)
+let matches_pattern (parts : Types.pattern_part list) (s : string) : bool =
+  let rec compile (pc : int) (parts : Types.pattern_part list) =
+    (* [pc] is the position of the first instruction *)
+    List.fold parts ~init:[] ~f:(fun prog part ->
+        prog @ compile_part (pc + List.length prog) part)
+  and compile_part (pc : int) (part : Types.pattern_part) =
+    match part with
+    | Verbatim s -> uchars s |> List.map ~f:(fun c -> `Char c)
+    | AnyChar -> [ `AnyChar ]
+    | AnyString -> [ `Split (pc + 1, pc + 3); `AnyChar; `Jump pc ]
+    | Class cls -> [ `Class cls ]
+    | Alternatives alternatives ->
+        let rec alternatives_at pc = function
+          | [] -> []
+          | [ alternative ] -> compile pc alternative
+          | alternative :: alternatives ->
+              let body = compile (pc + 1) alternative in
+              let next = pc + 1 + List.length body + 1 in
+              let rest = alternatives_at next alternatives in
+              (`Split (pc + 1, next) :: body)
+              @ [ `Jump (next + List.length rest) ]
+              @ rest
+        in
+        alternatives_at pc alternatives
+  in
+  let prog = Array.of_list (compile 0 parts @ [ `Match ]) in
+  let len = Array.length prog in
+  let rec add states pc =
+    if not states.(pc) then (
+      states.(pc) <- true;
+      match prog.(pc) with
+      | `Split (a, b) ->
+          add states a;
+          add states b
+      | `Jump a -> add states a
+      | _ -> ())
   in
-  let rec aux (pattern : Types.namespace_chunk list) (path : string option list)
-      =
-    match (pattern, path) with
-    | [], [] -> true
-    | Exact x :: pattern, Some y :: path ->
-        [%equal: string] x y && aux pattern path
-    | Glob One :: pattern, _ :: path -> aux pattern path
-    | Glob Many :: pattern, [] -> aux pattern []
-    | Glob Many :: pattern', _ :: path' ->
-        aux pattern' path || aux pattern path'
-    | _ -> false
+  let init = Array.create ~len false in
+  add init 0;
+  let step states c =
+    let next = Array.create ~len false in
+    Array.iteri states ~f:(fun pc active ->
+        let accepts =
+          match prog.(pc) with
+          | `Char c' -> Uchar.equal c c'
+          | `AnyChar -> true
+          | `Class ({ negated; ranges } : Types.char_class) ->
+              let uchar s = List.hd_exn (uchars s) in
+              Bool.(
+                negated
+                <> List.exists ranges ~f:(fun (low, high) ->
+                       Uchar.between c ~low:(uchar low) ~high:(uchar high)))
+          | _ -> false
+        in
+        if active && accepts then add next (pc + 1));
+    next
+  in
+  List.fold (uchars s) ~init ~f:step
+  |> Array.existsi ~f:(fun pc active ->
+         active && [%matches? `Match] prog.(pc))
+
+(** Matches a namespace against a path: [None] chunks are chunks without
+    names (e.g. [impl] blocks), that can only be matched by globs. This
+    takes a time linear in the length of the path: [states.(i)] holds when
+    the [i] first chunks of the namespace match the chunks of the path read
+    so far. *)
+let matches_path (ns : Types.namespace) (path : string option list) : bool =
+  let chunks = Array.of_list ns.chunks in
+  let n = Array.length chunks in
+  let close states =
+    for i = 0 to n - 1 do
+      match chunks.(i) with
+      | Glob Many when states.(i) -> states.(i + 1) <- true
+      | _ -> ()
+    done;
+    states
+  in
+  let step states (chunk : string option) =
+    let next = Array.create ~len:(n + 1) false in
+    for i = 0 to n - 1 do
+      if states.(i) then
+        match (chunks.(i), chunk) with
+        | Glob Many, _ -> next.(i) <- true
+        | Glob One, _ -> next.(i + 1) <- true
+        | Exact x, Some y when [%equal: string] x y -> next.(i + 1) <- true
+        | Pattern parts, Some y when matches_pattern parts y ->
+            next.(i + 1) <- true
+        | _ -> ()
+    done;
+    close next
+  in
+  let init = Array.init (n + 1) ~f:(fun i -> i = 0) |> close in
+  (List.fold ~init ~f:step path).(n)
+
+let path_of_def_id (did : Types.def_id_contents) : string option list =
+  [ Some did.krate ]
+  @ List.map
+      ~f:(fun (chunk : Types.disambiguated_def_path_item) ->
+        match chunk.data with
+        | TypeNs s | ValueNs s | MacroNs s | LifetimeNs s -> Some s
+        | _ -> None)
+      did.path
+
+let matches_namespace (ns : Types.namespace) (did : t) : bool =
+  Explicit_def_id.to_def_id did.def_id |> path_of_def_id |> matches_path ns
+
+module ItemFactsStore = struct
+  type facts = {
+    kind : Types.item_kind_selector option;
+    public : bool;
+    attributes : string list list;
+    implemented_trait : Types.def_id option;
+  }
+
+  module T = struct
+    type t = Types.def_id_contents [@@deriving compare, sexp, hash]
+  end
+
+  let state : (Types.def_id_contents, facts) Hashtbl.t =
+    Hashtbl.create (module T)
+
+  let register (did : Types.def_id) (facts : facts) : unit =
+    Hashtbl.set state ~key:did.contents.value ~data:facts
+
+  let lookup (did : t) : facts option =
+    Hashtbl.find state (Explicit_def_id.to_def_id did.def_id)
+end
+
+let matches_clause (clause : Types.inclusion_clause) (did : t) : bool =
+  let holds (facts : ItemFactsStore.facts) (selector : Types.item_selector) =
+    match selector with
+    | Kind kind ->
+        [%equal: Types.item_kind_selector option] (Some kind) facts.kind
+    | Public -> facts.public
+    | Attribute ns ->
+        List.exists
+          ~f:(List.map ~f:Option.some >> matches_path ns)
+          facts.attributes
+    | ImplOf ns ->
+        Option.exists
+          ~f:(fun (trait : Types.def_id) ->
+            path_of_def_id trait.contents.value |> matches_path ns)
+          facts.implemented_trait
   in
-  aux ns.chunks path
+  matches_namespace clause.namespace did
+  &&
+  match (clause.predicates, ItemFactsStore.lookup did) with
+  | [], _ -> true
+  | _, None -> false
+  | predicates, Some facts ->
+      List.for_all
+        ~f:(fun (predicate : Types.item_predicate) ->
+          Bool.(predicate.negated <> holds facts predicate.selector))
+        predicates
@@ -104,3 +104,23 @@ module ImplInfoStore : sig
 end
 
 val matches_namespace : Types.namespace -> t -> bool
+
+module ItemFactsStore : sig
+  (** Facts about the items given by the frontend, used to evaluate
+      the predicates of inclusion clauses (e.g. [+k::**/kind=fn,pub]). *)
+  type facts = {
+    kind : Types.item_kind_selector option;
+    public : bool;
+    attributes : string list list;
+        (** The paths of the attributes of the item, split on [::] *)
+    implemented_trait : Types.def_id option;
+        (** For trait implementations, the trait being implemented *)
+  }
+
+  val register : Types.def_id -> facts -> unit
+end
+
+val matches_clause : Types.inclusion_clause -> t -> bool
+(** [matches_clause clause id] holds when the namespace of [clause]
+    matches [id] and when the item [id] satisfies the predicates of
+    [clause]. Items with no registered facts never satisfy predicates. *)
@@ -421,7 +421,56 @@ module Make (F : Features.T) = struct
       >> List.map ~f:(fun s -> " - " ^ s)
       >> String.concat ~sep:"\n"
     in
-    let show_inclusion_clause Types.{ kind; namespace } =
+    let rec show_pattern_part : Types.pattern_part -> string = function
+      | Verbatim s -> s
+      | AnyChar -> "?"
+      | AnyString -> "*"
+      | Class { negated; ranges } ->
+          "["
+          ^ (if negated then "!" else "")
+          ^ (List.map
+               ~f:(fun (lo, hi) ->
+                 if String.equal lo hi then lo else lo ^ "-" ^ hi)
+               ranges
+            |> String.concat)
+          ^ "]"
+      | Alternatives alternatives ->
+          "{"
+          ^ (List.map
+               ~f:(List.map ~f:show_pattern_part >> String.concat)
+               alternatives
+            |> String.concat ~sep:",")
+          ^ "}"
+    in
+    let show_namespace (namespace : Types.namespace) =
+      List.map
+        ~f:(function
+          | Types.Glob One -> "*"
+          | Glob Many -> "**"
+          | Exact s -> s
+          | Pattern parts -> List.map ~f:show_pattern_part parts |> String.concat)
+        namespace.chunks
+      |> String.concat ~sep:"::"
+    in
+    let show_predicate ({ negated; selector } : Types.item_predicate) =
+      (if negated then "!" else "")
+      ^
+      match selector with
+      | Kind kind -> (
+          "kind="
+          ^
+          match kind with
+          | Fn -> "fn"
+          | Struct -> "struct"
+          | Enum -> "enum"
+          | Impl -> "impl"
+          | Trait -> "trait"
+          | Const -> "const")
+      | Public -> "pub"
+      | Attribute ns -> "attr=" ^ show_namespace ns
+      | ImplOf ns -> "trait=" ^ show_namespace ns
+    in
+    let show_inclusion_clause Types.{ kind; namespace; predicates } =
       (match kind with
       | Excluded -> "-"
       | SignatureOnly -> "+:"
@@ -430,16 +479,16 @@ module Make (F : Features.T) = struct
           | Transitive -> "+"
           | Shallow -> "+~"
           | None' -> "+!"))
-      ^ "["
-      ^ (List.map
-           ~f:(function Glob One -> "*" | Glob Many -> "**" | Exact s -> s)
-           namespace.chunks
-        |> String.concat ~sep:"::")
+      ^ "[" ^ show_namespace namespace
+      ^ (if List.is_empty predicates then ""
+         else
+           "/"
+           ^ (List.map ~f:show_predicate predicates |> String.concat ~sep:","))
       ^ "]"
     in
     let items_drop_body = Hash_set.create (module Concrete_ident) in
     let apply_clause selection' (clause : Types.inclusion_clause) =
-      let matches = Concrete_ident.matches_namespace clause.Types.namespace in
+      let matches = Concrete_ident.matches_clause clause in
       let matched0 = Set.filter ~f:matches selection in
       let with_deps, drop_bodies =
         match clause.kind with
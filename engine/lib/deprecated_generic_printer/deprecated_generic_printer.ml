@@ -178,7 +178,7 @@ module Make (F : Features.T) (View : Concrete_ident.RENDER_API) = struct
                     arms
                 in
                 header ^^ iblock braces arms
-            | Let { monadic; lhs; rhs; body } ->
+            | Let { monadic; lhs; rhs; body; _ } ->
                 (Option.map
                    ~f:(fun monad -> print#expr_monadic_let ~monad)
                    monadic
@@ -151,7 +151,7 @@ module Make (F : Features.T) = struct
           print#with_span ~span (fun _ -> print#generic_param' p)
 
       method arm : arm fn =
-        fun { arm; span } -> print#with_span ~span (fun _ -> print#arm' arm)
+        fun { arm; span; _ } -> print#with_span ~span (fun _ -> print#arm' arm)
 
       method ty : par_state -> ty fn =
         fun _ctx ty ->
@@ -21,7 +21,7 @@ struct
       inherit Base.base
 
       (* BEGIN GENERATED *)
-      method arm ~arm:_ ~span:_ = default_document_for "arm"
+      method arm ~arm:_ ~span:_ ~attrs:_ = default_document_for "arm"
 
       method arm' ~super:_ ~arm_pat:_ ~body:_ ~guard:_ =
         default_document_for "arm'"
@@ -105,7 +105,7 @@ struct
       method expr'_If ~super:_ ~cond:_ ~then_:_ ~else_:_ =
         default_document_for "expr'_If"
 
-      method expr'_Let ~super:_ ~monadic:_ ~lhs:_ ~rhs:_ ~body:_ =
+      method expr'_Let ~super:_ ~monadic:_ ~lhs:_ ~rhs:_ ~body:_ ~attrs:_ =
         default_document_for "expr'_Let"
 
       method expr'_Literal ~super:_ _x2 = default_document_for "expr'_Literal"
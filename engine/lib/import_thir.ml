@@ -133,6 +133,17 @@ let c_attr (attr : Thir.attribute) : attr =
 
 let c_attrs : Thir.attribute list -> attrs = List.map ~f:c_attr
 
+let c_comment (comment : Thir.comment) : attr =
+  let kind =
+    match comment.kind with Thir.Line -> DCKLine | Thir.Block -> DCKBlock
+  in
+  {
+    kind = Comment { kind; body = comment.contents };
+    span = Span.of_thir comment.span;
+  }
+
+let c_comments : Thir.comment list -> attrs = List.map ~f:c_comment
+
 let c_item_attrs (attrs : Thir.item_attributes) : attrs =
   (* TODO: This is a quite coarse approximation, we need to reflect
      that parent/self structure in our AST. See
@@ -147,6 +158,10 @@ let c_item_attrs (attrs : Thir.item_attributes) : attrs =
         | [ ((Uid _ | AssociatedItem _), _) ] -> false
         | _ -> true)
   in
+  (* [parent] starts with the attributes of the innermost parent: since
+     [Attrs.status] picks the first status, an item-level
+     `hax_lib::include` or `hax_lib::exclude` overrides the one of its
+     module, which overrides the ones of outer modules. *)
   self @ parent
 
 type extended_literal =
@@ -407,12 +422,20 @@ end) : EXPR = struct
         o_expr
       |> Option.value ~default:(unit_expr full_span)
     in
-    List.fold_right o_stmts ~init ~f:(fun { kind; _ } body ->
+    List.fold_right o_stmts ~init ~f:(fun { kind; comments } body ->
+        let attrs = c_comments comments in
         match kind with
         | Expr { expr = rhs; _ } ->
             let rhs = c_expr rhs in
             let e =
-              Let { monadic = None; lhs = wild_pat rhs.span rhs.typ; rhs; body }
+              Let
+                {
+                  monadic = None;
+                  lhs = wild_pat rhs.span rhs.typ;
+                  rhs;
+                  body;
+                  attrs;
+                }
             in
             { e; typ; span = Span.union rhs.span body.span }
         | Let
@@ -449,7 +472,7 @@ end) : EXPR = struct
         | Let { pattern = lhs; initializer' = Some rhs; _ } ->
             let lhs = c_pat lhs in
             let rhs = c_expr rhs in
-            let e = Let { monadic = None; lhs; rhs; body } in
+            let e = Let { monadic = None; lhs; rhs; body; attrs } in
             { e; typ; span = Span.union rhs.span body.span })
 
   and c_expr_unwrapped (e : Thir.decorated_for__expr_kind) : expr =
@@ -1191,7 +1214,8 @@ end) : EXPR = struct
           { guard; span = Span.of_thir e.span })
         arm.guard
     in
-    { arm = { arm_pat; body; guard }; span }
+    let attrs = c_comments arm.comments @ c_attrs arm.attributes in
+    { arm = { arm_pat; body; guard }; span; attrs }
 
   and c_param span (param : Thir.param) : param =
     {
@@ -1348,7 +1372,7 @@ let c_trait_item (item : Thir.trait_item) : trait_item =
     ti_generics = { params; constraints };
     ti_v = c_trait_item' item item.kind;
     ti_ident;
-    ti_attrs = c_item_attrs item.attributes;
+    ti_attrs = c_comments item.comments @ c_item_attrs item.attributes;
   }
 
 let is_automatically_derived (attrs : Thir.attribute list) =
@@ -1441,7 +1465,7 @@ let rec c_item ~ident ~type_only (item : Thir.item) : item list =
 and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
   let open (val make ~krate:item.owner_id.contents.value.krate : EXPR) in
   let span = Span.of_thir item.span in
-  let attrs = c_item_attrs item.attributes in
+  let attrs = c_comments item.comments @ c_item_attrs item.attributes in
   (* this is true if the user explicilty requested to erase using the `opaque` macro *)
   let erased_by_user attrs =
     Attr_payloads.payloads attrs
@@ -1557,7 +1581,10 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
       in
       let variants =
         List.map
-          ~f:(fun ({ data; def_id = variant_id; attributes; _ } as original) ->
+          ~f:(fun
+              ({ data; def_id = variant_id; attributes; comments; _ } as
+               original)
+            ->
             let is_record =
               [%matches? (Struct { fields = _ :: _; _ } : Types.variant_data)]
                 data
@@ -1567,14 +1594,15 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
               match data with
               | Tuple (fields, _, _) | Struct { fields; _ } ->
                   List.map
-                    ~f:(fun { def_id = id; ty; span; attributes; _ } ->
+                    ~f:(fun { def_id = id; ty; span; attributes; comments; _ }
+                       ->
                       ( Concrete_ident.of_def_id ~value:true id,
                         c_ty span ty,
-                        c_attrs attributes ))
+                        c_comments comments @ c_attrs attributes ))
                     fields
               | Unit _ -> []
             in
-            let attrs = c_attrs attributes in
+            let attrs = c_comments comments @ c_attrs attributes in
             ({ name; arguments; is_record; attrs }, original))
           variants
       in
@@ -1598,10 +1626,10 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
         let mk fields is_record =
           let arguments =
             List.map
-              ~f:(fun Thir.{ def_id = id; ty; span; attributes; _ } ->
+              ~f:(fun Thir.{ def_id = id; ty; span; attributes; comments; _ } ->
                 ( Concrete_ident.of_def_id ~value:true id,
                   c_ty span ty,
-                  c_attrs attributes ))
+                  c_comments comments @ c_attrs attributes ))
               fields
           in
           { name; arguments; is_record; attrs }
@@ -1656,7 +1684,9 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
           let item_def_id =
             Concrete_ident.of_def_id ~value:false item.owner_id
           in
-          let attrs = c_item_attrs item.attributes in
+          let attrs =
+            c_comments item.comments @ c_item_attrs item.attributes
+          in
           let sub_item_erased_by_user = erased_by_user attrs in
           let erased_by_type_only =
             type_only && match item.kind with Fn _ -> true | _ -> false
@@ -1759,7 +1789,8 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
                               parent_bounds;
                         });
                 ii_ident;
-                ii_attrs = c_item_attrs item.attributes;
+                ii_attrs =
+                  c_comments item.comments @ c_item_attrs item.attributes;
               })
             items
       in
@@ -1820,6 +1851,14 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
   | ExternCrate _ | Static _ | Macro _ | Mod _ | ForeignMod _ | GlobalAsm _
   | TraitAlias _ ->
       mk NotImplementedYet
+  | ExportError reason ->
+      let details =
+        match (reason : Thir.export_error_reason) with
+        | Fatal -> "a fatal error was reported, see the errors above"
+        | Panic message -> "the frontend panicked: " ^ message
+      in
+      Diagnostics.SpanFreeError.raise ~span:[ item.span ] ThirImport
+        (T.FrontendExportError { details })
 
 let import_item ~type_only (item : Thir.item) :
     concrete_ident * (item list * Diagnostics.t list) =
@@ -1834,3 +1873,41 @@ let import_item ~type_only (item : Thir.item) :
         c_item item ~ident ~type_only |> List.map ~f)
   in
   (ident, (r, reports))
+
+let item_facts (item : Thir.item) : Concrete_ident.ItemFactsStore.facts =
+  let kind : Types.item_kind_selector option =
+    match item.kind with
+    | Fn _ -> Some Fn
+    | Struct _ -> Some Struct
+    | Enum _ -> Some Enum
+    | Impl _ -> Some Impl
+    | Trait _ -> Some Trait
+    | Const _ -> Some Const
+    | _ -> None
+  in
+  let attribute_path (attr : attr) =
+    match (attr.kind, Attr_payloads.payloads [ attr ]) with
+    | _, [ (ItemStatus (Included _), _) ] -> Some "hax_lib::include"
+    | _, [ (ItemStatus (Excluded _), _) ] -> Some "hax_lib::exclude"
+    | Tool { path; _ }, _ -> Some path
+    | (DocComment _ | Comment _), _ -> None
+  in
+  let attributes =
+    c_attrs item.attributes.attributes
+    |> List.filter_map ~f:attribute_path
+    |> List.map
+         ~f:
+           (String.split_on_chars ~on:[ ':' ]
+           >> List.filter ~f:(String.is_empty >> not))
+  in
+  let implemented_trait =
+    match item.kind with
+    | Impl { of_trait = Some trait_ref; _ } -> Some trait_ref.def_id
+    | _ -> None
+  in
+  {
+    kind;
+    public = [%matches? Types.Public] item.visibility;
+    attributes;
+    implemented_trait;
+  }
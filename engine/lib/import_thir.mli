@@ -8,3 +8,9 @@ val import_item :
   type_only:bool ->
   Types.item_for__decorated_for__expr_kind ->
   Concrete_ident.t * (Ast.Rust.item list * Diagnostics.t list)
+
+val item_facts :
+  Types.item_for__decorated_for__expr_kind ->
+  Concrete_ident.ItemFactsStore.facts
+(** Computes the facts needed to evaluate the predicates of inclusion
+    clauses on an item (see [Concrete_ident.matches_clause]). *)
@@ -128,7 +128,10 @@ struct
                 else
                   (UB.make_var_pat var e.typ e.span, { e with e = LocalVar var })
               in
-              { body with e = Let { monadic = None; lhs; rhs; body } }
+              {
+                body with
+                e = Let { monadic = None; lhs; rhs; body; attrs = [] };
+              }
         in
         UB.map_body_of_nested_lets f e
 
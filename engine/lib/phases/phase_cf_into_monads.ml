@@ -143,7 +143,7 @@ struct
       let typ = dty span expr.typ in
       match expr.e with
       | Let { monadic = Some _; _ } -> .
-      | Let { monadic = None; lhs; rhs; body } -> (
+      | Let { monadic = None; lhs; rhs; body; attrs } -> (
           let body' = dexpr body in
           let rhs' = dexpr rhs in
           let mrhs = KnownMonads.from_typ dty rhs.typ rhs'.typ in
@@ -153,7 +153,11 @@ struct
               let monadic = None in
               let rhs = rhs' in
               let body = body' in
-              { e = Let { monadic; lhs; rhs; body }; span; typ = body.typ }
+              {
+                e = Let { monadic; lhs; rhs; body; attrs };
+                span;
+                typ = body.typ;
+              }
           | _ ->
               let mbody = KnownMonads.from_typ dty body.typ body'.typ in
               let m = KnownMonads.lub span mbody.monad mrhs.monad in
@@ -164,15 +168,19 @@ struct
                 | None -> None
                 | Some m -> Some (m, Features.On.monadic_binding)
               in
-              { e = Let { monadic; lhs; rhs; body }; span; typ = body.typ })
+              {
+                e = Let { monadic; lhs; rhs; body; attrs };
+                span;
+                typ = body.typ;
+              })
       | Match { scrutinee; arms } ->
           let arms =
             List.map
-              ~f:(fun { arm = { arm_pat; body = a; guard }; span } ->
+              ~f:(fun { arm = { arm_pat; body = a; guard }; span; attrs } ->
                 let b = dexpr a in
                 let m = KnownMonads.from_typ dty a.typ b.typ in
                 let g = Option.map ~f:dguard guard in
-                (m, (dpat arm_pat, span, b, g)))
+                (m, (dpat arm_pat, span, attrs, b, g)))
               arms
           in
           let arms =
@@ -184,10 +192,10 @@ struct
             | None -> [] (* [arms] is empty *)
             | Some m ->
                 List.map
-                  ~f:(fun (mself, (arm_pat, span, body, guard)) ->
+                  ~f:(fun (mself, (arm_pat, span, attrs, body, guard)) ->
                     let body = KnownMonads.lift "Match" body mself.monad m in
                     let arm_pat = { arm_pat with typ = body.typ } in
-                    ({ arm = { arm_pat; body; guard }; span } : B.arm))
+                    ({ arm = { arm_pat; body; guard }; span; attrs } : B.arm))
                   arms
           in
           let typ =
@@ -83,8 +83,10 @@ module%inlined_contents Make (F : Features.T) = struct
         (treated : B.arm list) : B.arm list =
       match remaining with
       | [] -> treated
-      | { arm = { arm_pat; body; guard = None }; span } :: remaining ->
-          let new_arm : B.arm = UB.M.arm (dpat arm_pat) (dexpr body) ~span in
+      | { arm = { arm_pat; body; guard = None }; span; attrs } :: remaining ->
+          let new_arm : B.arm =
+            { (UB.M.arm (dpat arm_pat) (dexpr body) ~span) with attrs }
+          in
           transform_arms scrutinee remaining (new_arm :: treated)
       (* Matches an arm `arm_pat if let lhs = rhs => body` *)
       (* And rewrites to `_ => match <option_match> {Some(x) => x, None => match scrutinee {<treated>} }` *)
@@ -99,6 +101,7 @@ module%inlined_contents Make (F : Features.T) = struct
               guard = Some { guard = IfLet { lhs; rhs; _ }; span = guard_span };
             };
           span;
+          attrs;
         }
         :: remaining ->
           let module MS = (val UB.M.make guard_span) in
@@ -182,9 +185,13 @@ module%inlined_contents Make (F : Features.T) = struct
               ~typ:result_typ
           in
           let new_arm : B.arm =
-            UB.M.arm
-              (UB.M.pat_PWild ~typ:(dty span arm_pat.typ) ~span)
-              new_body ~span
+            {
+              (UB.M.arm
+                 (UB.M.pat_PWild ~typ:(dty span arm_pat.typ) ~span)
+                 new_body ~span)
+              with
+              attrs;
+            }
           in
           transform_arms scrutinee remaining [ new_arm ]
     [@@inline_ands bindings_of dexpr - dexpr' - darm - darm' - dguard - dguard']
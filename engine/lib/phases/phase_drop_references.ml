@@ -91,13 +91,14 @@ struct
             }
       | Match { scrutinee; arms } ->
           Match { scrutinee = dexpr scrutinee; arms = List.map ~f:darm arms }
-      | Let { monadic; lhs; rhs; body } ->
+      | Let { monadic; lhs; rhs; body; attrs } ->
           Let
             {
               monadic = Option.map ~f:(dsupported_monads span *** Fn.id) monadic;
               lhs = dpat lhs;
               rhs = dexpr rhs;
               body = dexpr body;
+              attrs;
             }
       | LocalVar local_ident -> LocalVar local_ident
       | GlobalVar global_ident -> GlobalVar global_ident
@@ -75,11 +75,11 @@ module%inlined_contents Make (F : Features.T) = struct
           | Return { e; _ }, None -> e
           (* we know [e] is on an exit position: the return is
              thus useless, we can skip it *)
-          | Let { monadic = None; lhs; rhs; body }, _ ->
+          | Let { monadic = None; lhs; rhs; body; attrs }, _ ->
               let body = self#visit_expr in_loop body in
               {
                 e with
-                e = Let { monadic = None; lhs; rhs; body };
+                e = Let { monadic = None; lhs; rhs; body; attrs };
                 typ = body.typ;
               }
               (* If a let expression is an exit node, then it's body
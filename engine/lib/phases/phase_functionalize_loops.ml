@@ -69,6 +69,7 @@ struct
                 _;
               };
             body;
+            _;
           }
         when Global_ident.eq_name Hax_lib___internal_loop_invariant f ->
           { body; invariant = Some (pat, invariant) }
@@ -99,6 +99,7 @@ struct
                 _;
               };
             body;
+            attrs;
           } ->
           let h (type a) (f : a list -> a) (x : a) (y : a) =
             match lhs.p with PWild -> y | _ -> f [ x; y ]
@@ -118,12 +119,13 @@ struct
                          { s with expr_level = []; drop_expr = false }
                          value);
                   body;
+                  attrs;
                 };
             typ = body.typ;
             span = expr.span;
           }
       | Let { monadic = Some _; _ } -> .
-      | Let { monadic = None; lhs; rhs; body } ->
+      | Let { monadic = None; lhs; rhs; body; attrs } ->
           let drop_expr = [%matches? A.PWild] lhs.p in
           let rhs_vars =
             free_assigned_variables#visit_expr () rhs
@@ -149,6 +151,7 @@ struct
                   lhs = lhs';
                   rhs = dexpr_s { s with expr_level = rhs_vars; drop_expr } rhs;
                   body;
+                  attrs;
                 };
             typ = body.typ;
             span = expr.span;
@@ -357,7 +360,15 @@ struct
               in
               B.
                 {
-                  e = Let { monadic = None; lhs; rhs = loop; body = out };
+                  e =
+                    Let
+                      {
+                        monadic = None;
+                        lhs;
+                        rhs = loop;
+                        body = out;
+                        attrs = [];
+                      };
                   span;
                   typ = out.typ;
                 }
@@ -40,6 +40,7 @@ module Make (F : Features.T) =
                      };
                    rhs;
                    body;
+                   _;
                  };
              _;
             }
@@ -54,6 +54,7 @@ module Make (F : Features.T) =
                        _;
                      } as match_expr;
                    body;
+                   attrs;
                  };
              _;
             } ->
@@ -87,7 +88,14 @@ module Make (F : Features.T) =
                            {
                              e with
                              e =
-                               Let { monadic = None; lhs; rhs = arm_body; body };
+                               Let
+                                 {
+                                   monadic = None;
+                                   lhs;
+                                   rhs = arm_body;
+                                   body;
+                                   attrs;
+                                 };
                            } )
                 in
                 let arm =
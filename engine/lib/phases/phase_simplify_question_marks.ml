@@ -228,7 +228,11 @@ module%inlined_contents Make (FA : Features.T) = struct
                     let body =
                       { typ = local_success; e = LocalVar var_ok; span }
                     in
-                    { arm = { arm_pat; body; guard = None }; span }
+                    {
+                      arm = { arm_pat; body; guard = None };
+                      span;
+                      attrs = [];
+                    }
                   in
                   let arm_err =
                     let pat = UA.make_var_pat var_err local_err span in
@@ -247,7 +251,11 @@ module%inlined_contents Make (FA : Features.T) = struct
                     in
                     let e = Return { e = err; witness = return_witness } in
                     let return = { typ = local_success; e; span } in
-                    { arm = { arm_pat; body = return; guard = None }; span }
+                    {
+                      arm = { arm_pat; body = return; guard = None };
+                      span;
+                      attrs = [];
+                    }
                   in
                   let arms, typ = ([ arm_ok; arm_err ], local_success) in
                   { e = Match { scrutinee = expr; arms }; typ; span }
@@ -262,7 +270,11 @@ module%inlined_contents Make (FA : Features.T) = struct
                     let body =
                       { typ = local_success; e = LocalVar var_some; span }
                     in
-                    { arm = { arm_pat; body; guard = None }; span }
+                    {
+                      arm = { arm_pat; body; guard = None };
+                      span;
+                      attrs = [];
+                    }
                   in
                   let arm_none =
                     let arm_pat = mk_cons Core__option__Option__None [] in
@@ -272,7 +284,11 @@ module%inlined_contents Make (FA : Features.T) = struct
                     in
                     let e = Return { e = none; witness = return_witness } in
                     let return = { typ = local_success; e; span } in
-                    { arm = { arm_pat; body = return; guard = None }; span }
+                    {
+                      arm = { arm_pat; body = return; guard = None };
+                      span;
+                      attrs = [];
+                    }
                   in
                   let arms, typ = ([ arm_some; arm_none ], local_success) in
                   { e = Match { scrutinee = expr; arms }; typ; span }
@@ -304,7 +304,7 @@ module Raw = struct
         in
         !"(match (" & pexpr scrutinee & !") {" & arms & !"})"
     (* | Let { monadic = Some _; _ } -> !"monadic_let!()" *)
-    | Let { monadic; lhs; rhs; body } ->
+    | Let { monadic; lhs; rhs; body; _ } ->
         (* TODO: here, [rhs.typ]! *)
         let lhs_typ = pty lhs.span lhs.typ in
         let rhs_typ = pty rhs.span rhs.typ in
@@ -389,6 +389,7 @@ module Raw = struct
     match attr.kind with
     | Tool { path; tokens } -> !"#[" & !path & !"(" & !tokens & !")" & !"]"
     | DocComment { kind = _; body } -> !"/**" & !body & !"*/"
+    | Comment { kind = _; body } -> !"/*" & !body & !"*/"
 
   let pattrs attrs = List.map ~f:pattr attrs |> concat
 
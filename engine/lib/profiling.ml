@@ -6,6 +6,20 @@ let enabled = ref true
 (** Profiles the function `f`, that operates in a given context over a given quantity of things it is processing. *)
 let profile (type b) (context : Diagnostics.Context.t) (quantity : int)
     (f : unit -> b) : b =
+  (* Phase events are sent regardless of [enabled]: they drive the
+     progress indicator of `cargo hax`. *)
+  let phase = Diagnostics.Context.display context in
+  Hax_io.write
+    (Types.PhaseStarted { phase; quantity = Int.to_int64 quantity });
+  let f () =
+    try
+      let result = f () in
+      Hax_io.write (Types.PhaseFinished { phase; errored = false });
+      result
+    with e ->
+      Hax_io.write (Types.PhaseFinished { phase; errored = true });
+      raise e
+  in
   if !enabled (* `!` derefs, it's not a negation *) then (
     let time0 = Core.Time_ns.now () in
     let mem0 = Core.Gc.minor_words () in
@@ -19,6 +33,8 @@ let profile (type b) (context : Diagnostics.Context.t) (quantity : int)
           (Types.ProfilingData
              {
                context = Diagnostics.Context.display context;
+               start_ns =
+                 Core.Time_ns.to_int63_ns_since_epoch time0 |> Int63.to_string;
                time_ns =
                  Core.Time_ns.Span.to_int63_ns time_ns |> Int63.to_string;
                memory = Int.to_string memory;
@@ -35,3 +51,15 @@ let profile (type b) (context : Diagnostics.Context.t) (quantity : int)
       finalize true;
       raise e)
   else f ()
+
+(** Reports that [processed] out of [total] items of the current phase
+    were processed. To keep the protocol light, progress is only sent
+    once per percent. *)
+let items_progress ~(processed : int) ~(total : int) : unit =
+  if
+    processed = total
+    || processed * 100 / total > (processed - 1) * 100 / total
+  then
+    Hax_io.write
+      (Types.ItemsProgress
+         { processed = Int.to_int64 processed; total = Int.to_int64 total })
@@ -123,7 +123,7 @@ struct
   end
 
   module Hoist = struct
-    type binding = pat * expr [@@deriving show]
+    type binding = pat * expr * attrs [@@deriving show]
     type t = { lbs : binding list; effects : SideEffects.t } [@@deriving show]
 
     let plus x y : t =
@@ -183,7 +183,8 @@ struct
                      | { e = LocalVar var; _ } when false -> var
                      | _ -> fresh ()
                    in
-                   ( lbs @ [ (U.make_var_pat var expr.typ expr.span, expr) ],
+                   ( lbs
+                     @ [ (U.make_var_pat var expr.typ expr.span, expr, []) ],
                      { expr with e = LocalVar var } ))
                 :: l ))
         in
@@ -206,10 +207,10 @@ struct
           | _ -> err_hoist_invariant (fst e).span Stdlib.__LOC__)
     end
 
-    let let_of_binding ((pat, rhs) : pat * expr) (body : expr) : expr =
-      U.make_let pat rhs body
+    let let_of_binding ((pat, rhs, attrs) : binding) (body : expr) : expr =
+      U.make_let ~attrs pat rhs body
 
-    let lets_of_bindings (bindings : (pat * expr) list) (body : expr) : expr =
+    let lets_of_bindings (bindings : binding list) (body : expr) : expr =
       List.fold_right ~init:body ~f:let_of_binding bindings
 
     let collect_and_hoist_effects_object =
@@ -419,11 +420,12 @@ struct
                 let arms =
                   List.map ~f:(self#visit_arm env) arms
                   (* materialize letbindings in each arms *)
-                  |> List.map ~f:(fun ({ arm; span }, ({ lbs; effects } : t)) ->
+                  |> List.map
+                       ~f:(fun ({ arm; span; attrs }, ({ lbs; effects } : t)) ->
                          let arm =
                            { arm with body = lets_of_bindings lbs arm.body }
                          in
-                         (({ arm; span } : arm), { lbs = []; effects }))
+                         (({ arm; span; attrs } : arm), { lbs = []; effects }))
                      (* cancel effects that concern variables introduced in pats  *)
                   |> List.map ~f:(fun (arm, { lbs; effects }) ->
                          let vars =
@@ -442,14 +444,14 @@ struct
                   ( { e with e = Match { scrutinee; arms } },
                     m#plus eff_arms effects ))
           | Let { monadic = Some _; _ } -> .
-          | Let { monadic = None; lhs; rhs; body } ->
+          | Let { monadic = None; lhs; rhs; body; attrs } ->
               let rhs, { lbs = rhs_lbs; effects = rhs_effects } =
                 self#visit_expr env rhs
               in
               let body, { lbs = body_lbs; effects = body_effects } =
                 self#visit_expr env body
               in
-              let lbs = rhs_lbs @ ((lhs, rhs) :: body_lbs) in
+              let lbs = rhs_lbs @ ((lhs, rhs, attrs) :: body_lbs) in
               let effects = SideEffects.plus rhs_effects body_effects in
               (body, { lbs; effects })
           | GlobalVar _ -> (e, m#zero)
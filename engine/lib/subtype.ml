@@ -206,7 +206,7 @@ struct
           }
     | Match { scrutinee; arms } ->
         Match { scrutinee = dexpr scrutinee; arms = List.map ~f:darm arms }
-    | Let { monadic; lhs; rhs; body } ->
+    | Let { monadic; lhs; rhs; body; attrs } ->
         Let
           {
             monadic =
@@ -216,6 +216,7 @@ struct
             lhs = dpat lhs;
             rhs = dexpr rhs;
             body = dexpr body;
+            attrs;
           }
     | Block { e; safety_mode; witness } ->
         Block
@@ -338,7 +339,8 @@ struct
       witness = S.state_passing_loop span s.witness;
     }
 
-  and darm (a : A.arm) : B.arm = { span = a.span; arm = darm' a.arm }
+  and darm (a : A.arm) : B.arm =
+    { span = a.span; arm = darm' a.arm; attrs = a.attrs }
 
   and darm' (a : A.arm') : B.arm' =
     {
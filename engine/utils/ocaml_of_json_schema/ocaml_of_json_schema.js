@@ -573,7 +573,12 @@ end
 
     impl += `let hax_version = {escape|${contents['$id'].replace(/\|escape\}/g, '|_escape}')}|escape}`;
 
+    // Spans and impl expressions are interned in the ID table, but
+    // the engine manipulates them as plain records: their nodes are
+    // unwrapped (see \`node_for__span_contents\` below).
+    let unwrapped_nodes = ['Node_for_SpanContents', 'Node_for_ImplExprContents'];
     let items = Object.entries(definitions)
+        .filter(([name, _]) => !unwrapped_nodes.includes(name))
         .map(([name, def]) => ['Node_for_TyKind' == name ? 'node_for_ty_kind_generated' : name, def])
         .map(([name, def]) => ['Node_for_DefIdContents' == name ? 'node_for_def_id_contents_generated' : name, def])
         .map(
@@ -611,9 +616,13 @@ open ParseError
     impl += `
 and node_for__ty_kind = node_for_ty_kind_generated
 and node_for__def_id_contents = node_for_def_id_contents_generated
+and node_for__span_contents = span_contents
+and span = span_contents
+and node_for__impl_expr_contents = impl_expr_contents
+and impl_expr = impl_expr_contents
 
 
-type map_types = ${"[`TyKind of ty_kind | `DefIdContents of def_id_contents]"}
+type map_types = ${"[`TyKind of ty_kind | `DefIdContents of def_id_contents | `SpanContents of span_contents | `ImplExprContents of impl_expr_contents]"}
 let cache_map: (int64, ${"[ `Value of map_types | `JSON of Yojson.Safe.t ]"}) Base.Hashtbl.t = Base.Hashtbl.create (module Base.Int64)
 
 module Exn = struct
@@ -667,6 +676,20 @@ and node_for__def_id_contents_of_yojson (o: Yojson.Safe.t): node_for__def_id_con
            o
    in
    {value; id = Base.Int64.zero}
+and node_for__span_contents_of_yojson (o: Yojson.Safe.t): node_for__span_contents =
+   fst (table_id_node_of_yojson "SpanContents"
+           (fun value -> \`SpanContents value)
+           (function | \`SpanContents value -> Some value | _ -> None)
+           span_contents_of_yojson
+           o)
+and node_for__impl_expr_contents_of_yojson (o: Yojson.Safe.t): node_for__impl_expr_contents =
+   fst (table_id_node_of_yojson "ImplExprContents"
+           (fun value -> \`ImplExprContents value)
+           (function | \`ImplExprContents value -> Some value | _ -> None)
+           impl_expr_contents_of_yojson
+           o)
+and span_of_yojson (o: Yojson.Safe.t): span = node_for__span_contents_of_yojson o
+and impl_expr_of_yojson (o: Yojson.Safe.t): impl_expr = node_for__impl_expr_contents_of_yojson o
 `;
     impl += ('');
     impl += ('let rec ' + items.map(({ name, type, parse, to_json }) =>
@@ -675,6 +698,12 @@ and node_for__def_id_contents_of_yojson (o: Yojson.Safe.t): node_for__def_id_con
     impl += `
 and yojson_of_node_for__ty_kind {value; id} = yojson_of_node_for_ty_kind_generated {value; id}
 and yojson_of_node_for__def_id_contents {value; id} = yojson_of_node_for_def_id_contents_generated {value; id}
+and yojson_of_node_for__span_contents (value: node_for__span_contents): Yojson.Safe.t =
+   \`Assoc [("id", \`Int 0); ("value", yojson_of_span_contents value)]
+and yojson_of_node_for__impl_expr_contents (value: node_for__impl_expr_contents): Yojson.Safe.t =
+   \`Assoc [("id", \`Int 0); ("value", yojson_of_impl_expr_contents value)]
+and yojson_of_span (o: span): Yojson.Safe.t = yojson_of_node_for__span_contents o
+and yojson_of_impl_expr (o: impl_expr): Yojson.Safe.t = yojson_of_node_for__impl_expr_contents o
 end
 
 open struct
@@ -18,11 +18,247 @@ impl ToString for Glob {
     }
 }
 
+/// A class of characters, e.g. `[a-z_]` or `[!0-9]`.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone, JsonSchema)]
+pub struct CharClass {
+    pub negated: bool,
+    /// Inclusive ranges of characters. The engine gets them as
+    /// strings: an OCaml `char` is a byte, not a Unicode scalar value.
+    #[schemars(with = "Vec<(String, String)>")]
+    pub ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    fn contains(&self, c: char) -> bool {
+        self.negated != self.ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c))
+    }
+}
+
+impl ToString for CharClass {
+    fn to_string(&self) -> String {
+        let ranges: String = self
+            .ranges
+            .iter()
+            .map(|(lo, hi)| {
+                if lo == hi {
+                    lo.to_string()
+                } else {
+                    format!("{lo}-{hi}")
+                }
+            })
+            .collect();
+        format!("[{}{ranges}]", if self.negated { "!" } else { "" })
+    }
+}
+
+/// A part of a chunk pattern.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone, JsonSchema)]
+pub enum PatternPart {
+    /// Matches exactly the given characters
+    Verbatim(String),
+    /// `?`: matches any character
+    AnyChar,
+    /// `*`: matches any sequence of characters
+    AnyString,
+    /// `[...]`: matches a character of the class
+    Class(CharClass),
+    /// `{a,b}`: matches any of the alternatives
+    Alternatives(Vec<Vec<PatternPart>>),
+}
+
+impl ToString for PatternPart {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Verbatim(s) => s.clone(),
+            Self::AnyChar => "?".to_string(),
+            Self::AnyString => "*".to_string(),
+            Self::Class(class) => class.to_string(),
+            Self::Alternatives(alternatives) => {
+                let alternatives: Vec<String> = alternatives
+                    .iter()
+                    .map(|parts| parts.iter().map(PatternPart::to_string).collect())
+                    .collect();
+                format!("{{{}}}", alternatives.join(","))
+            }
+        }
+    }
+}
+
+/// An instruction of the automaton a chunk pattern is compiled to.
+enum Inst<'a> {
+    Char(char),
+    AnyChar,
+    Class(&'a CharClass),
+    /// Continues both at the first and at the second instruction
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+fn compile<'a>(parts: &'a [PatternPart], prog: &mut Vec<Inst<'a>>) {
+    for part in parts {
+        match part {
+            PatternPart::Verbatim(s) => prog.extend(s.chars().map(Inst::Char)),
+            PatternPart::AnyChar => prog.push(Inst::AnyChar),
+            PatternPart::AnyString => {
+                let split = prog.len();
+                prog.push(Inst::Split(split + 1, split + 3));
+                prog.push(Inst::AnyChar);
+                prog.push(Inst::Jump(split));
+            }
+            PatternPart::Class(class) => prog.push(Inst::Class(class)),
+            PatternPart::Alternatives(alternatives) => {
+                let mut jumps = vec![];
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    let split = prog.len();
+                    let last = i + 1 == alternatives.len();
+                    if !last {
+                        prog.push(Inst::Split(split + 1, 0));
+                    }
+                    compile(alternative, prog);
+                    if !last {
+                        jumps.push(prog.len());
+                        prog.push(Inst::Jump(0));
+                        let next = prog.len();
+                        prog[split] = Inst::Split(split + 1, next);
+                    }
+                }
+                let end = prog.len();
+                for jump in jumps {
+                    prog[jump] = Inst::Jump(end);
+                }
+            }
+        }
+    }
+}
+
+/// Matches `s` against a chunk pattern. The pattern is compiled to an
+/// automaton that is simulated on `s`: this takes a time linear in the
+/// length of `s`.
+fn matches_pattern(parts: &[PatternPart], s: &str) -> bool {
+    let mut prog = vec![];
+    compile(parts, &mut prog);
+    prog.push(Inst::Match);
+    fn add(prog: &[Inst], pc: usize, states: &mut Vec<bool>) {
+        if states[pc] {
+            return;
+        }
+        states[pc] = true;
+        match prog[pc] {
+            Inst::Split(a, b) => {
+                add(prog, a, states);
+                add(prog, b, states);
+            }
+            Inst::Jump(a) => add(prog, a, states),
+            _ => (),
+        }
+    }
+    let mut states = vec![false; prog.len()];
+    add(&prog, 0, &mut states);
+    for c in s.chars() {
+        let mut next = vec![false; prog.len()];
+        for (pc, inst) in prog.iter().enumerate().filter(|(pc, _)| states[*pc]) {
+            let accepts = match inst {
+                Inst::Char(expected) => *expected == c,
+                Inst::AnyChar => true,
+                Inst::Class(class) => class.contains(c),
+                _ => false,
+            };
+            if accepts {
+                add(&prog, pc + 1, &mut next);
+            }
+        }
+        states = next;
+    }
+    prog.iter()
+        .zip(states)
+        .any(|(inst, active)| active && matches!(inst, Inst::Match))
+}
+
+/// Parses the parts of a chunk pattern, up to the end of `chars` or,
+/// if `in_alternative` is set, up to a `,`, `|` or `}`. Alternatives
+/// can be separated by `|` where `,` is already a separator (e.g. in
+/// the comma-separated values of `--inline-macro-call`).
+fn parse_pattern_parts(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    in_alternative: bool,
+) -> Result<Vec<PatternPart>, String> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    macro_rules! push {
+        ($part:expr) => {{
+            if !literal.is_empty() {
+                parts.push(PatternPart::Verbatim(std::mem::take(&mut literal)));
+            }
+            parts.push($part)
+        }};
+    }
+    while let Some(&c) = chars.peek() {
+        if in_alternative && matches!(c, ',' | '|' | '}') {
+            break;
+        }
+        chars.next();
+        match c {
+            '*' => {
+                if !matches!(parts.last(), Some(PatternPart::AnyString)) || !literal.is_empty() {
+                    push!(PatternPart::AnyString)
+                }
+            }
+            '?' => push!(PatternPart::AnyChar),
+            '[' => {
+                let negated = chars.next_if(|c| matches!(c, '!' | '^')).is_some();
+                let mut ranges = vec![];
+                loop {
+                    match chars.next() {
+                        None => Err("Expected a `]` closing the character class")?,
+                        Some(']') if !ranges.is_empty() => break,
+                        Some(lo) => {
+                            let hi = if chars.next_if_eq(&'-').is_some() {
+                                chars
+                                    .next()
+                                    .ok_or("Expected the end of a range of characters")?
+                            } else {
+                                lo
+                            };
+                            if hi < lo {
+                                Err(format!("Invalid range of characters `{lo}-{hi}`"))?
+                            }
+                            ranges.push((lo, hi));
+                        }
+                    }
+                }
+                push!(PatternPart::Class(CharClass { negated, ranges }))
+            }
+            '{' => {
+                let mut alternatives = vec![parse_pattern_parts(chars, true)?];
+                loop {
+                    match chars.next() {
+                        Some(',' | '|') => alternatives.push(parse_pattern_parts(chars, true)?),
+                        Some('}') => break,
+                        _ => Err("Expected a `}` closing the alternatives")?,
+                    }
+                }
+                push!(PatternPart::Alternatives(alternatives))
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(PatternPart::Verbatim(literal));
+    }
+    Ok(parts)
+}
+
 #[derive_group(Serializers)]
 #[derive(Debug, Clone, JsonSchema)]
 pub enum NamespaceChunk {
     Glob(Glob),
     Exact(String),
+    /// A chunk with wildcards (`*` or `?`), character classes (e.g.
+    /// `[a-z]`) or alternatives (e.g. `{encode,decode}`)
+    Pattern(Vec<PatternPart>),
 }
 
 impl ToString for NamespaceChunk {
@@ -30,16 +266,30 @@ impl ToString for NamespaceChunk {
         match self {
             Self::Glob(glob) => glob.to_string(),
             Self::Exact(string) => string.to_string(),
+            Self::Pattern(parts) => parts.iter().map(PatternPart::to_string).collect(),
         }
     }
 }
 
-impl std::convert::From<&str> for NamespaceChunk {
-    fn from(s: &str) -> Self {
-        match s {
+impl std::str::FromStr for NamespaceChunk {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Ok(match s {
             "*" => NamespaceChunk::Glob(Glob::One),
             "**" => NamespaceChunk::Glob(Glob::Many),
-            _ => NamespaceChunk::Exact(String::from(s)),
+            _ if !s.contains(['*', '?', '[', '{']) => NamespaceChunk::Exact(String::from(s)),
+            _ => NamespaceChunk::Pattern(parse_pattern_parts(&mut s.chars().peekable(), false)?),
+        })
+    }
+}
+
+impl NamespaceChunk {
+    /// Does a chunk of a path match this (non-glob) chunk?
+    fn matches(&self, chunk: &str) -> bool {
+        match self {
+            Self::Glob(_) => true,
+            Self::Exact(x) => x == chunk,
+            Self::Pattern(parts) => matches_pattern(parts, chunk),
         }
     }
 }
@@ -61,41 +311,141 @@ impl ToString for Namespace {
     }
 }
 
-impl std::convert::From<String> for Namespace {
-    fn from(s: String) -> Self {
-        Namespace {
+impl std::str::FromStr for Namespace {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Ok(Namespace {
             chunks: s
                 .split("::")
                 .filter(|s| !s.is_empty())
-                .map(NamespaceChunk::from)
-                .collect(),
-        }
+                .map(str::parse)
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
 impl Namespace {
-    pub fn matches(&self, path: &Vec<String>) -> bool {
-        fn aux(pattern: &[NamespaceChunk], path: &[String]) -> bool {
-            match (pattern, path) {
-                ([], []) => true,
-                ([NamespaceChunk::Exact(x), pattern @ ..], [y, path @ ..]) => {
-                    x == y && aux(pattern, path)
-                }
-                ([NamespaceChunk::Glob(Glob::One), pattern @ ..], [_, path @ ..]) => {
-                    aux(pattern, path)
+    pub fn matches(&self, path: &[String]) -> bool {
+        self.matches_path(
+            &path
+                .iter()
+                .map(|chunk| Some(chunk.as_str()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Matches a path whose chunks are optional: `None` chunks can
+    /// only be matched by the globs `*` and `**`. This takes a time
+    /// linear in the length of the path: `states[i]` is set when the
+    /// `i` first chunks of the namespace match the chunks of the path
+    /// read so far.
+    pub fn matches_path(&self, path: &[Option<&str>]) -> bool {
+        let n = self.chunks.len();
+        let close = |states: &mut Vec<bool>| {
+            for i in 0..n {
+                if states[i] && matches!(self.chunks[i], NamespaceChunk::Glob(Glob::Many)) {
+                    states[i + 1] = true;
                 }
-                ([NamespaceChunk::Glob(Glob::Many), pattern @ ..], []) => aux(pattern, path),
-                ([NamespaceChunk::Glob(Glob::Many), pattern_tl @ ..], [_path_hd, path_tl @ ..]) => {
-                    aux(pattern_tl, path) || aux(pattern, path_tl)
+            }
+        };
+        let mut states = vec![false; n + 1];
+        states[0] = true;
+        close(&mut states);
+        for chunk in path {
+            let mut next = vec![false; n + 1];
+            for i in (0..n).filter(|i| states[*i]) {
+                match (&self.chunks[i], chunk) {
+                    (NamespaceChunk::Glob(Glob::Many), _) => next[i] = true,
+                    (NamespaceChunk::Glob(Glob::One), _) => next[i + 1] = true,
+                    (pattern, Some(chunk)) if pattern.matches(chunk) => next[i + 1] = true,
+                    _ => (),
                 }
-                _ => false,
             }
+            close(&mut next);
+            states = next;
         }
-        aux(self.chunks.as_slice(), path.as_slice())
+        states[n]
     }
 }
 
+/// A flavour of MIR, in the order of the MIR pipeline: each one is
+/// computed from the previous one.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+pub enum MirFlavour {
+    Built,
+    Promoted,
+    Elaborated,
+    Optimized,
+    Ctfe,
+}
+
 #[derive(Debug, Clone)]
 pub struct Options {
     pub inline_macro_calls: Vec<Namespace>,
+    /// Whether the bodies of the constants and statics that call
+    /// `const fn`s are replaced by their values.
+    pub evaluate_const_fns: bool,
+    /// The flavours of MIR exported for each body, if MIR bodies are
+    /// exported at all.
+    pub mir_flavours: Vec<MirFlavour>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(namespace: &str, path: &str) -> bool {
+        let namespace: Namespace = namespace.parse().unwrap();
+        let path: Vec<String> = path.split("::").map(String::from).collect();
+        namespace.matches(&path)
+    }
+
+    #[test]
+    fn globs() {
+        assert!(matches("k::*", "k::f"));
+        assert!(!matches("k::*", "k::m::f"));
+        assert!(matches("k::**", "k::m::f"));
+        assert!(matches("k::**::f", "k::f"));
+        assert!(matches("**::f", "k::m::f"));
+        assert!(!matches("**::f", "k::m::g"));
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(matches("k::test_*", "k::test_add"));
+        assert!(!matches("k::test_*", "k::add"));
+        assert!(matches("k::f?", "k::f1"));
+        assert!(!matches("k::f?", "k::f12"));
+        assert!(matches("k::[a-c_]x", "k::_x"));
+        assert!(!matches("k::[a-c_]x", "k::dx"));
+        assert!(matches("k::[!t]est", "k::best"));
+        assert!(!matches("k::[!t]est", "k::test"));
+        assert!(matches("k::{ntt,poly}_*", "k::poly_mul"));
+        assert!(!matches("k::{ntt,poly}_*", "k::vec_mul"));
+        assert!(matches("k::{a{b,c},d}", "k::ac"));
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert!(matches("k::caf?", "k::café"));
+        assert!(matches("k::caf[!e]", "k::café"));
+        assert!(!matches("k::caf[!é]", "k::café"));
+        assert!(matches("k::na?ve", "k::naïve"));
+        assert!(matches("k::[日-月]?", "k::日本"));
+    }
+
+    #[test]
+    fn round_trip() {
+        for pattern in ["k::**::[!a-z_]?*", "{ntt,poly}::f", "k::*::g"] {
+            let namespace: Namespace = pattern.parse().unwrap();
+            assert_eq!(namespace.to_string(), pattern);
+        }
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!("k::[a-z".parse::<Namespace>().is_err());
+        assert!("k::{a,b".parse::<Namespace>().is_err());
+    }
 }
@@ -28,6 +28,25 @@ mod module {
         })
     }
 
+    /// Whether a THIR body calls a `const fn`. The constructors of
+    /// tuple structs and variants are `const fn`s too, but calling
+    /// them builds a value that needs no evaluation.
+    fn calls_const_fn<'tcx>(
+        tcx: rustc_middle::ty::TyCtxt<'tcx>,
+        thir: &rustc_middle::thir::Thir<'tcx>,
+    ) -> bool {
+        use rustc_hir::def::DefKind;
+        thir.exprs.iter().any(|expr| match expr.kind {
+            rustc_middle::thir::ExprKind::Call { ty, .. } => match ty.kind() {
+                rustc_middle::ty::FnDef(def_id, _) => {
+                    !matches!(tcx.def_kind(*def_id), DefKind::Ctor(..)) && tcx.is_const_fn(*def_id)
+                }
+                _ => false,
+            },
+            _ => false,
+        })
+    }
+
     pub trait IsBody: Sized + Clone + 'static {
         fn body<'tcx, S: UnderOwnerState<'tcx>>(did: RLocalDefId, s: &S) -> Self;
     }
@@ -80,6 +99,12 @@ mod module {
                         ty: expr.ty.sinto(s),
                         span: expr.span.sinto(s),
                     }
+                } else if let Some(value) = (s.base().options.evaluate_const_fns
+                    && calls_const_fn(s.base().tcx, &thir))
+                .then(|| eval_global(s, did.to_def_id()))
+                .flatten()
+                {
+                    value.into()
                 } else {
                     expr.sinto(&with_owner_id(s.base(), thir, (), did.to_def_id()))
                 }
@@ -95,16 +120,55 @@ mod module {
         impl<MirKind: IsMirKind + Clone + 'static> IsBody for MirBody<MirKind> {
             fn body<'tcx, S: UnderOwnerState<'tcx>>(did: RLocalDefId, s: &S) -> Self {
                 let (thir, _) = get_thir(did, s);
-                let mir = MirKind::get_mir(s.base().tcx, did, |body| {
-                    let body = Rc::new(body.clone());
-                    body.sinto(&with_owner_id(
-                        s.base(),
-                        thir,
-                        body.clone(),
-                        did.to_def_id(),
-                    ))
-                });
-                mir.s_unwrap(s)
+                let kind = std::any::TypeId::of::<MirKind>();
+                let cached =
+                    s.with_item_cache(did.to_def_id(), |caches| caches.mir.get(&kind).cloned());
+                let mir = cached
+                    .or_else(|| {
+                        let body =
+                            MirKind::get_mir(s.base().tcx, did, |body| Rc::new(body.clone()))?;
+                        s.with_item_cache(did.to_def_id(), |caches| {
+                            caches.mir.insert(kind, body.clone())
+                        });
+                        Some(body)
+                    })
+                    .map(|body| {
+                        body.sinto(&with_owner_id(
+                            s.base(),
+                            thir,
+                            body.clone(),
+                            did.to_def_id(),
+                        ))
+                    });
+                mir.s_expect(
+                    s,
+                    &format!(
+                        "no MIR body of kind {} (either stolen or not applicable)",
+                        std::any::type_name::<MirKind>()
+                    ),
+                )
+            }
+        }
+        impl IsBody for Vec<AnyMirBody> {
+            fn body<'tcx, S: UnderOwnerState<'tcx>>(did: RLocalDefId, s: &S) -> Self {
+                use hax_frontend_exporter_options::MirFlavour;
+                s.base()
+                    .options
+                    .mir_flavours
+                    .iter()
+                    // Runtime functions have no CTFE MIR
+                    .filter(|flavour| {
+                        **flavour != MirFlavour::Ctfe
+                            || s.base().tcx.hir().body_const_context(did).is_some()
+                    })
+                    .map(|flavour| match flavour {
+                        MirFlavour::Built => AnyMirBody::Built(IsBody::body(did, s)),
+                        MirFlavour::Promoted => AnyMirBody::Promoted(IsBody::body(did, s)),
+                        MirFlavour::Elaborated => AnyMirBody::Elaborated(IsBody::body(did, s)),
+                        MirFlavour::Optimized => AnyMirBody::Optimized(IsBody::body(did, s)),
+                        MirFlavour::Ctfe => AnyMirBody::Ctfe(IsBody::body(did, s)),
+                    })
+                    .collect()
             }
         }
     }
@@ -1,22 +1,33 @@
+//! Non-doc comments. They are attached to the item, field, variant,
+//! match arm or statement that directly follows them: a comment
+//! belongs to a node if only whitespace, doc comments, outer
+//! attributes and other comments separate it from the node, and if no
+//! blank line does.
+
 use crate::prelude::*;
 use rustc_lexer::TokenKind;
 use std::fs;
 
+/// Strips the delimiters of a comment.
+fn clean_comment(comment: &str) -> &str {
+    let comment = if let Some(comment) = comment.strip_prefix("/*") {
+        comment
+            .strip_suffix("*/")
+            .expect("A comment that starts with `/*` should always ends with `*/`")
+    } else {
+        comment
+            .strip_prefix("//")
+            .expect("A comment has to start with `//` or `/*`")
+    };
+    comment.strip_prefix("!").unwrap_or(comment)
+}
+
 /// Returns a list of (spanned) comments found in file `path`, or an
 /// error if the file at `path` could not be open.
-pub fn comments_of_file(path: PathBuf) -> std::io::Result<Vec<(Span, String)>> {
-    fn clean_comment(comment: &str) -> &str {
-        let comment = if let Some(comment) = comment.strip_prefix("/*") {
-            comment
-                .strip_suffix("*/")
-                .expect("A comment that starts with `/*` should always ends with `*/`")
-        } else {
-            comment
-                .strip_prefix("//")
-                .expect("A comment has to start with `//` or `/*`")
-        };
-        comment.strip_prefix("!").unwrap_or(comment)
-    }
+pub fn comments_of_file<'tcx, S: BaseState<'tcx>>(
+    s: &S,
+    path: PathBuf,
+) -> std::io::Result<Vec<(Span, String)>> {
     let source = &fs::read_to_string(&path)?;
 
     let mut comments = vec![];
@@ -35,15 +46,122 @@ pub fn comments_of_file(path: PathBuf) -> std::io::Result<Vec<(Span, String)>> {
 
         if let TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } = token.kind {
             if !sub.starts_with("///") && !sub.starts_with("/**") {
-                let span = Span {
+                let contents = SpanContents {
                     lo,
                     hi: Loc { line, col },
                     filename: FileName::Real(RealFileName::LocalPath(path.clone())),
-                    rust_span_data: None,
                 };
+                let span = Span::new(s, contents, None);
                 comments.push((span, clean_comment(sub).to_string()));
             }
         }
     }
     Ok(comments)
 }
+
+/// The tokens of a source file, with their byte offsets.
+pub type FileTokens = Rc<Vec<(usize, TokenKind)>>;
+
+/// The tokens of `file`, if its source is available.
+fn file_tokens<'tcx, S: BaseState<'tcx>>(
+    s: &S,
+    file: &rustc_span::SourceFile,
+) -> Option<FileTokens> {
+    if let Some(tokens) = s.with_global_cache(|cache| cache.file_tokens.get(&file.stable_id).cloned())
+    {
+        return Some(tokens);
+    }
+    let mut pos = 0;
+    let tokens: Vec<_> = rustc_lexer::tokenize(file.src.as_ref()?)
+        .map(|token| {
+            let start = pos;
+            pos += token.len as usize;
+            (start, token.kind)
+        })
+        .collect();
+    let tokens = Rc::new(tokens);
+    s.with_global_cache(|cache| cache.file_tokens.insert(file.stable_id, tokens.clone()));
+    Some(tokens)
+}
+
+/// The comments attached to the node whose source starts at `span`,
+/// in source order.
+pub(crate) fn comments_before<'tcx, S: BaseState<'tcx>>(
+    s: &S,
+    span: rustc_span::Span,
+) -> Vec<Comment> {
+    let span = span.source_callsite();
+    if span.is_dummy() {
+        return vec![];
+    }
+    let rustc_span::SourceFileAndBytePos { sf: file, pos } =
+        s.base().tcx.sess.source_map().lookup_byte_offset(span.lo());
+    let (Some(source), Some(tokens)) = (file.src.as_ref(), file_tokens(s, &file)) else {
+        return vec![];
+    };
+    let Ok(mut i) = tokens.binary_search_by_key(&(pos.0 as usize), |(start, _)| *start) else {
+        return vec![];
+    };
+    let mut comments = vec![];
+    while i > 0 {
+        i -= 1;
+        let (start, kind) = tokens[i];
+        match kind {
+            // A blank line separates the comments above from the node
+            TokenKind::Whitespace
+                if source[start..tokens[i + 1].0]
+                    .chars()
+                    .filter(|c| *c == '\n')
+                    .count()
+                    >= 2 =>
+            {
+                break
+            }
+            TokenKind::Whitespace
+            | TokenKind::LineComment {
+                doc_style: Some(_), ..
+            }
+            | TokenKind::BlockComment {
+                doc_style: Some(_), ..
+            } => (),
+            TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } => comments.push(i),
+            // Skips an outer attribute `#[...]`
+            TokenKind::CloseBracket => {
+                let mut depth = 0;
+                let open = (0..=i).rev().find(|&j| {
+                    match tokens[j].1 {
+                        TokenKind::CloseBracket => depth += 1,
+                        TokenKind::OpenBracket => depth -= 1,
+                        _ => (),
+                    };
+                    depth == 0
+                });
+                match open {
+                    Some(j) if j > 0 && tokens[j - 1].1 == TokenKind::Pound => i = j - 1,
+                    _ => break,
+                }
+            }
+            _ => break,
+        }
+    }
+    comments
+        .into_iter()
+        .rev()
+        .map(|i| {
+            let (start, kind) = tokens[i];
+            let end = tokens.get(i + 1).map_or(source.len(), |(end, _)| *end);
+            let span = rustc_span::Span::with_root_ctxt(
+                file.start_pos + rustc_span::BytePos(start as u32),
+                file.start_pos + rustc_span::BytePos(end as u32),
+            );
+            Comment {
+                kind: match kind {
+                    TokenKind::LineComment { .. } => CommentKind::Line,
+                    _ => CommentKind::Block,
+                },
+                contents: clean_comment(&source[start..end]).to_string(),
+                span: span.sinto(s),
+            }
+        })
+        .collect()
+}
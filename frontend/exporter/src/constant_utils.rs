@@ -284,7 +284,6 @@ mod rustc {
         scalar: &rustc_middle::mir::interpret::Scalar,
         span: rustc_span::Span,
     ) -> ConstantExpr {
-        use rustc_middle::mir::Mutability;
         let cspan = span.sinto(s);
         // The documentation explicitly says not to match on a scalar.
         // We match on the type and use it to convert the value.
@@ -311,55 +310,15 @@ mod rustc {
                 let lit = bits_and_type_to_float_constant_literal(data, float_type.sinto(s));
                 ConstantExprKind::Literal(lit)
             }
-            ty::Ref(_, inner_ty, Mutability::Not) | ty::RawPtr(inner_ty, Mutability::Mut) => {
-                let tcx = s.base().tcx;
-                let pointer = scalar.to_pointer(&tcx).unwrap_or_else(|_| {
+            ty::Ref(..) | ty::RawPtr(..) | ty::FnPtr(..) => {
+                let pointer = scalar.to_pointer(&s.base().tcx).unwrap_or_else(|_| {
                     fatal!(
                         s[span],
-                        "Type is [Ref] or [RawPtr], but the scalar {:#?} is not a [Pointer]",
+                        "Type is [Ref], [RawPtr] or [FnPtr], but the scalar {:#?} is not a [Pointer]",
                         scalar
                     )
                 });
-                use rustc_middle::mir::interpret::GlobalAlloc;
-                let contents = match tcx.global_alloc(pointer.provenance.s_unwrap(s).alloc_id()) {
-                    GlobalAlloc::Static(did) => ConstantExprKind::GlobalName {
-                        id: did.sinto(s),
-                        generics: Vec::new(),
-                        trait_refs: Vec::new(),
-                    },
-                    GlobalAlloc::Memory(alloc) => {
-                        let bytes = alloc
-                            .inner()
-                            .get_bytes_unchecked(rustc_middle::mir::interpret::AllocRange {
-                                start: rustc_abi::Size::ZERO,
-                                size: alloc.inner().size(),
-                            })
-                            .to_vec();
-                        match inner_ty.kind() {
-                            ty::Slice(slice_ty) | ty::Array(slice_ty, _)
-                                if matches!(slice_ty.kind(), ty::Uint(ty::UintTy::U8)) =>
-                            {
-                                ConstantExprKind::Literal(ConstantLiteral::ByteStr(bytes))
-                            }
-                            _ => ConstantExprKind::Memory(bytes),
-                        }
-                    }
-                    provenance => fatal!(
-                        s[span],
-                        "Expected provenance to be `GlobalAlloc::Static` or \
-                        `GlobalAlloc::Memory`, got {:#?} instead",
-                        provenance
-                    ),
-                };
-                let contents = contents.decorate(inner_ty.sinto(s), cspan.clone());
-                match ty.kind() {
-                    ty::Ref(..) => ConstantExprKind::Borrow(contents),
-                    ty::RawPtr(_, mutability) => ConstantExprKind::RawBorrow {
-                        arg: contents,
-                        mutability: mutability.sinto(s),
-                    },
-                    _ => unreachable!(),
-                }
+                pointer_to_constant_expr_kind(s, ty, pointer, None, span)
             }
             // A [Scalar] might also be any zero-sized [Adt] or [Tuple] (i.e., unit)
             ty::Tuple(ty) if ty.is_empty() => ConstantExprKind::Tuple { fields: vec![] },
@@ -635,55 +594,358 @@ mod rustc {
         kind.decorate(ty.sinto(s), span.sinto(s))
     }
 
-    pub(crate) fn const_value_reference_to_constant_expr<'tcx, S: UnderOwnerState<'tcx>>(
+    /// Translates a pointer of type `ty` (a reference, a raw pointer
+    /// or a function pointer): references to statics become their
+    /// names, other pointees are decoded. `len` is the metadata of
+    /// wide pointers to slices and `str`.
+    fn pointer_to_constant_expr_kind<'tcx, S: UnderOwnerState<'tcx>>(
         s: &S,
-        ty: rustc_middle::ty::Ty<'tcx>,
-        val: rustc_middle::mir::ConstValue<'tcx>,
+        ty: ty::Ty<'tcx>,
+        pointer: rustc_middle::mir::interpret::Pointer<
+            Option<rustc_middle::mir::interpret::CtfeProvenance>,
+        >,
+        len: Option<u64>,
         span: rustc_span::Span,
-    ) -> ConstantExpr {
+    ) -> ConstantExprKind {
+        use rustc_middle::mir::interpret::GlobalAlloc;
         let tcx = s.base().tcx;
+        let (provenance, offset) = pointer.into_parts();
+        let Some(provenance) = provenance else {
+            // The pointer is an integer address: `address as *const T`
+            use crate::rustc_type_ir::inherent::Ty;
+            let usize_ty = ty::Ty::new_usize(tcx).sinto(s);
+            let lit = ConstantLiteral::Int(ConstantInt::Uint(offset.bytes().into(), UintTy::Usize));
+            return ConstantExprKind::Cast {
+                source: ConstantExprKind::Literal(lit).decorate(usize_ty, span.sinto(s)),
+            };
+        };
+        let global_alloc = tcx.global_alloc(provenance.alloc_id());
+        if let ty::FnPtr(..) = ty.kind() {
+            let GlobalAlloc::Function { instance } = global_alloc else {
+                fatal!(s[span], "Expected a function, got {:#?}", global_alloc)
+            };
+            let (def_id, generics, generics_impls, method_impl) =
+                get_function_from_def_id_and_generics(s, instance.def_id(), instance.args);
+            return ConstantExprKind::FnPtr {
+                def_id,
+                generics,
+                generics_impls,
+                method_impl,
+            };
+        }
+        let pointee_ty = ty
+            .builtin_deref(true)
+            .s_expect(s, "pointer_to_constant_expr_kind: expected a pointer type");
+        let contents = match global_alloc {
+            // The anonymous statics rustc creates for the nested
+            // allocations of a static are inlined
+            GlobalAlloc::Static(did)
+                if !matches!(
+                    tcx.def_kind(did),
+                    rustc_hir::def::DefKind::Static { nested: true, .. }
+                ) =>
+            {
+                ConstantExprKind::GlobalName {
+                    id: did.sinto(s),
+                    generics: Vec::new(),
+                    trait_refs: Vec::new(),
+                }
+                .decorate(pointee_ty.sinto(s), span.sinto(s))
+            }
+            GlobalAlloc::Static(did) => {
+                let alloc = tcx.eval_static_initializer(did).s_unwrap(s);
+                pointee_to_constant_expr(s, alloc.inner(), offset, pointee_ty, len, span)
+            }
+            GlobalAlloc::Memory(alloc) => {
+                pointee_to_constant_expr(s, alloc.inner(), offset, pointee_ty, len, span)
+            }
+            GlobalAlloc::Function { .. } | GlobalAlloc::VTable(..) => fatal!(
+                s[span],
+                "Expected provenance to be `GlobalAlloc::Static` or \
+                `GlobalAlloc::Memory`, got {:#?} instead",
+                global_alloc
+            ),
+        };
         match ty.kind() {
-            ty::TyKind::Tuple(_) | ty::TyKind::Adt(..) => {
-                let dc = tcx
-                    .try_destructure_mir_constant_for_user_output(val, ty)
+            ty::Ref(..) => ConstantExprKind::Borrow(contents),
+            ty::RawPtr(_, mutability) => ConstantExprKind::RawBorrow {
+                arg: contents,
+                mutability: mutability.sinto(s),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Decodes the pointee (of type `ty`) of a reference or a raw
+    /// pointer. Strings and byte arrays become literals.
+    fn pointee_to_constant_expr<'tcx, S: UnderOwnerState<'tcx>>(
+        s: &S,
+        alloc: &rustc_middle::mir::interpret::Allocation,
+        offset: rustc_abi::Size,
+        ty: ty::Ty<'tcx>,
+        len: Option<u64>,
+        span: rustc_span::Span,
+    ) -> ConstantExpr {
+        let bytes = |len: u64| {
+            let start = offset.bytes_usize();
+            alloc
+                .inspect_with_uninit_and_ptr_outside_interpreter(start..start + len as usize)
+                .to_vec()
+        };
+        let is_u8 = |ty: &ty::Ty<'tcx>| matches!(ty.kind(), ty::Uint(ty::UintTy::U8));
+        let kind = match (ty.kind(), len) {
+            (ty::Str, Some(len)) => {
+                ConstantExprKind::Literal(ConstantLiteral::byte_str(bytes(len)))
+            }
+            (ty::Slice(elem_ty), Some(len)) if is_u8(elem_ty) => {
+                ConstantExprKind::Literal(ConstantLiteral::ByteStr(bytes(len)))
+            }
+            (ty::Array(elem_ty, len), _) if is_u8(elem_ty) => {
+                let len = len.try_to_target_usize(s.base().tcx).s_unwrap(s);
+                ConstantExprKind::Literal(ConstantLiteral::ByteStr(bytes(len)))
+            }
+            (ty::Slice(elem_ty), Some(len)) => {
+                let tcx = s.base().tcx;
+                let elem_layout = tcx
+                    .layout_of(ty::ParamEnv::reveal_all().and(*elem_ty))
                     .s_unwrap(s);
+                ConstantExprKind::Array {
+                    fields: (0..len)
+                        .map(|i| {
+                            let offset = offset + elem_layout.size * i;
+                            alloc_to_constant_expr(s, alloc, offset, *elem_ty, span)
+                        })
+                        .collect(),
+                }
+            }
+            _ => return alloc_to_constant_expr(s, alloc, offset, ty, span),
+        };
+        kind.decorate(ty.sinto(s), span.sinto(s))
+    }
 
-                // Iterate over the fields, which should be values
-                // Below: we are mutually recursive with [const_value_to_constant_expr],
-                // which takes a [Const] as input, but it should be
-                // ok because we call it on a strictly smaller value.
-                let fields = dc
-                    .fields
-                    .iter()
-                    .copied()
-                    .map(|(val, ty)| const_value_to_constant_expr(s, ty, val, span));
-
-                let kind = match ty.kind() {
-                    ty::TyKind::Tuple(_) => {
-                        assert!(dc.variant.is_none());
-                        let fields = fields.collect();
-                        ConstantExprKind::Tuple { fields }
+    /// The variant of the value of type `ty` (an enum or a struct)
+    /// stored at `offset` in `alloc`, read from its tag.
+    fn read_variant<'tcx>(
+        tcx: ty::TyCtxt<'tcx>,
+        alloc: &rustc_middle::mir::interpret::Allocation,
+        offset: rustc_abi::Size,
+        def: ty::AdtDef<'tcx>,
+        layout: &rustc_abi::LayoutS<rustc_abi::FieldIdx, rustc_abi::VariantIdx>,
+    ) -> Option<rustc_abi::VariantIdx> {
+        use rustc_abi::{TagEncoding, VariantIdx, Variants};
+        use rustc_middle::mir::interpret::alloc_range;
+        match &layout.variants {
+            Variants::Single { index } => Some(*index),
+            Variants::Multiple {
+                tag,
+                tag_encoding,
+                tag_field,
+                ..
+            } => {
+                let size = tag.size(&tcx);
+                let tag_offset = offset + layout.fields.offset(*tag_field);
+                let tag = alloc
+                    .read_scalar(
+                        &tcx,
+                        alloc_range(tag_offset, size),
+                        size == tcx.data_layout.pointer_size,
+                    )
+                    .ok()?
+                    .try_to_scalar_int();
+                let tag = match (tag, tag_encoding) {
+                    (Ok(tag), _) => tag.to_bits(size),
+                    // The niche is a pointer: as a pointer into an
+                    // allocation is never null, the value is the
+                    // untagged variant
+                    (
+                        Err(_),
+                        TagEncoding::Niche {
+                            untagged_variant, ..
+                        },
+                    ) => return Some(*untagged_variant),
+                    (Err(_), TagEncoding::Direct) => return None,
+                };
+                match tag_encoding {
+                    TagEncoding::Direct => def
+                        .discriminants(tcx)
+                        .find(|(_, discr)| size.truncate(discr.val) == tag)
+                        .map(|(variant, _)| variant),
+                    TagEncoding::Niche {
+                        untagged_variant,
+                        niche_variants,
+                        niche_start,
+                    } => {
+                        let relative = size.truncate(tag.wrapping_sub(*niche_start));
+                        let start = niche_variants.start().as_u32();
+                        let count = niche_variants.end().as_u32() - start;
+                        Some(if relative <= count as u128 {
+                            VariantIdx::from_u32(start + relative as u32)
+                        } else {
+                            *untagged_variant
+                        })
                     }
-                    ty::TyKind::Adt(adt_def, ..) => {
-                        let variant = dc.variant.unwrap_or(rustc_target::abi::FIRST_VARIANT);
-                        let variants_info = get_variant_information(adt_def, variant, s);
-                        let fields = fields
-                            .zip(&adt_def.variant(variant).fields)
-                            .map(|(value, field)| ConstantFieldExpr {
-                                field: field.did.sinto(s),
-                                value,
-                            })
-                            .collect();
+                }
+            }
+        }
+    }
+
+    /// Decodes the value of type `ty` stored at `offset` in the
+    /// allocation `alloc`, following the layout rustc computed for
+    /// `ty`. The values that have no structure (e.g. unions) are
+    /// exported as raw memory.
+    pub(crate) fn alloc_to_constant_expr<'tcx, S: UnderOwnerState<'tcx>>(
+        s: &S,
+        alloc: &rustc_middle::mir::interpret::Allocation,
+        offset: rustc_abi::Size,
+        ty: ty::Ty<'tcx>,
+        span: rustc_span::Span,
+    ) -> ConstantExpr {
+        use rustc_middle::mir::interpret::alloc_range;
+        let tcx = s.base().tcx;
+        let param_env = ty::ParamEnv::reveal_all();
+        let ty = tcx.normalize_erasing_regions(param_env, ty);
+        let layout = tcx.layout_of(param_env.and(ty)).s_unwrap(s);
+        let field = |field_offset, field_ty| {
+            alloc_to_constant_expr(s, alloc, offset + field_offset, field_ty, span)
+        };
+        let bytes = || {
+            let range = offset.bytes_usize()..(offset + layout.size).bytes_usize();
+            alloc
+                .inspect_with_uninit_and_ptr_outside_interpreter(range)
+                .to_vec()
+        };
+        let memory = || ConstantExprKind::Memory(bytes());
+        let kind = match ty.kind() {
+            ty::Ref(_, pointee_ty, _) | ty::RawPtr(pointee_ty, _)
+                if !pointee_ty.is_sized(tcx, param_env) =>
+            {
+                let pointer_size = tcx.data_layout.pointer_size;
+                let pointer = alloc
+                    .read_scalar(&tcx, alloc_range(offset, pointer_size), true)
+                    .s_unwrap(s)
+                    .to_pointer(&tcx)
+                    .discard_err()
+                    .s_unwrap(s);
+                let len = alloc
+                    .read_scalar(
+                        &tcx,
+                        alloc_range(offset + pointer_size, pointer_size),
+                        false,
+                    )
+                    .s_unwrap(s)
+                    .to_target_usize(&tcx)
+                    .discard_err()
+                    .s_unwrap(s);
+                match pointee_ty.kind() {
+                    ty::Str | ty::Slice(_) => {
+                        pointer_to_constant_expr_kind(s, ty, pointer, Some(len), span)
+                    }
+                    _ => ConstantExprKind::Todo(format!("wide pointer to {pointee_ty:?}")),
+                }
+            }
+            ty::Bool
+            | ty::Char
+            | ty::Int(_)
+            | ty::Uint(_)
+            | ty::Float(_)
+            | ty::Ref(..)
+            | ty::RawPtr(..)
+            | ty::FnPtr(..) => {
+                let is_pointer = matches!(ty.kind(), ty::Ref(..) | ty::RawPtr(..) | ty::FnPtr(..));
+                let scalar = alloc
+                    .read_scalar(&tcx, alloc_range(offset, layout.size), is_pointer)
+                    .s_unwrap(s);
+                return scalar_to_constant_expr(s, ty, &scalar, span);
+            }
+            ty::FnDef(..) => {
+                return const_value_to_constant_expr(
+                    s,
+                    ty,
+                    rustc_middle::mir::ConstValue::ZeroSized,
+                    span,
+                )
+            }
+            // Byte arrays (e.g. tables of constants) become byte
+            // strings rather than one node per byte, as behind a
+            // reference
+            ty::Array(elem_ty, _) if matches!(elem_ty.kind(), ty::Uint(ty::UintTy::U8)) => {
+                ConstantExprKind::Literal(ConstantLiteral::ByteStr(bytes()))
+            }
+            ty::Array(elem_ty, _) => match layout.fields {
+                rustc_abi::FieldsShape::Array { stride, count } => ConstantExprKind::Array {
+                    fields: (0..count).map(|i| field(stride * i, *elem_ty)).collect(),
+                },
+                _ => unreachable!(),
+            },
+            ty::Tuple(tys) => ConstantExprKind::Tuple {
+                fields: tys
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| field(layout.fields.offset(i), ty))
+                    .collect(),
+            },
+            ty::Adt(def, args) if !def.is_union() && !def.variants().is_empty() => {
+                let layout = layout.layout.0 .0;
+                match read_variant(tcx, alloc, offset, *def, layout) {
+                    Some(variant) => {
+                        let variant_layout = match &layout.variants {
+                            rustc_abi::Variants::Multiple { variants, .. } => &variants[variant],
+                            rustc_abi::Variants::Single { .. } => layout,
+                        };
                         ConstantExprKind::Adt {
-                            info: variants_info,
-                            fields,
+                            info: get_variant_information(def, variant, s),
+                            fields: def
+                                .variant(variant)
+                                .fields
+                                .iter()
+                                .enumerate()
+                                .map(|(i, field_def)| ConstantFieldExpr {
+                                    field: field_def.did.sinto(s),
+                                    value: field(
+                                        variant_layout.fields.offset(i),
+                                        field_def.ty(tcx, args),
+                                    ),
+                                })
+                                .collect(),
                         }
                     }
-                    _ => unreachable!(),
-                };
-                kind.decorate(ty.sinto(s), span.sinto(s))
+                    None => memory(),
+                }
             }
-            _ => fatal!(s[span], "Expected the type to be tuple or adt: {:?}", val),
+            _ => memory(),
+        };
+        kind.decorate(ty.sinto(s), span.sinto(s))
+    }
+
+    /// The fully evaluated value of a constant or a static, when it
+    /// has no generic parameters.
+    pub(crate) fn eval_global<'tcx, S: UnderOwnerState<'tcx>>(
+        s: &S,
+        def_id: rustc_hir::def_id::DefId,
+    ) -> Option<ConstantExpr> {
+        use rustc_hir::def::DefKind;
+        let tcx = s.base().tcx;
+        if tcx.generics_of(def_id).count() != 0 || tcx.is_foreign_item(def_id) {
+            return None;
+        }
+        let ty = tcx.type_of(def_id).instantiate_identity();
+        let span = tcx.def_span(def_id);
+        match tcx.def_kind(def_id) {
+            DefKind::Static { .. } => {
+                let alloc = tcx.eval_static_initializer(def_id).ok()?;
+                Some(alloc_to_constant_expr(
+                    s,
+                    alloc.inner(),
+                    rustc_abi::Size::ZERO,
+                    ty,
+                    span,
+                ))
+            }
+            DefKind::Const | DefKind::AssocConst => {
+                let value = tcx.const_eval_poly(def_id).ok()?;
+                Some(const_value_to_constant_expr(s, ty, value, span))
+            }
+            _ => None,
         }
     }
 
@@ -696,7 +958,27 @@ mod rustc {
         use rustc_middle::mir::ConstValue;
         match val {
             ConstValue::Scalar(scalar) => scalar_to_constant_expr(s, ty, &scalar, span),
-            ConstValue::Indirect { .. } => const_value_reference_to_constant_expr(s, ty, val, span),
+            ConstValue::Indirect { alloc_id, offset } => {
+                let alloc = s.base().tcx.global_alloc(alloc_id).unwrap_memory();
+                alloc_to_constant_expr(s, alloc.inner(), offset, ty, span)
+            }
+            // Slices of other elements than bytes are decoded element-wise
+            ConstValue::Slice { data, meta }
+                if ty.builtin_deref(true).is_some_and(|pointee| {
+                    matches!(pointee.kind(), ty::Slice(elem_ty) if !matches!(elem_ty.kind(), ty::Uint(ty::UintTy::U8)))
+                }) =>
+            {
+                let pointee_ty = ty.builtin_deref(true).unwrap();
+                let contents = pointee_to_constant_expr(
+                    s,
+                    data.inner(),
+                    rustc_abi::Size::ZERO,
+                    pointee_ty,
+                    Some(meta),
+                    span,
+                );
+                ConstantExprKind::Borrow(contents).decorate(ty.sinto(s), span.sinto(s))
+            }
             ConstValue::Slice { data, meta } => {
                 let end = meta.try_into().unwrap();
                 // This is outside of the interpreter, so we are okay to use
@@ -1,6 +1,14 @@
 //! Stolen from <https://github.com/Wassasin/deterministic-hash/blob/main/src/lib.rs>
 use core::hash::Hasher;
 
+/// Hashes `x` in a way that doesn't depend on the platform.
+pub fn deterministic_hash<T: std::hash::Hash + ?Sized>(x: &T) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasher;
+    use std::hash::BuildHasherDefault;
+    <BuildHasherDefault<DeterministicHasher<DefaultHasher>>>::default().hash_one(x)
+}
+
 /// Wrapper around any hasher to make it deterministic.
 #[derive(Default)]
 pub struct DeterministicHasher<T: Hasher>(T);
@@ -18,6 +18,7 @@
 /// global state.
 use crate::prelude::*;
 use std::{
+    collections::HashMap,
     hash::{Hash, Hasher},
     sync::{atomic::Ordering, Arc, LazyLock, Mutex, MutexGuard},
 };
@@ -35,6 +36,9 @@ pub struct Id {
 pub struct Session {
     next_id: Id,
     table: Table,
+    /// The IDs of the interned values, indexed by the hash of their
+    /// type and contents (see `Node::intern`).
+    interned: HashMap<u64, Vec<Id>>,
 }
 
 impl Session {
@@ -43,36 +47,59 @@ impl Session {
     }
 }
 
+impl Table {
+    /// The smallest ID greater than every ID of the table. Shifting
+    /// the IDs of another table by this offset makes both tables
+    /// disjoint.
+    pub fn next_id(&self) -> u32 {
+        self.0.keys().map(|id| id.id + 1).max().unwrap_or(0)
+    }
+
+    /// Merges `other` into `self`. The IDs of the two tables are
+    /// expected to be disjoint (see `WithTable::with_id_offset`).
+    pub fn merge(&mut self, other: Table) {
+        for (id, value) in other.0.into_iter() {
+            if self.0.contains_key(&id) {
+                panic!("id_table::Table::merge: the ID {id:?} is present in both tables")
+            }
+            self.0.insert_raw_value(id, value);
+        }
+    }
+}
+
 /// The different types of values one can store in an ID table.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Value {
     Ty(Arc<TyKind>),
     DefId(Arc<DefIdContents>),
+    Span(Arc<SpanContents>),
+    ImplExpr(Arc<ImplExprContents>),
+    GenericPredicates(Arc<GenericPredicatesContents>),
 }
 
-impl SupportedType<Value> for TyKind {
-    fn to_types(value: Arc<Self>) -> Value {
-        Value::Ty(value)
-    }
-    fn from_types(t: &Value) -> Option<Arc<Self>> {
-        match t {
-            Value::Ty(value) => Some(value.clone()),
-            _ => None,
+macro_rules! supported_types {
+    ($($variant:ident($ty:ty)),*$(,)?) => {$(
+        impl SupportedType<Value> for $ty {
+            fn to_types(value: Arc<Self>) -> Value {
+                Value::$variant(value)
+            }
+            fn from_types(t: &Value) -> Option<Arc<Self>> {
+                match t {
+                    Value::$variant(value) => Some(value.clone()),
+                    _ => None,
+                }
+            }
         }
-    }
+    )*};
 }
 
-impl SupportedType<Value> for DefIdContents {
-    fn to_types(value: Arc<Self>) -> Value {
-        Value::DefId(value)
-    }
-    fn from_types(t: &Value) -> Option<Arc<Self>> {
-        match t {
-            Value::DefId(value) => Some(value.clone()),
-            _ => None,
-        }
-    }
-}
+supported_types!(
+    Ty(TyKind),
+    DefId(DefIdContents),
+    Span(SpanContents),
+    ImplExpr(ImplExprContents),
+    GenericPredicates(GenericPredicatesContents),
+);
 
 /// A node is a bundle of an ID with a value.
 #[derive(Deserialize, Serialize, Debug, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
@@ -154,6 +181,12 @@ mod heterogeneous_map {
         pub(super) fn into_iter(self) -> impl Iterator<Item = (Key, Value)> {
             self.0.into_iter()
         }
+        pub(super) fn keys(&self) -> impl Iterator<Item = &Key> {
+            self.0.keys()
+        }
+        pub(super) fn contains_key(&self, key: &Key) -> bool {
+            self.0.contains_key(key)
+        }
         pub(super) fn get<T>(&self, key: &Key) -> Option<Option<Arc<T>>>
         where
             T: SupportedType<Value>,
@@ -187,6 +220,37 @@ impl<T: Sync + Send + 'static + SupportedType<Value>> Node<T> {
         Self { id, value }
     }
 
+    /// Like `Node::new`, but reuses the node of a value equal to
+    /// `value` if one was already interned in this session. Values
+    /// that are shared a lot (spans, impl expressions...) are thus
+    /// stored only once in the table.
+    pub fn intern(value: T, session: &mut Session) -> Self
+    where
+        T: Hash + Eq,
+    {
+        let mut hasher = std::hash::DefaultHasher::new();
+        std::any::TypeId::of::<T>().hash(&mut hasher);
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        for id in session.interned.get(&hash).into_iter().flatten() {
+            if let Some(Some(existing)) = session.table.0.get::<T>(id) {
+                if *existing == value {
+                    return Self {
+                        id: id.clone(),
+                        value: existing,
+                    };
+                }
+            }
+        }
+        let node = Self::new(value, session);
+        session
+            .interned
+            .entry(hash)
+            .or_default()
+            .push(node.id.clone());
+        node
+    }
+
     pub fn inner(&self) -> &Arc<T> {
         &self.value
     }
@@ -208,6 +272,16 @@ static DESERIALIZATION_STATE: LazyLock<Mutex<Table>> =
     LazyLock::new(|| Mutex::new(Table::default()));
 static DESERIALIZATION_STATE_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
 
+/// An offset added to every ID read while deserializing a `WithTable`.
+static DESERIALIZATION_ID_OFFSET: std::sync::atomic::AtomicU32 =
+    std::sync::atomic::AtomicU32::new(0);
+
+fn shift_id(id: Id) -> Id {
+    Id {
+        id: id.id + DESERIALIZATION_ID_OFFSET.load(Ordering::Relaxed),
+    }
+}
+
 /// The mode of serialization: should `Node<T>` ship values of type `T` or not?
 static SERIALIZATION_MODE_USE_IDS: std::sync::atomic::AtomicBool =
     std::sync::atomic::AtomicBool::new(false);
@@ -229,6 +303,16 @@ impl<T> WithTable<T> {
         SERIALIZATION_MODE_USE_IDS.store(false, Ordering::Relaxed);
         result
     }
+    /// Runs `f`, a function deserializing `WithTable` values, so
+    /// that every ID read is shifted by `offset`. This allows for
+    /// merging tables coming from different sessions (e.g. the
+    /// `haxmeta` files of several crates).
+    pub fn with_id_offset<R>(offset: u32, f: impl FnOnce() -> R) -> R {
+        let previous = DESERIALIZATION_ID_OFFSET.swap(offset, Ordering::Relaxed);
+        let result = f();
+        DESERIALIZATION_ID_OFFSET.store(previous, Ordering::Relaxed);
+        result
+    }
     pub fn destruct(self) -> (T, Table) {
         let Self { value, table: map } = self;
         (value, map)
@@ -301,7 +385,7 @@ mod serde_repr {
         fn try_from(cached: NodeRepr<T>) -> Result<Self, Self::Error> {
             use serde::de::Error;
             let table = DESERIALIZATION_STATE.lock().unwrap();
-            let id = cached.id;
+            let id = shift_id(cached.id);
             let kind = if let Some(kind) = cached.value {
                 kind
             } else {
@@ -331,6 +415,7 @@ mod serde_repr {
             D: serde::Deserializer<'de>,
         {
             let (id, v) = <(Id, Value)>::deserialize(deserializer)?;
+            let id = shift_id(id);
             DESERIALIZATION_STATE
                 .lock()
                 .unwrap()
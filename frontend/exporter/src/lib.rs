@@ -44,12 +44,12 @@ cfg_feature_rustc! {
     mod rustc_utils;
     pub mod state;
     mod utils;
-    mod deterministic_hash;
     pub mod comments;
 }
 
 mod body;
 mod constant_utils;
+pub mod deterministic_hash;
 pub mod id_table;
 mod types;
 
@@ -16,6 +16,7 @@ pub use crate::types::*;
 pub use self::rustc::*;
 #[cfg(feature = "rustc")]
 pub mod rustc {
+    pub(crate) use crate::comments::comments_before;
     pub use crate::rustc_utils::*;
     pub use crate::state::*;
     pub use crate::utils::*;
@@ -57,7 +57,7 @@ pub(crate) fn get_variant_information<'s, S: UnderOwnerState<'s>>(
 pub enum ReadSpanErr {
     NotRealFileName(String),
     WhileReading(std::io::Error),
-    NotEnoughLines { span: Span },
+    NotEnoughLines { span: SpanContents },
 }
 impl std::convert::From<std::io::Error> for ReadSpanErr {
     fn from(value: std::io::Error) -> Self {
@@ -66,7 +66,7 @@ impl std::convert::From<std::io::Error> for ReadSpanErr {
 }
 
 #[tracing::instrument]
-pub(crate) fn read_span_from_file(span: &Span) -> Result<String, ReadSpanErr> {
+pub(crate) fn read_span_from_file(span: &SpanContents) -> Result<String, ReadSpanErr> {
     use ReadSpanErr::*;
     let realpath = (match span.filename.clone() {
         FileName::Real(RealFileName::LocalPath(path)) => Ok(path),
@@ -103,18 +103,17 @@ pub(crate) fn read_span_from_file(span: &Span) -> Result<String, ReadSpanErr> {
 }
 
 #[tracing::instrument(skip(sess))]
-pub fn translate_span(span: rustc_span::Span, sess: &rustc_session::Session) -> Span {
+pub fn translate_span(span: rustc_span::Span, sess: &rustc_session::Session) -> SpanContents {
     let smap: &rustc_span::source_map::SourceMap = sess.psess.source_map();
     let filename = smap.span_to_filename(span);
 
     let lo = smap.lookup_char_pos(span.lo());
     let hi = smap.lookup_char_pos(span.hi());
 
-    Span {
+    SpanContents {
         lo: lo.into(),
         hi: hi.into(),
         filename: filename.sinto(&()),
-        rust_span_data: Some(span.data()),
     }
 }
 
@@ -147,7 +146,7 @@ pub(crate) fn raw_macro_invocation_of_span<'t, S: BaseState<'t>>(
         let call_site = translate_span(expn_data.call_site, sess);
         match (expn_data.kind, expn_data.macro_def_id) {
             (rustc_span::hygiene::ExpnKind::Macro(_, _), Some(mac_def_id))
-                if macro_calls.keys().any(|span| span.clone() == call_site) =>
+                if macro_calls.contains_key(&call_site) =>
             {
                 let macro_ident: DefId = mac_def_id.sinto(state);
                 let path = Path::from(macro_ident.clone());
@@ -236,25 +235,150 @@ pub fn inline_macro_invocations<'t, S: BaseState<'t>, Body: IsBody>(
             Some((macro_ident, expn_data)) => {
                 let owner_id: rustc_hir::hir_id::OwnerId =
                     items.into_iter().map(|x| x.owner_id).next().s_unwrap(s);
-                let invocation =
-                    macro_invocation_of_raw_mac_invocation(&macro_ident, &expn_data, s);
-                let span = expn_data.call_site.sinto(s);
-                let owner_id: DefId = owner_id.sinto(s);
-                vec![Item {
-                    def_id: None,
-                    owner_id,
-                    kind: ItemKind::MacroInvokation(invocation),
-                    span,
-                    vis_span: rustc_span::DUMMY_SP.sinto(s),
-                    attributes: ItemAttributes::new(),
-                    expn_backtrace: vec![],
-                }]
+                let item = recover_export_error(|| {
+                    let invocation =
+                        macro_invocation_of_raw_mac_invocation(&macro_ident, &expn_data, s);
+                    Item {
+                        def_id: None,
+                        owner_id: owner_id.sinto(s),
+                        kind: ItemKind::MacroInvokation(invocation),
+                        span: expn_data.call_site.sinto(s),
+                        vis_span: rustc_span::DUMMY_SP.sinto(s),
+                        visibility: tcx.visibility(owner_id).sinto(s),
+                        attributes: ItemAttributes::new(),
+                        comments: comments_before(s, expn_data.call_site),
+                        expn_backtrace: vec![],
+                    }
+                })
+                .unwrap_or_else(|reason| {
+                    Item::export_error(s, owner_id, None, expn_data.call_site, reason)
+                });
+                vec![item]
             }
             _ => items.map(|item| item.sinto(s)).collect(),
         })
         .collect()
 }
 
+/// Extracts the arguments of a macro invocation out of its source
+/// text, without their delimiters.
+fn macro_arguments(kind: rustc_span::hygiene::MacroKind, source: &str) -> Option<String> {
+    use rustc_span::hygiene::MacroKind;
+    let strip_delimiters = |args: &str| {
+        let args = args.trim();
+        let mut chars = args.chars();
+        match (chars.next(), chars.next_back()) {
+            (Some('('), Some(')')) | (Some('['), Some(']')) | (Some('{'), Some('}')) => {
+                args[1..args.len() - 1].trim().to_string()
+            }
+            _ => args.to_string(),
+        }
+    };
+    match kind {
+        // `path!(args)`, or `macro_rules! name { .. }`
+        MacroKind::Bang => Some(strip_delimiters(source.split_once('!')?.1)),
+        // `#[path(args)]`, `#[path = args]` or `#[path]`
+        MacroKind::Attr => {
+            let source = source.strip_prefix('#')?.trim_start();
+            let source = source.strip_prefix('!').unwrap_or(source).trim_start();
+            let inner = source.strip_prefix('[')?.strip_suffix(']')?;
+            Some(match inner.find(['(', '[', '{', '=']) {
+                Some(i) if inner[i..].starts_with('=') => inner[i + 1..].trim().to_string(),
+                Some(i) => strip_delimiters(&inner[i..]),
+                None => String::new(),
+            })
+        }
+        MacroKind::Derive => None,
+    }
+}
+
+/// Collects every macro expansion of the crate, sorted by file and
+/// invocation span. The expansions that produced some HIR node are
+/// resolved; the other macro calls of the source of the crate,
+/// `mac_calls`, are recorded as written: they expanded to nothing or
+/// to some of their arguments only, or are `macro_rules!`
+/// definitions.
+pub fn macro_expansions<'tcx, S: BaseState<'tcx>>(
+    s: &S,
+    mac_calls: &HashMap<rustc_span::Span, rustc_ast::ast::MacCall>,
+) -> Vec<MacroExpansion> {
+    use rustc_hir::intravisit::Visitor;
+    use rustc_span::hygiene::{ExpnData, ExpnKind};
+    let tcx: ty::TyCtxt = s.base().tcx;
+
+    struct Collector<'tcx> {
+        tcx: ty::TyCtxt<'tcx>,
+        seen: std::collections::HashSet<(rustc_span::Span, rustc_span::def_id::DefId)>,
+        expansions: Vec<(
+            rustc_span::def_id::DefId,
+            rustc_span::hygiene::MacroKind,
+            rustc_span::Symbol,
+            ExpnData,
+        )>,
+    }
+    impl<'tcx> Visitor<'tcx> for Collector<'tcx> {
+        type NestedFilter = rustc_middle::hir::nested_filter::All;
+        fn nested_visit_map(&mut self) -> Self::Map {
+            self.tcx.hir()
+        }
+        fn visit_id(&mut self, hir_id: rustc_hir::HirId) {
+            for expn_data in self.tcx.hir().span(hir_id).macro_backtrace() {
+                if let (ExpnKind::Macro(kind, name), Some(def_id)) =
+                    (&expn_data.kind, expn_data.macro_def_id)
+                {
+                    if self.seen.insert((expn_data.call_site, def_id)) {
+                        self.expansions.push((def_id, *kind, *name, expn_data));
+                    }
+                }
+            }
+        }
+    }
+    let mut collector = Collector {
+        tcx,
+        seen: std::collections::HashSet::new(),
+        expansions: vec![],
+    };
+    tcx.hir().walk_toplevel_module(&mut collector);
+
+    let source_map = tcx.sess.source_map();
+    let arguments = |kind, span| {
+        source_map
+            .span_to_snippet(span)
+            .ok()
+            .and_then(|source| macro_arguments(kind, &source))
+    };
+    let resolved_calls: std::collections::HashSet<_> = collector
+        .expansions
+        .iter()
+        .map(|(_, _, _, expn_data)| expn_data.call_site.lo())
+        .collect();
+    let unresolved = mac_calls
+        .iter()
+        .filter(|(span, _)| !resolved_calls.contains(&span.lo()))
+        .map(|(span, mac)| MacroExpansion {
+            path: rustc_ast_pretty::pprust::path_to_string(&mac.path),
+            macro_ident: None,
+            kind: MacroKind::Bang,
+            arguments: arguments(rustc_span::hygiene::MacroKind::Bang, *span),
+            span: span.sinto(s),
+            def_site: None,
+        });
+    collector
+        .expansions
+        .into_iter()
+        .map(|(def_id, kind, name, expn_data)| MacroExpansion {
+            path: name.to_string(),
+            macro_ident: Some(def_id.sinto(s)),
+            kind: kind.sinto(s),
+            arguments: arguments(kind, expn_data.call_site),
+            span: expn_data.call_site.sinto(s),
+            def_site: Some(expn_data.def_site.sinto(s)),
+        })
+        .chain(unresolved)
+        .sorted_by(|x, y| (&x.span.filename, &x.span).cmp(&(&y.span.filename, &y.span)))
+        .collect()
+}
+
 /// Gets the closest ancestor of `id` that is the id of a type.
 pub fn get_closest_parent_type(
     tcx: &ty::TyCtxt,
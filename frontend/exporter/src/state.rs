@@ -128,6 +128,8 @@ mod types {
         pub per_item: HashMap<RDefId, ItemCache<'tcx>>,
         /// A ID table session, providing fresh IDs.
         pub id_table_session: id_table::Session,
+        /// The tokens of the source files, to find comments.
+        pub file_tokens: HashMap<rustc_span::StableSourceFileId, crate::comments::FileTokens>,
     }
 
     /// Defines a mapping from types to types, for use with `TypeMap`.
@@ -154,6 +156,9 @@ mod types {
             Rc<rustc_middle::thir::Thir<'tcx>>,
             rustc_middle::thir::ExprId,
         )>,
+        /// Cache MIR bodies, by kind (see `mir_kinds`): most of them
+        /// are stolen by the next MIR pass.
+        pub mir: HashMap<std::any::TypeId, Rc<rustc_middle::mir::Body<'tcx>>>,
     }
 
     #[derive(Clone)]
@@ -191,7 +196,7 @@ mod types {
         }
     }
 
-    pub type MacroCalls = Rc<HashMap<Span, Span>>;
+    pub type MacroCalls = Rc<HashMap<SpanContents, SpanContents>>;
     pub type RcThir<'tcx> = Rc<rustc_middle::thir::Thir<'tcx>>;
     pub type RcMir<'tcx> = Rc<rustc_middle::mir::Body<'tcx>>;
     pub type UnitBinder<'tcx> = rustc_middle::ty::Binder<'tcx, ()>;
@@ -359,11 +364,13 @@ impl ImplInfos {
     }
 }
 
-/// Returns a map from every implementation (`Impl`) `DefId`s to the
-/// type they implement, plus the bounds.
+/// Returns the list of every implementation (`Impl`) `DefId`s with
+/// the type they implement, plus the bounds. The list is sorted by
+/// `DefPathHash`, so that it doesn't depend on the order in which
+/// items were visited.
 pub fn impl_def_ids_to_impled_types_and_bounds<'tcx, S: BaseState<'tcx>>(
     s: &S,
-) -> HashMap<DefId, ImplInfos> {
+) -> Vec<(DefId, ImplInfos)> {
     let tcx = s.base().tcx;
 
     let def_ids: Vec<_> = s.with_global_cache(|cache| cache.per_item.keys().copied().collect());
@@ -380,6 +387,7 @@ pub fn impl_def_ids_to_impled_types_and_bounds<'tcx, S: BaseState<'tcx>>(
         .into_iter()
         .flat_map(with_parents)
         .unique()
+        .sorted_by_key(|did| tcx.def_path_hash(*did))
         .filter(|&did| {
             // keep only DefIds that corresponds to implementations
             matches!(
@@ -390,6 +398,17 @@ pub fn impl_def_ids_to_impled_types_and_bounds<'tcx, S: BaseState<'tcx>>(
                 })
             )
         })
-        .map(|did| (did.sinto(s), ImplInfos::from(s.base(), did)))
+        .filter_map(|did| {
+            // An impl whose infos can't be exported is left out, with
+            // a warning
+            recover_export_error(|| (did.sinto(s), ImplInfos::from(s.base(), did)))
+                .map_err(|reason| {
+                    tcx.dcx().span_warn(
+                        tcx.def_span(did),
+                        format!("hax: the frontend failed to export the infos of this impl: {reason}"),
+                    )
+                })
+                .ok()
+        })
         .collect()
 }
@@ -113,15 +113,41 @@ pub enum ImplExprAtom {
 /// "hello").clone()` combines the generic implementation of `Clone` for `(A, B)` with the
 /// concrete implementations for `u8` and `&str`, represented as a tree.
 #[derive_group(Serializers)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+#[serde(transparent)]
+pub struct ImplExpr {
+    pub(crate) contents: id_table::Node<ImplExprContents>,
+}
+
+/// The contents of an [`ImplExpr`], shared through the ID table.
+#[derive_group(Serializers)]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema, AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: resolution::ImplExpr<'tcx>, state: S as s)]
-pub struct ImplExpr {
+pub struct ImplExprContents {
     /// The trait this is an impl for.
     pub r#trait: Binder<TraitRef>,
     /// The kind of implemention of the root of the tree.
     pub r#impl: ImplExprAtom,
 }
 
+impl std::ops::Deref for ImplExpr {
+    type Target = ImplExprContents;
+    fn deref(&self) -> &Self::Target {
+        &self.contents
+    }
+}
+
+#[cfg(feature = "rustc")]
+impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, ImplExpr> for resolution::ImplExpr<'tcx> {
+    fn sinto(&self, s: &S) -> ImplExpr {
+        let contents: ImplExprContents = self.sinto(s);
+        let contents = s.with_global_cache(|cache| {
+            id_table::Node::intern(contents, &mut cache.id_table_session)
+        });
+        ImplExpr { contents }
+    }
+}
+
 /// Given a clause `clause` in the context of some impl block `impl_did`, susbts correctly `Self`
 /// from `clause` and (1) derive a `Clause` and (2) resolve an `ImplExpr`.
 #[cfg(feature = "rustc")]
@@ -176,7 +202,7 @@ pub fn solve_trait<'tcx, S: BaseState<'tcx> + HasOwnerId>(
             .get_or_insert_with(|| PredicateSearcher::new_for_owner(s.base().tcx, s.owner_id()))
             .resolve(&trait_ref, &warn)
     });
-    let impl_expr = match resolved {
+    let impl_expr: ImplExpr = match resolved {
         Ok(x) => x.sinto(s),
         Err(e) => crate::fatal!(s, "{}", e),
     };
@@ -48,6 +48,35 @@ pub enum Movability {
     Movable,
 }
 
+/// Reflects [`hir::CoroutineKind`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[args(<'tcx, S>, from: hir::CoroutineKind, state: S as _s)]
+pub enum CoroutineKind {
+    Desugared(CoroutineDesugaring, CoroutineSource),
+    Coroutine(Movability),
+}
+
+/// Reflects [`hir::CoroutineDesugaring`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[args(<'tcx, S>, from: hir::CoroutineDesugaring, state: S as _s)]
+pub enum CoroutineDesugaring {
+    Async,
+    Gen,
+    AsyncGen,
+}
+
+/// Reflects [`hir::CoroutineSource`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[args(<'tcx, S>, from: hir::CoroutineSource, state: S as _s)]
+pub enum CoroutineSource {
+    Block,
+    Closure,
+    Fn,
+}
+
 #[cfg(feature = "rustc")]
 impl<S> SInto<S, Mutability> for hir::Mutability {
     fn sinto(&self, _s: &S) -> Mutability {
@@ -348,6 +377,9 @@ pub struct ImplItem<Body: IsBody> {
     #[value(ItemAttributes::from_owner_id(s, *owner_id))]
     /// the attributes on this impl item
     pub attributes: ItemAttributes,
+    #[value(comments_before(s, *span))]
+    /// The comments right before this impl item
+    pub comments: Vec<Comment>,
 }
 
 /// Reflects [`hir::ImplItemKind`], inlining the body of the items.
@@ -490,6 +522,8 @@ pub struct HirFieldDef {
     pub ty: Ty,
     #[value(s.base().tcx.hir().attrs(*hir_id).sinto(s))]
     attributes: Vec<Attribute>,
+    #[value(comments_before(s, *span))]
+    comments: Vec<Comment>,
 }
 
 /// Reflects [`hir::Variant`]
@@ -517,6 +551,8 @@ pub struct Variant<Body: IsBody> {
     pub span: Span,
     #[value(s.base().tcx.hir().attrs(*hir_id).sinto(s))]
     pub attributes: Vec<Attribute>,
+    #[value(comments_before(s, *span))]
+    pub comments: Vec<Comment>,
 }
 
 /// Reflects [`hir::UsePath`]
@@ -613,6 +649,9 @@ pub struct PathSegment {
 pub enum ItemKind<Body: IsBody> {
     #[disable_mapping]
     MacroInvokation(MacroInvokation),
+    /// The exporter failed to translate this item
+    #[disable_mapping]
+    ExportError(ExportErrorReason),
     ExternCrate(Option<Symbol>),
     Use(UsePath, UseKind),
     Static(Ty, Mutability, Body),
@@ -668,6 +707,63 @@ pub enum ItemKind<Body: IsBody> {
 
 pub type EnumDef<Body> = Vec<Variant<Body>>;
 
+/// Why the exporter failed to translate an item, see
+/// [`ItemKind::ExportError`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum ExportErrorReason {
+    /// A fatal error was reported; it was emitted as a rustc
+    /// warning, so that the crate still compiles
+    Fatal,
+    /// The exporter panicked with the given message
+    Panic(String),
+}
+
+impl std::fmt::Display for ExportErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Fatal => write!(f, "a fatal error was reported, see the warnings above"),
+            Self::Panic(message) => write!(f, "the frontend panicked: {message}"),
+        }
+    }
+}
+
+#[cfg(feature = "rustc")]
+thread_local! {
+    /// Whether [`recover_export_error`] is running
+    static RECOVERING_EXPORT_ERROR: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Whether a fatal error would be recovered by
+/// [`recover_export_error`]: it is then reported as a warning, since
+/// the faulty item is exported as an [`ItemKind::ExportError`] and
+/// shouldn't fail the compilation of the crate.
+#[cfg(feature = "rustc")]
+pub(crate) fn recovering_export_error() -> bool {
+    RECOVERING_EXPORT_ERROR.get()
+}
+
+/// Runs `f`, turning a fatal error or a panic into an
+/// [`ExportErrorReason`], so that one faulty item doesn't abort the
+/// export of the whole crate.
+#[cfg(feature = "rustc")]
+pub fn recover_export_error<T>(f: impl FnOnce() -> T) -> Result<T, ExportErrorReason> {
+    let recovering = RECOVERING_EXPORT_ERROR.replace(true);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    RECOVERING_EXPORT_ERROR.set(recovering);
+    result.map_err(|payload| {
+        if payload.is::<rustc_span::fatal_error::FatalErrorMarker>() {
+            ExportErrorReason::Fatal
+        } else if let Some(message) = payload.downcast_ref::<&str>() {
+            ExportErrorReason::Panic(message.to_string())
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            ExportErrorReason::Panic(message.clone())
+        } else {
+            ExportErrorReason::Panic("unknown panic payload".to_string())
+        }
+    })
+}
+
 /// Reflects [`hir::TraitItemKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::TraitItemKind<'tcx>, state: S as tcx)]
@@ -712,6 +808,9 @@ pub struct TraitItem<Body: IsBody> {
     #[value(ItemAttributes::from_owner_id(s, *owner_id))]
     /// The attributes on this trait item
     pub attributes: ItemAttributes,
+    #[value(comments_before(s, *span))]
+    /// The comments right before this trait item
+    pub comments: Vec<Comment>,
 }
 
 #[cfg(feature = "rustc")]
@@ -910,11 +1009,40 @@ pub struct Item<Body: IsBody> {
     pub owner_id: DefId,
     pub span: Span,
     pub vis_span: Span,
+    pub visibility: Visibility<DefId>,
     pub kind: ItemKind<Body>,
     pub attributes: ItemAttributes,
+    /// The comments right before the item
+    pub comments: Vec<Comment>,
     pub expn_backtrace: Vec<ExpnData>,
 }
 
+#[cfg(feature = "rustc")]
+impl<Body: IsBody> Item<Body> {
+    /// The item standing for the item `owner_id` that the exporter
+    /// failed to translate, see [`ItemKind::ExportError`]
+    pub(crate) fn export_error<'tcx, S: BaseState<'tcx>>(
+        s: &S,
+        owner_id: hir::OwnerId,
+        def_id: Option<DefId>,
+        span: rustc_span::Span,
+        reason: ExportErrorReason,
+    ) -> Self {
+        let module = s.base().tcx.parent_module_from_def_id(owner_id.def_id);
+        Item {
+            def_id,
+            owner_id: owner_id.sinto(s),
+            span: span.sinto(s),
+            vis_span: span.sinto(s),
+            visibility: Visibility::Restricted(module.to_def_id().sinto(s)),
+            kind: ItemKind::ExportError(reason),
+            attributes: ItemAttributes::new(),
+            comments: vec![],
+            expn_backtrace: vec![],
+        }
+    }
+}
+
 #[cfg(feature = "rustc")]
 impl<'tcx, S: BaseState<'tcx>, Body: IsBody> SInto<S, Item<Body>> for hir::Item<'tcx> {
     fn sinto(&self, s: &S) -> Item<Body> {
@@ -924,15 +1052,18 @@ impl<'tcx, S: BaseState<'tcx>, Body: IsBody> SInto<S, Item<Body>> for hir::Item<
         let def_id = Path::from(owner_id.clone())
             .ends_with(&[name])
             .then(|| owner_id.clone());
-        Item {
-            def_id,
+        recover_export_error(|| Item {
+            def_id: def_id.clone(),
             owner_id,
             span: self.span.sinto(s),
             vis_span: self.span.sinto(s),
+            visibility: s.base().tcx.visibility(self.owner_id).sinto(s),
             kind: self.kind.sinto(s),
             attributes: ItemAttributes::from_owner_id(s, self.owner_id),
+            comments: comments_before(s, self.span),
             expn_backtrace: self.span.macro_backtrace().map(|o| o.sinto(s)).collect(),
-        }
+        })
+        .unwrap_or_else(|reason| Item::export_error(s, self.owner_id, def_id, self.span, reason))
     }
 }
 
@@ -1059,6 +1190,34 @@ pub struct MacroInvokation {
     pub span: Span,
 }
 
+/// A macro invocation of the crate, as computed by
+/// [`macro_expansions`]. Unlike [`MacroInvokation`], which only
+/// covers the macros listed with `--inline-macro-call`, every
+/// invocation is recorded, including the ones of macros invoked by
+/// other macros, and the `macro_rules!` definitions.
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct MacroExpansion {
+    /// The path of the macro, as written in the invocation
+    pub path: String,
+    /// The resolved macro. This is `None` for `macro_rules!`
+    /// definitions, and for invocations whose expansion has no node
+    /// of its own, e.g. an expansion to nothing or to one of the
+    /// arguments.
+    pub macro_ident: Option<DefId>,
+    pub kind: MacroKind,
+    /// The source text of the arguments, without their delimiters:
+    /// `a, b` for `m!(a, b)` or `#[m(a, b)]`. This is `None` for
+    /// derives, and when the source of the invocation is not
+    /// available.
+    pub arguments: Option<String>,
+    /// The span of the invocation, i.e. of the code replaced by the
+    /// expansion
+    pub span: Span,
+    /// The span of the macro definition, if the macro is resolved
+    pub def_site: Option<Span>,
+}
+
 /// Reflects [`rustc_ast::token::CommentKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::token::CommentKind, state: S as _s)]
@@ -1069,6 +1228,17 @@ pub enum CommentKind {
     Block,
 }
 
+/// A non-doc comment, attached to the item, field, variant, match arm
+/// or statement it precedes (see [`crate::comments`]).
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct Comment {
+    pub kind: CommentKind,
+    /// The text of the comment, without its delimiters
+    pub contents: String,
+    pub span: Span,
+}
+
 /// Reflects [`rustc_ast::ast::AttrArgs`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::AttrArgs, state: S as tcx)]
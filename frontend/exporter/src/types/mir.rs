@@ -140,7 +140,14 @@ pub mod mir_kinds {
                 id: LocalDefId,
                 f: impl FnOnce(&Body<'tcx>) -> T,
             ) -> Option<T> {
-                Some(f(tcx.optimized_mir(id)))
+                use rustc_hir::ConstContext;
+                // `optimized_mir` is only defined for runtime code:
+                // the optimized MIR of constants and statics is their
+                // CTFE MIR.
+                match tcx.hir().body_const_context(id) {
+                    None | Some(ConstContext::ConstFn) => Some(f(tcx.optimized_mir(id))),
+                    Some(_) => Some(f(tcx.mir_for_ctfe(id))),
+                }
             }
         }
 
@@ -150,7 +157,10 @@ pub mod mir_kinds {
                 id: LocalDefId,
                 f: impl FnOnce(&Body<'tcx>) -> T,
             ) -> Option<T> {
-                Some(f(tcx.mir_for_ctfe(id)))
+                // Runtime functions have no CTFE MIR
+                tcx.hir()
+                    .body_const_context(id)
+                    .map(|_| f(tcx.mir_for_ctfe(id)))
             }
         }
     }
@@ -204,6 +214,20 @@ pub struct MirBody<KIND> {
     pub _kind: std::marker::PhantomData<KIND>,
 }
 
+/// A MIR body, tagged with its flavour. The bodies exported for an
+/// item are a list of those, one per flavour requested in the
+/// options (see `hax_frontend_exporter_options::MirFlavour`), except
+/// for the CTFE MIR of runtime functions, which doesn't exist.
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum AnyMirBody {
+    Built(MirBody<mir_kinds::Built>),
+    Promoted(MirBody<mir_kinds::Promoted>),
+    Elaborated(MirBody<mir_kinds::Elaborated>),
+    Optimized(MirBody<mir_kinds::Optimized>),
+    Ctfe(MirBody<mir_kinds::CTFE>),
+}
+
 #[derive_group(Serializers)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::SourceScopeData<'tcx>, state: S as s)]
@@ -395,16 +419,19 @@ fn translate_terminator_kind_call<'tcx, S: BaseState<'tcx> + HasMir<'tcx> + HasO
     } else {
         use mir::Operand;
         match func {
-            Operand::Constant(_) => {
-                unimplemented!("{:?}", func);
+            Operand::Constant(constant) => {
+                // Constant function pointer, e.g. a `const` of type `fn(..)`.
+                FunOperand::DynamicConstant(constant.as_ref().sinto(s))
             }
             Operand::Move(place) => {
                 // Function pointer or closure.
                 let place = place.sinto(s);
                 FunOperand::DynamicMove(place)
             }
-            Operand::Copy(_place) => {
-                unimplemented!("{:?}", func);
+            Operand::Copy(place) => {
+                // Function pointer or closure.
+                let place = place.sinto(s);
+                FunOperand::DynamicCopy(place)
             }
         }
     };
@@ -538,6 +565,10 @@ pub enum FunOperand {
     },
     /// Use of a closure or a function pointer value. Counts as a move from the given place.
     DynamicMove(Place),
+    /// Use of a closure or a function pointer value copied from the given place.
+    DynamicCopy(Place),
+    /// Use of a constant function pointer value.
+    DynamicConstant(ConstOperand),
 }
 
 #[derive_group(Serializers)]
@@ -1110,13 +1141,174 @@ pub enum FakeBorrowKind {
     Shallow,
 }
 
-sinto_todo!(rustc_ast::ast, InlineAsmTemplatePiece);
-sinto_todo!(rustc_ast::ast, InlineAsmOptions);
-sinto_todo!(rustc_middle::mir, InlineAsmOperand<'tcx>);
-sinto_todo!(rustc_middle::mir, AssertMessage<'tcx>);
-sinto_todo!(rustc_middle::mir, UnwindAction);
-sinto_todo!(rustc_middle::mir, FakeReadCause);
-sinto_todo!(rustc_middle::mir, RetagKind);
+/// Reflects [`rustc_middle::mir::AssertKind`]: the reason why an
+/// `Assert` terminator fails.
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::AssertMessage<'tcx>, state: S as s)]
+pub enum AssertMessage {
+    BoundsCheck { len: Operand, index: Operand },
+    Overflow(BinOp, Operand, Operand),
+    OverflowNeg(Operand),
+    DivisionByZero(Operand),
+    RemainderByZero(Operand),
+    ResumedAfterReturn(CoroutineKind),
+    ResumedAfterPanic(CoroutineKind),
+    MisalignedPointerDereference { required: Operand, found: Operand },
+}
+
+/// Reflects [`rustc_middle::mir::UnwindAction`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::UnwindAction, state: S as s)]
+pub enum UnwindAction {
+    Continue,
+    Unreachable,
+    Terminate(UnwindTerminateReason),
+    Cleanup(BasicBlock),
+}
+
+/// Reflects [`rustc_middle::mir::UnwindTerminateReason`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S>, from: rustc_middle::mir::UnwindTerminateReason, state: S as _s)]
+pub enum UnwindTerminateReason {
+    Abi,
+    InCleanup,
+}
+
+/// Reflects [`rustc_middle::mir::FakeReadCause`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::FakeReadCause, state: S as s)]
+pub enum FakeReadCause {
+    ForMatchGuard,
+    ForMatchedPlace(Option<GlobalIdent>),
+    ForGuardBinding,
+    ForLet(Option<GlobalIdent>),
+    ForIndex,
+}
+
+/// Reflects [`rustc_middle::mir::RetagKind`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S>, from: rustc_middle::mir::RetagKind, state: S as _s)]
+pub enum RetagKind {
+    FnEntry,
+    TwoPhase,
+    Raw,
+    Default,
+}
+
+/// Reflects [`rustc_middle::mir::coverage::CoverageKind`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S>, from: rustc_middle::mir::coverage::CoverageKind, state: S as _s)]
+pub enum CoverageKind {
+    SpanMarker,
+    BlockMarker {
+        #[map(x.as_u32())]
+        id: u32,
+    },
+    CounterIncrement {
+        #[map(x.as_u32())]
+        id: u32,
+    },
+    ExpressionUsed {
+        #[map(x.as_u32())]
+        id: u32,
+    },
+    CondBitmapUpdate {
+        index: u32,
+        decision_depth: u16,
+    },
+    TestVectorBitmapUpdate {
+        bitmap_idx: u32,
+        decision_depth: u16,
+    },
+}
+
+/// Reflects [`rustc_middle::mir::InlineAsmOperand`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::InlineAsmOperand<'tcx>, state: S as s)]
+pub enum InlineAsmOperand {
+    In {
+        reg: InlineAsmRegOrRegClass,
+        value: Operand,
+    },
+    Out {
+        reg: InlineAsmRegOrRegClass,
+        late: bool,
+        place: Option<Place>,
+    },
+    InOut {
+        reg: InlineAsmRegOrRegClass,
+        late: bool,
+        in_value: Operand,
+        out_place: Option<Place>,
+    },
+    Const {
+        #[map(x.as_ref().sinto(s))]
+        value: ConstOperand,
+    },
+    SymFn {
+        #[map(x.as_ref().sinto(s))]
+        value: ConstOperand,
+    },
+    SymStatic {
+        def_id: DefId,
+    },
+    Label {
+        target_index: usize,
+    },
+}
+
+/// Reflects [`rustc_target::asm::InlineAsmRegOrRegClass`]: registers
+/// and register classes are architecture-specific, we give their
+/// names.
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum InlineAsmRegOrRegClass {
+    Reg(String),
+    RegClass(String),
+}
+
+#[cfg(feature = "rustc")]
+impl<S> SInto<S, InlineAsmRegOrRegClass> for rustc_target::asm::InlineAsmRegOrRegClass {
+    fn sinto(&self, _s: &S) -> InlineAsmRegOrRegClass {
+        use rustc_target::asm::InlineAsmRegOrRegClass as R;
+        match self {
+            R::Reg(reg) => InlineAsmRegOrRegClass::Reg(reg.name().to_string()),
+            R::RegClass(class) => InlineAsmRegOrRegClass::RegClass(class.name().to_string()),
+        }
+    }
+}
+
+/// Reflects [`rustc_ast::ast::InlineAsmTemplatePiece`]
+#[derive_group(Serializers)]
+#[derive(AdtInto, Clone, Debug, JsonSchema)]
+#[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::InlineAsmTemplatePiece, state: S as s)]
+pub enum InlineAsmTemplatePiece {
+    String(#[map(x.to_string())] String),
+    Placeholder {
+        operand_idx: usize,
+        modifier: Option<char>,
+        span: Span,
+    },
+}
+
+/// Reflects [`rustc_ast::ast::InlineAsmOptions`], as the names of
+/// the options set, as written in `asm!` (e.g. `nomem`).
+pub type InlineAsmOptions = Vec<String>;
+
+#[cfg(feature = "rustc")]
+impl<S> SInto<S, InlineAsmOptions> for rustc_ast::ast::InlineAsmOptions {
+    fn sinto(&self, _s: &S) -> InlineAsmOptions {
+        self.iter_names()
+            .map(|(name, _)| name.to_lowercase())
+            .collect()
+    }
+}
+
 sinto_todo!(rustc_middle::mir, UserTypeProjection);
-sinto_todo!(rustc_middle::mir, UnwindTerminateReason);
-sinto_todo!(rustc_middle::mir::coverage, CoverageKind);
@@ -109,18 +109,27 @@ pub enum FullDefKind<Body> {
         param_env: ParamEnv,
         #[value(s.base().tcx.adt_def(s.owner_id()).sinto(s))]
         def: AdtDef,
+        /// The layout of the type, if it is monomorphic.
+        #[value(layout_of_def(s, s.owner_id()))]
+        layout: Option<Layout>,
     },
     Union {
         #[value(get_param_env(s, s.owner_id()))]
         param_env: ParamEnv,
         #[value(s.base().tcx.adt_def(s.owner_id()).sinto(s))]
         def: AdtDef,
+        /// The layout of the type, if it is monomorphic.
+        #[value(layout_of_def(s, s.owner_id()))]
+        layout: Option<Layout>,
     },
     Enum {
         #[value(get_param_env(s, s.owner_id()))]
         param_env: ParamEnv,
         #[value(s.base().tcx.adt_def(s.owner_id()).sinto(s))]
         def: AdtDef,
+        /// The layout of the type, if it is monomorphic.
+        #[value(layout_of_def(s, s.owner_id()))]
+        layout: Option<Layout>,
     },
     /// Type alias: `type Foo = Bar;`
     TyAlias {
@@ -279,6 +288,10 @@ pub enum FullDefKind<Body> {
         ty: Ty,
         #[value(s.owner_id().as_local().map(|ldid| Body::body(ldid, s)))]
         body: Option<Body>,
+        /// The fully evaluated value, if the constant has no generic
+        /// parameters.
+        #[value(eval_global(s, s.owner_id()))]
+        value: Option<ConstantExpr>,
     },
     /// Associated constant: `trait MyTrait { const ASSOC: usize; }`
     AssocConst {
@@ -292,6 +305,10 @@ pub enum FullDefKind<Body> {
         ty: Ty,
         #[value(s.owner_id().as_local().map(|ldid| Body::body(ldid, s)))]
         body: Option<Body>,
+        /// The fully evaluated value, if the constant has no generic
+        /// parameters.
+        #[value(eval_global(s, s.owner_id()))]
+        value: Option<ConstantExpr>,
     },
     /// Anonymous constant, e.g. the `1 + 2` in `[u8; 1 + 2]`
     AnonConst,
@@ -310,6 +327,10 @@ pub enum FullDefKind<Body> {
         ty: Ty,
         #[value(s.owner_id().as_local().map(|ldid| Body::body(ldid, s)))]
         body: Option<Body>,
+        /// The value of the initializer, if the static is not an `extern`
+        /// one.
+        #[value(eval_global(s, s.owner_id()))]
+        value: Option<ConstantExpr>,
     },
 
     // Crates and modules
@@ -20,6 +20,15 @@ impl ItemAttributes {
             parent_attributes: vec![],
         }
     }
+    /// The attributes of the item itself.
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+    /// The attributes of the parents of the item, starting with the
+    /// innermost parent.
+    pub fn parent_attributes(&self) -> &[Attribute] {
+        &self.parent_attributes
+    }
 }
 
 #[cfg(feature = "rustc")]
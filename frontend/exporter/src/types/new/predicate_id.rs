@@ -17,7 +17,11 @@ mod rustc {
             // Here, we need to be careful about not hashing a `crate::Predicate`,
             // but `crate::Binder<crate::PredicateKind>` instead,
             // otherwise we would get into a infinite recursion.
-            PredicateId(deterministic_hash(self))
+            // Rustc's stable hash is not doing what we want here: it is
+            // sensible to the environment (architecture, paths observable
+            // from `Span`s, etc.). Instead, we first `sinto` and then hash
+            // deterministically.
+            PredicateId(crate::deterministic_hash::deterministic_hash(self))
         }
     }
 
@@ -29,18 +33,4 @@ mod rustc {
             *self
         }
     }
-
-    /// We need identifiers that are stable across different
-    /// architectures, different paths (which are observable from
-    /// `Span`s), etc.
-    /// Rustc's stable hash is not doing what we want here: it is sensible
-    /// to the environment. Instead, we first `sinto` and then hash with
-    /// `deterministic_hash` below.
-    fn deterministic_hash<T: std::hash::Hash>(x: &T) -> u64 {
-        use crate::deterministic_hash::DeterministicHasher;
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::BuildHasher;
-        use std::hash::BuildHasherDefault;
-        <BuildHasherDefault<DeterministicHasher<DefaultHasher>>>::default().hash_one(x)
-    }
 }
@@ -79,11 +79,10 @@ pub struct ExpnData {
 }
 
 /// Reflects [`rustc_span::Span`]
-#[derive(::serde::Serialize, ::serde::Deserialize, Clone, Debug, JsonSchema, Eq, Ord)]
+#[derive(::serde::Serialize, ::serde::Deserialize, Clone, Debug, JsonSchema)]
+#[serde(transparent)]
 pub struct Span {
-    pub lo: Loc,
-    pub hi: Loc,
-    pub filename: FileName,
+    pub(crate) contents: id_table::Node<SpanContents>,
     /// Original rustc span; can be useful for reporting rustc
     /// diagnostics (this is used in Charon)
     #[cfg(feature = "rustc")]
@@ -95,32 +94,64 @@ pub struct Span {
     // expn_backtrace: Vec<ExpnData>,
 }
 
+/// The contents of a [`Span`], shared through the ID table.
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SpanContents {
+    pub lo: Loc,
+    pub hi: Loc,
+    pub filename: FileName,
+}
+
+impl Span {
+    #[cfg(feature = "rustc")]
+    pub fn new<'tcx, S: BaseState<'tcx>>(
+        s: &S,
+        contents: SpanContents,
+        rust_span_data: Option<rustc_span::SpanData>,
+    ) -> Self {
+        let contents = s.with_global_cache(|cache| {
+            id_table::Node::intern(contents, &mut cache.id_table_session)
+        });
+        Span {
+            contents,
+            rust_span_data,
+        }
+    }
+}
+
+impl std::ops::Deref for Span {
+    type Target = SpanContents;
+    fn deref(&self) -> &Self::Target {
+        &self.contents
+    }
+}
+
 const _: () = {
     // `rust_span_data` is a metadata that should *not* be taken into
-    // account while hashing or comparing
+    // account while hashing or comparing. Spans are compared by
+    // contents, regardless of their IDs.
 
     impl std::hash::Hash for Span {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-            self.lo.hash(state);
-            self.hi.hash(state);
-            self.filename.hash(state);
+            self.contents.hash(state);
         }
     }
     impl PartialEq for Span {
         fn eq(&self, other: &Self) -> bool {
-            self.lo == other.lo && self.hi == other.hi && self.filename == other.filename
+            **self == **other
         }
     }
+    impl Eq for Span {}
 
     impl PartialOrd for Span {
         fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-            Some(
-                self.lo.partial_cmp(&other.lo)?.then(
-                    self.hi
-                        .partial_cmp(&other.hi)?
-                        .then(self.filename.partial_cmp(&other.filename)?),
-                ),
-            )
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Span {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            (**self).cmp(&**other)
         }
     }
 };
@@ -141,7 +172,11 @@ impl<'tcx, S: BaseState<'tcx>> SInto<S, Span> for rustc_span::Span {
         if let Some(span) = s.with_global_cache(|cache| cache.spans.get(self).cloned()) {
             return span;
         }
-        let span = translate_span(*self, s.base().tcx.sess);
+        let span = Span::new(
+            s,
+            translate_span(*self, s.base().tcx.sess),
+            Some(self.data()),
+        );
         s.with_global_cache(|cache| cache.spans.insert(*self, span.clone()));
         span
     }
@@ -162,6 +162,12 @@ pub struct Block {
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Stmt {
     pub kind: StmtKind,
+    /// The comments right before the statement
+    #[value(comments_before(s, match &self.kind {
+        thir::StmtKind::Let { span, .. } => *span,
+        thir::StmtKind::Expr { expr, .. } => s.thir().exprs[*expr].span,
+    }))]
+    pub comments: Vec<Comment>,
 }
 
 #[cfg(feature = "rustc")]
@@ -557,6 +563,9 @@ pub struct Arm {
     pub span: Span,
     #[value(attribute_from_scope(gstate, scope).1)]
     attributes: Vec<Attribute>,
+    /// The comments right before the arm
+    #[value(comments_before(gstate, *span))]
+    comments: Vec<Comment>,
 }
 
 /// Reflects [`thir::Param`]
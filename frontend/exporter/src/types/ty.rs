@@ -746,7 +746,7 @@ impl Ty {
     pub fn new<'tcx, S: BaseState<'tcx>>(s: &S, kind: TyKind) -> Self {
         s.with_global_cache(|cache| {
             let table_session = &mut cache.id_table_session;
-            let kind = id_table::Node::new(kind, table_session);
+            let kind = id_table::Node::intern(kind, table_session);
             Ty { kind }
         })
     }
@@ -905,7 +905,39 @@ pub struct AdtDef {
     pub repr: ReprOptions,
 }
 
-sinto_todo!(rustc_middle::ty, AdtFlags);
+/// Reflects [`ty::AdtFlags`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct AdtFlags {
+    pub is_enum: bool,
+    pub is_union: bool,
+    pub is_struct: bool,
+    pub has_ctor: bool,
+    pub is_phantom_data: bool,
+    pub is_fundamental: bool,
+    pub is_box: bool,
+    pub is_manually_drop: bool,
+    pub is_variant_list_non_exhaustive: bool,
+    pub is_unsafe_cell: bool,
+}
+
+#[cfg(feature = "rustc")]
+impl<S> SInto<S, AdtFlags> for ty::AdtFlags {
+    fn sinto(&self, _s: &S) -> AdtFlags {
+        AdtFlags {
+            is_enum: self.contains(Self::IS_ENUM),
+            is_union: self.contains(Self::IS_UNION),
+            is_struct: self.contains(Self::IS_STRUCT),
+            has_ctor: self.contains(Self::HAS_CTOR),
+            is_phantom_data: self.contains(Self::IS_PHANTOM_DATA),
+            is_fundamental: self.contains(Self::IS_FUNDAMENTAL),
+            is_box: self.contains(Self::IS_BOX),
+            is_manually_drop: self.contains(Self::IS_MANUALLY_DROP),
+            is_variant_list_non_exhaustive: self.contains(Self::IS_VARIANT_LIST_NON_EXHAUSTIVE),
+            is_unsafe_cell: self.contains(Self::IS_UNSAFE_CELL),
+        }
+    }
+}
 
 /// Reflects [`ty::ReprOptions`]
 #[derive_group(Serializers)]
@@ -924,9 +956,236 @@ pub struct ReprOptions {
     pub field_shuffle_seed: u64,
 }
 
-sinto_todo!(rustc_abi, IntegerType);
-sinto_todo!(rustc_abi, ReprFlags);
-sinto_todo!(rustc_abi, Align);
+/// Reflects [`rustc_abi::IntegerType`]: the integer type given by a
+/// `#[repr(..)]` attribute.
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum IntegerType {
+    Signed(IntTy),
+    Unsigned(UintTy),
+}
+
+#[cfg(feature = "rustc")]
+impl<S> SInto<S, IntegerType> for rustc_abi::IntegerType {
+    fn sinto(&self, _s: &S) -> IntegerType {
+        use rustc_abi::{Integer::*, IntegerType::*};
+        match *self {
+            Pointer(true) => IntegerType::Signed(IntTy::Isize),
+            Pointer(false) => IntegerType::Unsigned(UintTy::Usize),
+            Fixed(int, true) => IntegerType::Signed(match int {
+                I8 => IntTy::I8,
+                I16 => IntTy::I16,
+                I32 => IntTy::I32,
+                I64 => IntTy::I64,
+                I128 => IntTy::I128,
+            }),
+            Fixed(int, false) => IntegerType::Unsigned(match int {
+                I8 => UintTy::U8,
+                I16 => UintTy::U16,
+                I32 => UintTy::U32,
+                I64 => UintTy::U64,
+                I128 => UintTy::U128,
+            }),
+        }
+    }
+}
+
+/// Reflects [`rustc_abi::ReprFlags`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct ReprFlags {
+    /// `#[repr(C)]`
+    pub is_c: bool,
+    /// `#[repr(simd)]`
+    pub is_simd: bool,
+    /// `#[repr(transparent)]`
+    pub is_transparent: bool,
+    /// Internal only for now: whether fields must be laid out in
+    /// declaration order
+    pub is_linear: bool,
+    /// Whether the layout is randomized (`-Z randomize-layout`)
+    pub randomize_layout: bool,
+}
+
+#[cfg(feature = "rustc")]
+impl<S> SInto<S, ReprFlags> for rustc_abi::ReprFlags {
+    fn sinto(&self, _s: &S) -> ReprFlags {
+        ReprFlags {
+            is_c: self.contains(Self::IS_C),
+            is_simd: self.contains(Self::IS_SIMD),
+            is_transparent: self.contains(Self::IS_TRANSPARENT),
+            is_linear: self.contains(Self::IS_LINEAR),
+            randomize_layout: self.contains(Self::RANDOMIZE_LAYOUT),
+        }
+    }
+}
+
+/// Reflects [`rustc_abi::Align`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct Align {
+    pub bytes: u64,
+}
+
+#[cfg(feature = "rustc")]
+impl<S> SInto<S, Align> for rustc_abi::Align {
+    fn sinto(&self, _s: &S) -> Align {
+        Align {
+            bytes: self.bytes(),
+        }
+    }
+}
+
+/// The layout rustc computed for a type (see [`rustc_abi::LayoutS`]).
+/// Sizes and offsets are in bytes.
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct Layout {
+    pub size: u64,
+    pub align: Align,
+    pub fields: FieldsShape,
+    pub variants: LayoutVariants,
+    /// Whether the type is uninhabited
+    pub uninhabited: bool,
+}
+
+/// Reflects [`rustc_abi::FieldsShape`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum FieldsShape {
+    /// Scalar primitives and `!`, which never have fields
+    Primitive,
+    /// All the fields (their number is given) start at offset 0
+    Union(usize),
+    /// Array and slice-like layout, with `count` elements separated
+    /// by `stride` bytes
+    Array { stride: u64, count: u64 },
+    /// The offsets of the fields, in declaration order
+    Arbitrary { offsets: Vec<u64> },
+}
+
+/// Reflects [`rustc_abi::Variants`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum LayoutVariants {
+    /// A single variant: structs, unions, and enums with at most
+    /// one inhabited variant
+    Single { index: VariantIdx },
+    /// Enums with several variants, told apart by a tag
+    Multiple {
+        tag: Scalar,
+        tag_encoding: TagEncoding,
+        /// The index of the tag among the fields of the enum
+        tag_field: usize,
+        variants: IndexVec<VariantIdx, Layout>,
+    },
+}
+
+/// A scalar, e.g. the tag of an enum: its size and range of valid
+/// values (which wraps around if `start > end`).
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct Scalar {
+    pub size: u64,
+    pub valid_range_start: u128,
+    pub valid_range_end: u128,
+}
+
+/// Reflects [`rustc_abi::TagEncoding`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum TagEncoding {
+    /// The tag is the discriminant of the variant
+    Direct,
+    /// The variants `niche_start_variant..=niche_end_variant` are
+    /// encoded in the invalid values of a field of
+    /// `untagged_variant`: variant `v` is encoded as `(v -
+    /// niche_start_variant) + niche_start`.
+    Niche {
+        untagged_variant: VariantIdx,
+        niche_start_variant: VariantIdx,
+        niche_end_variant: VariantIdx,
+        niche_start: u128,
+    },
+}
+
+#[cfg(feature = "rustc")]
+impl<'tcx, S: BaseState<'tcx>> SInto<S, Layout>
+    for rustc_abi::LayoutS<rustc_abi::FieldIdx, rustc_abi::VariantIdx>
+{
+    fn sinto(&self, s: &S) -> Layout {
+        use rustc_abi::{FieldsShape as F, TagEncoding as T, Variants as V};
+        let tcx = s.base().tcx;
+        let scalar = |scalar: &rustc_abi::Scalar| {
+            let range = scalar.valid_range(&tcx);
+            Scalar {
+                size: scalar.size(&tcx).bytes(),
+                valid_range_start: range.start,
+                valid_range_end: range.end,
+            }
+        };
+        Layout {
+            size: self.size.bytes(),
+            align: self.align.abi.sinto(s),
+            fields: match &self.fields {
+                F::Primitive => FieldsShape::Primitive,
+                F::Union(count) => FieldsShape::Union(count.get()),
+                F::Array { stride, count } => FieldsShape::Array {
+                    stride: stride.bytes(),
+                    count: *count,
+                },
+                F::Arbitrary { offsets, .. } => FieldsShape::Arbitrary {
+                    offsets: offsets.iter().map(|offset| offset.bytes()).collect(),
+                },
+            },
+            variants: match &self.variants {
+                V::Single { index } => LayoutVariants::Single {
+                    index: index.sinto(s),
+                },
+                V::Multiple {
+                    tag,
+                    tag_encoding,
+                    tag_field,
+                    variants,
+                } => LayoutVariants::Multiple {
+                    tag: scalar(tag),
+                    tag_encoding: match tag_encoding {
+                        T::Direct => TagEncoding::Direct,
+                        T::Niche {
+                            untagged_variant,
+                            niche_variants,
+                            niche_start,
+                        } => TagEncoding::Niche {
+                            untagged_variant: untagged_variant.sinto(s),
+                            niche_start_variant: niche_variants.start().sinto(s),
+                            niche_end_variant: niche_variants.end().sinto(s),
+                            niche_start: *niche_start,
+                        },
+                    },
+                    tag_field: *tag_field,
+                    variants: variants.iter().map(|variant| variant.sinto(s)).collect(),
+                },
+            },
+            uninhabited: self.abi.is_uninhabited(),
+        }
+    }
+}
+
+/// The layout of the type defined by `def_id`, when it has no generic
+/// parameters and its layout can be computed.
+#[cfg(feature = "rustc")]
+pub(crate) fn layout_of_def<'tcx, S: BaseState<'tcx>>(
+    s: &S,
+    def_id: rustc_hir::def_id::DefId,
+) -> Option<Layout> {
+    let tcx = s.base().tcx;
+    if tcx.generics_of(def_id).count() != 0 {
+        return None;
+    }
+    let ty = tcx.type_of(def_id).instantiate_identity();
+    let layout = tcx.layout_of(tcx.param_env(def_id).and(ty)).ok()?;
+    Some(layout.layout.0 .0.sinto(s))
+}
 
 #[cfg(feature = "rustc")]
 impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, AdtDef> for ty::AdtDef<'tcx> {
@@ -1214,15 +1473,41 @@ impl<T> Binder<T> {
 }
 
 /// Reflects [`ty::GenericPredicates`]
+#[derive_group(Serializers)]
+#[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct GenericPredicates {
+    pub(crate) contents: id_table::Node<GenericPredicatesContents>,
+}
+
+/// The contents of a [`GenericPredicates`], shared through the ID table.
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::GenericPredicates<'tcx>, state: S as s)]
 #[derive_group(Serializers)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct GenericPredicates {
+pub struct GenericPredicatesContents {
     #[value(self.predicates.iter().map(|x| x.sinto(s)).collect())]
     pub predicates: Vec<(Clause, Span)>,
 }
 
+impl std::ops::Deref for GenericPredicates {
+    type Target = GenericPredicatesContents;
+    fn deref(&self) -> &Self::Target {
+        &self.contents
+    }
+}
+
+#[cfg(feature = "rustc")]
+impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, GenericPredicates> for ty::GenericPredicates<'tcx> {
+    fn sinto(&self, s: &S) -> GenericPredicates {
+        let contents: GenericPredicatesContents = self.sinto(s);
+        let contents = s.with_global_cache(|cache| {
+            id_table::Node::intern(contents, &mut cache.id_table_session)
+        });
+        GenericPredicates { contents }
+    }
+}
+
 #[cfg(feature = "rustc")]
 impl<'tcx, S: UnderOwnerState<'tcx>, T1, T2> SInto<S, Binder<T2>> for ty::Binder<'tcx, T1>
 where
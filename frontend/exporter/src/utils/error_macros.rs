@@ -25,7 +25,18 @@ mod internal_helpers {
         };
     }
     macro_rules! _span_verb_base {
-        ($verb:ident, $s:ident, $span:expr, $message:expr) => {{
+        (fatal, $s:ident, $span:expr, $message:expr) => {{
+            if $crate::recovering_export_error() {
+                $crate::utils::_span_verb_base!(@emit warn, $s, $span, $message);
+                rustc_span::fatal_error::FatalError.raise()
+            } else {
+                $crate::utils::_span_verb_base!(@emit fatal, $s, $span, $message)
+            }
+        }};
+        ($verb:ident, $s:ident, $span:expr, $message:expr) => {
+            $crate::utils::_span_verb_base!(@emit $verb, $s, $span, $message)
+        };
+        (@emit $verb:ident, $s:ident, $span:expr, $message:expr) => {{
             let backtrace = std::backtrace::Backtrace::capture();
             eprintln!("{}", backtrace);
             let mut builder = $crate::utils::_verb!($verb, $s.base().tcx.dcx(), $message);
@@ -126,6 +126,14 @@ pub fn fstar_verification_status(attr: pm::TokenStream, item: pm::TokenStream) -
 }
 
 /// Include this item in the Hax translation.
+///
+/// On a module (e.g. `#[hax_lib::include] mod m;`, or
+/// `#![hax_lib::include]` in the module), this sets the default status
+/// of the items of the module: items can override it with
+/// `#[hax_lib::exclude]`. Inner attributes and attributes on
+/// non-inline modules are unstable in Rust, use
+/// `#![cfg_attr(hax, hax_lib::include)]` for the crate to build with
+/// a stable toolchain.
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn include(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
@@ -136,6 +144,13 @@ pub fn include(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream
 }
 
 /// Exclude this item from the Hax translation.
+///
+/// On a module (e.g. `#[hax_lib::exclude] mod m;`, or
+/// `#![hax_lib::exclude]` in the module), this excludes every item of
+/// the module, except items marked `#[hax_lib::include]`. Inner
+/// attributes and attributes on non-inline modules are unstable in
+/// Rust, use `#![cfg_attr(hax, hax_lib::exclude)]` for the crate to
+/// build with a stable toolchain.
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn exclude(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
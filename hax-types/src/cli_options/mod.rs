@@ -120,7 +120,8 @@ pub struct ProVerifOptions {
     /// implementation only, `+!` means interface only and `+` means
     /// implementation and interface. Rust path chunks can be either a
     /// concrete string, or a glob (just like bash globs, but with
-    /// Rust paths).
+    /// Rust paths). Clauses can be read from a file with
+    /// `@path/to/file` (see `--include-namespaces`).
     #[arg(
         long,
         value_parser = parse_inclusion_clause,
@@ -153,7 +154,9 @@ pub struct FStarOptions<E: Extension> {
     /// `-`. `-` means implementation only, `+!` means interface only
     /// and `+` means implementation and interface. Rust path chunks
     /// can be either a concrete string, or a glob (just like bash
-    /// globs, but with Rust paths).
+    /// globs, but with Rust paths). Like for `--include-namespaces`,
+    /// paths can be followed by predicates (e.g. `+k::**/pub`),
+    /// and clauses can be read from a file with `@path/to/file`.
     #[arg(
         long,
         value_parser = parse_inclusion_clause,
@@ -213,11 +216,49 @@ pub enum InclusionKind {
     Excluded,
 }
 
+/// The kinds of items a predicate `kind=<KIND>` can select.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKindSelector {
+    Fn,
+    Struct,
+    Enum,
+    Impl,
+    Trait,
+    Const,
+}
+
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone)]
+pub enum ItemSelector {
+    /// `kind=<KIND>`: selects the items of kind `<KIND>`
+    Kind(ItemKindSelector),
+    /// `pub`: selects the public items
+    Public,
+    /// `attr=<PATH>`: selects the items with an attribute matching
+    /// `<PATH>` (e.g. `attr=test` or `attr=hax_lib::include`)
+    Attribute(Namespace),
+    /// `trait=<PATH>`: selects the implementations of a trait
+    /// matching `<PATH>` (e.g. `trait=**::Serialize`)
+    ImplOf(Namespace),
+}
+
+/// A predicate on items, optionally negated with `!`.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone)]
+pub struct ItemPredicate {
+    pub negated: bool,
+    pub selector: ItemSelector,
+}
+
 #[derive_group(Serializers)]
 #[derive(JsonSchema, Debug, Clone)]
 pub struct InclusionClause {
     pub kind: InclusionKind,
     pub namespace: Namespace,
+    /// Predicates the items matched by `namespace` should satisfy,
+    /// e.g. `/kind=fn,pub`.
+    pub predicates: Vec<ItemPredicate>,
 }
 
 const PREFIX_INCLUDED_TRANSITIVE: &str = "+";
@@ -226,6 +267,47 @@ const PREFIX_INCLUDED_NONE: &str = "+!";
 const PREFIX_SIGNATURE_ONLY: &str = "+:";
 const PREFIX_EXCLUDED: &str = "-";
 
+impl ToString for ItemPredicate {
+    fn to_string(&self) -> String {
+        let selector = match &self.selector {
+            ItemSelector::Kind(kind) => format!("kind={}", format!("{kind:?}").to_lowercase()),
+            ItemSelector::Public => "pub".to_string(),
+            ItemSelector::Attribute(namespace) => format!("attr={}", namespace.to_string()),
+            ItemSelector::ImplOf(namespace) => format!("trait={}", namespace.to_string()),
+        };
+        format!("{}{selector}", if self.negated { "!" } else { "" })
+    }
+}
+
+fn parse_item_predicate(
+    s: &str,
+) -> Result<ItemPredicate, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let (negated, s) = match s.trim().strip_prefix('!') {
+        Some(s) => (true, s.trim()),
+        None => (false, s.trim()),
+    };
+    let selector = match s.split_once('=') {
+        None if s == "pub" => ItemSelector::Public,
+        Some(("kind", kind)) => ItemSelector::Kind(match kind.trim() {
+            "fn" => ItemKindSelector::Fn,
+            "struct" => ItemKindSelector::Struct,
+            "enum" => ItemKindSelector::Enum,
+            "impl" => ItemKindSelector::Impl,
+            "trait" => ItemKindSelector::Trait,
+            "const" => ItemKindSelector::Const,
+            kind => Err(format!(
+                "Expected `fn`, `struct`, `enum`, `impl`, `trait` or `const`, got `{kind}`"
+            ))?,
+        }),
+        Some(("attr", path)) => ItemSelector::Attribute(path.trim().parse()?),
+        Some(("trait", path)) => ItemSelector::ImplOf(path.trim().parse()?),
+        _ => Err(format!(
+            "Expected `kind=<KIND>`, `pub`, `attr=<PATH>` or `trait=<PATH>`, got `{s}`"
+        ))?,
+    };
+    Ok(ItemPredicate { negated, selector })
+}
+
 impl ToString for InclusionClause {
     fn to_string(&self) -> String {
         let kind = match self.kind {
@@ -235,8 +317,112 @@ impl ToString for InclusionClause {
             InclusionKind::SignatureOnly => PREFIX_SIGNATURE_ONLY,
             InclusionKind::Excluded => PREFIX_EXCLUDED,
         };
-        format!("{kind}{}", self.namespace.to_string())
+        let predicates = if self.predicates.is_empty() {
+            String::new()
+        } else {
+            let predicates: Vec<_> = self.predicates.iter().map(|p| p.to_string()).collect();
+            format!("/{}", predicates.join(","))
+        };
+        format!("{kind}{}{predicates}", self.namespace.to_string())
+    }
+}
+
+/// Reads the inclusion clauses listed in the file `path`: one clause
+/// per line, `#` starting a comment. A line `@other` includes the
+/// clauses of the file `other`, relative to the directory of `path`.
+/// `including` is the stack of the files being read.
+fn read_clause_file(
+    path: &std::path::Path,
+    including: &mut Vec<PathBuf>,
+) -> Result<Vec<InclusionClause>, String> {
+    let path = absolute_path(path).map_err(|err| err.to_string())?;
+    if including.contains(&path) {
+        Err(format!("`{}` includes itself", path.display()))?
+    }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Cannot read `{}`: {err}", path.display()))?;
+    including.push(path.clone());
+    let mut clauses = vec![];
+    for (n, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.strip_prefix('@') {
+            Some(file) => {
+                let file = path.parent().unwrap_or(&path).join(file);
+                clauses.extend(read_clause_file(&file, including)?)
+            }
+            None => clauses.push(
+                parse_inclusion_clause(line)
+                    .map_err(|err| format!("{}:{}: {err}", path.display(), n + 1))?,
+            ),
+        }
+    }
+    including.pop();
+    Ok(clauses)
+}
+
+impl InclusionClause {
+    /// `@path` is parsed into a placeholder clause, which is replaced
+    /// by the clauses of the file `path` when paths are normalized (see
+    /// the `NormalizePaths` instance of `Vec<InclusionClause>`).
+    fn clause_file(path: PathBuf) -> Self {
+        InclusionClause {
+            kind: InclusionKind::Included(DepsKind::Transitive),
+            namespace: Namespace {
+                chunks: vec![NamespaceChunk::Exact(format!("@{}", path.display()))],
+            },
+            predicates: vec![],
+        }
+    }
+
+    fn as_clause_file(&self) -> Option<PathBuf> {
+        match &self.namespace.chunks[..] {
+            [NamespaceChunk::Exact(chunk)] => chunk.strip_prefix('@').map(PathBuf::from),
+            _ => None,
+        }
+    }
+}
+
+impl NormalizePaths for Vec<InclusionClause> {
+    fn normalize_paths(&mut self) {
+        *self = std::mem::take(self)
+            .into_iter()
+            .flat_map(|clause| match clause.as_clause_file() {
+                Some(path) => read_clause_file(&path, &mut vec![]).unwrap(),
+                None => vec![clause],
+            })
+            .collect();
+    }
+}
+
+/// Finds the first occurrence of `needle` in `s` that is not nested
+/// in brackets or braces: predicates contain globs, whose character
+/// classes (`[ab]`) and alternatives (`{a,b}`) may contain `,`.
+fn find_top_level(s: &str, needle: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if c == needle && depth == 0 => return Some(i),
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Splits `s` on the occurrences of `separator` that are not nested
+/// in brackets or braces (see `find_top_level`).
+fn split_top_level(mut s: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    while let Some(i) = find_top_level(s, separator) {
+        parts.push(&s[..i]);
+        s = &s[i + separator.len_utf8()..];
     }
+    parts.push(s);
+    parts
 }
 
 pub fn parse_inclusion_clause(
@@ -246,6 +432,12 @@ pub fn parse_inclusion_clause(
     if s.is_empty() {
         Err("Expected `-` or `+`, got an empty string")?
     }
+    if let Some(path) = s.strip_prefix('@') {
+        // The file is read now, so that errors are reported while
+        // parsing the command line
+        read_clause_file(std::path::Path::new(path), &mut vec![])?;
+        return Ok(InclusionClause::clause_file(absolute_path(path)?));
+    }
     let (prefix, namespace) = {
         let f = |&c: &char| matches!(c, '+' | '-' | '~' | '!' | ':');
         (
@@ -263,9 +455,23 @@ pub fn parse_inclusion_clause(
             "Expected `+`, `+~`, `+!`, `+:` or `-`, got an `{prefix}`"
         ))?,
     };
+    // A `/` cannot appear in a namespace, thus everything after the
+    // first one is the list of predicates
+    let (namespace, predicates) = match namespace.split_once('/') {
+        Some((namespace, predicates)) => (
+            namespace,
+            split_top_level(predicates, ',')
+                .into_iter()
+                .filter(|p| !p.trim().is_empty())
+                .map(parse_item_predicate)
+                .collect::<Result<_, _>>()?,
+        ),
+        None => (&namespace[..], vec![]),
+    };
     Ok(InclusionClause {
         kind,
-        namespace: namespace.to_string().into(),
+        namespace: namespace.parse()?,
+        predicates,
     })
 }
 
@@ -282,7 +488,10 @@ pub struct TranslationOptions {
     /// (e.g. `mycrate::mymod::myfn` is matched by
     /// `mycrate::*::myfn`), while `**` matches any subpath, empty
     /// included (e.g. `mycrate::mymod::myfn` is matched by
-    /// `**::myfn`).
+    /// `**::myfn`). Within a name, `*` matches any sequence of
+    /// characters and `?` any character, `[a-z_]` matches a character
+    /// of a class (`[!a-z]` negates it), and `{a,b}` matches `a` or
+    /// `b` (e.g. `mycrate::{ntt,poly}::encode_*`).
 
     /// By default, hax includes all items. Then, the patterns
     /// prefixed by modifiers are processed from left to right,
@@ -305,6 +514,24 @@ pub struct TranslationOptions {
     /// dependencies). This includes full struct and enums, but only
     /// the type signature of functions and trait impls (except when
     /// they contain associated types), dropping their bodies.
+
+    /// {n}{n}A pattern can be followed by a `/` and a comma-separated
+    /// list of predicates, restricting the selected items to the ones
+    /// satisfying every predicate (e.g. `+k::**/kind=fn,pub` selects
+    /// the public functions of `k`). The grammar of a clause is thus
+    /// `<MODIFIER><PATTERN>[/<PREDICATE>,...]`: since `/` cannot
+    /// appear in a pattern, globs such as `+[a-z]*::**` are never
+    /// mistaken for predicates. The predicates are
+    /// `kind=<KIND>` (`fn`, `struct`, `enum`, `impl`, `trait` or
+    /// `const`), `pub`, `attr=<PATH>` (items with an attribute
+    /// matching `<PATH>`, e.g. `attr=test`) and `trait=<PATH>`
+    /// (implementations of a trait matching `<PATH>`). A predicate
+    /// can be negated with `!` (e.g. `-k::**/!pub`).
+
+    /// {n}{n}Clauses can be read from a file with `@path/to/file`: the
+    /// file lists one clause per line, `#` starts a comment, and a
+    /// line `@other` includes the clauses of the file `other`
+    /// (relative to the directory of the including file).
     #[arg(
         value_parser = parse_inclusion_clause,
         value_delimiter = ' ',
@@ -319,6 +546,23 @@ pub struct BackendOptions<E: Extension> {
     #[command(subcommand)]
     pub backend: Backend<E>,
 
+    /// Translates to other backends as well, with their default
+    /// options (e.g. `cargo hax into --backends coq,pro-verif fstar`).
+    /// The backends can't be listed positionally (`cargo hax into
+    /// fstar coq`): the backend subcommand takes its own options, and
+    /// whatever follows it is parsed as such. The crate is built once
+    /// for all backends, unless it uses the quoting macros of
+    /// `hax-lib` or the `hax_backend_*` cfgs: then it is built once
+    /// per backend. When `--output-dir` is set, the output of each
+    /// backend goes in a subdirectory named after the backend.
+    #[arg(
+        long,
+        value_name = "BACKEND",
+        value_delimiter = ',',
+        value_parser = BACKEND_NAMES,
+    )]
+    pub backends: Vec<String>,
+
     /// Don't write anything on disk. Output everything as JSON to stdout
     /// instead.
     #[arg(long = "dry-run")]
@@ -329,15 +573,48 @@ pub struct BackendOptions<E: Extension> {
     pub verbose: u8,
 
     /// Prints statistics about how many items have been translated
-    /// successfully by the engine.
+    /// successfully by the engine, per module and per kind of
+    /// diagnostic.
     #[arg(long)]
     pub stats: bool,
 
+    /// Writes a self-contained HTML report of the translation to
+    /// `<FILE>`: statistics per module and per kind of diagnostic,
+    /// and the status and errors of each item, with links to its
+    /// source and to the generated files.
+    #[arg(long, value_name = "FILE")]
+    pub stats_html: Option<PathBuf>,
+
     /// Enables profiling for the engine: for each phase of the
     /// engine, time and memory usage are recorded and reported.
     #[arg(long)]
     pub profile: bool,
 
+    /// Collects the profiling data of the driver and of the engine
+    /// (with phase nesting, item counts and memory usage) into
+    /// `<FILE>`, in the Chrome trace event format. The resulting file
+    /// can be opened with Perfetto or speedscope.
+    #[arg(long, value_name = "FILE")]
+    pub profile_output: Option<PathBuf>,
+
+    /// Saves everything needed to reproduce this run of the engine
+    /// in the directory `<DIR>`: the `haxmeta` files and the sources
+    /// of the crates, the options, the versions of hax, the input of
+    /// the engine, what it printed on stderr and its diagnostics. The
+    /// bundle can be replayed with `cargo hax replay <DIR>`, without
+    /// the original source tree.
+    #[arg(long, value_name = "DIR")]
+    pub bug_report: Option<PathBuf>,
+
+    /// Enables incremental extraction: only the modules whose items,
+    /// or whose dependencies' signatures, changed since the last
+    /// extraction are extracted again. The items of the other
+    /// modules are given to the engine as context only, and their
+    /// files are left untouched. The cache is stored next to the
+    /// `haxmeta` file of the crate.
+    #[arg(long)]
+    pub incremental: bool,
+
     /// Enable engine debugging: dumps the AST at each phase.
     ///
     /// The value of `<DEBUG_ENGINE>` can be either:
@@ -376,13 +653,73 @@ pub struct BackendOptions<E: Extension> {
     pub cli_extension: E::BackendOptions,
 }
 
+/// The names of the backends, as subcommands of `into`.
+pub const BACKEND_NAMES: [&str; 5] = ["fstar", "coq", "ssprove", "easycrypt", "pro-verif"];
+
+/// Parses a backend given by its name, with its default options.
+#[derive(Parser)]
+struct BackendParser<E: Extension> {
+    #[command(subcommand)]
+    backend: Backend<E>,
+}
+
+impl Backend<()> {
+    /// The backend named `name` (see `BACKEND_NAMES`), with its
+    /// default options.
+    pub fn from_name(name: &str) -> Self {
+        BackendParser::<()>::parse_from(["hax", name]).backend
+    }
+}
+
+impl BackendOptions<()> {
+    /// The options of each backend to translate to: the backend given
+    /// as a subcommand first, then the ones given with `--backends`.
+    pub fn per_backend(&self) -> Vec<Self> {
+        let mut backends = vec![self.backend.clone()];
+        for name in &self.backends {
+            let backend = Backend::from_name(name);
+            if !backends
+                .iter()
+                .any(|b| b.to_string() == backend.to_string())
+            {
+                backends.push(backend);
+            }
+        }
+        let several = backends.len() > 1;
+        backends
+            .into_iter()
+            .map(|backend| {
+                let subdir = backend.to_string();
+                let in_subdir = |path: &Option<PathBuf>| {
+                    path.as_ref().map(|path| {
+                        if several {
+                            path.join(&subdir)
+                        } else {
+                            path.clone()
+                        }
+                    })
+                };
+                Self {
+                    output_dir: in_subdir(&self.output_dir),
+                    bug_report: in_subdir(&self.bug_report),
+                    backend,
+                    backends: vec![],
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive_group(Serializers)]
 #[derive(JsonSchema, Subcommand, Debug, Clone)]
 pub enum Command<E: Extension> {
     /// Translate to a backend. The translated modules will be written
     /// under the directory `<PKG>/proofs/<BACKEND>/extraction`, where
     /// `<PKG>` is the translated cargo package name and `<BACKEND>`
-    /// the name of the backend.
+    /// the name of the backend. To translate to several backends at
+    /// once, give the others with `--backends`, before the backend
+    /// subcommand: `cargo hax into --backends coq,pro-verif fstar`.
     #[clap(name = "into")]
     Backend(BackendOptions<E>),
 
@@ -395,15 +732,18 @@ pub enum Command<E: Extension> {
             default_value = "hax_frontend_export.json"
         )]
         output_file: PathOrDash,
-        /// Whether the bodies are exported as THIR, built MIR, const
-        /// MIR, or a combination. Repeat this option to extract a
-        /// combination (e.g. `-k thir -k mir-built`). Pass `--kind`
-        /// alone with no value to disable body extraction.
+        /// Whether the bodies are exported as THIR, as one of the
+        /// flavours of MIR, or as a combination. Repeat this option
+        /// to extract a combination (e.g. `-k thir -k mir-built`):
+        /// each body is then a tuple `(MIR, THIR)`. When several MIR
+        /// flavours are requested, `MIR` is the list of those, each
+        /// tagged with its name, in the order listed below. Pass
+        /// `--kind` alone with no value to disable body extraction.
         #[arg(
             value_enum,
             short,
             long = "kind",
-            num_args = 0..=3,
+            num_args = 0..=6,
             default_values_t = [ExportBodyKind::Thir]
         )]
         kind: Vec<ExportBodyKind>,
@@ -420,6 +760,117 @@ pub enum Command<E: Extension> {
         include_extra: bool,
     },
 
+    /// List the items of the crate with their inclusion status, as
+    /// computed from the inclusion clauses given with `-i` (see the
+    /// `into` subcommand). For each item, the clause that decided its
+    /// status is shown.
+    Items {
+        #[command(flatten)]
+        translation_options: TranslationOptions,
+
+        /// Outputs the list of items as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Explain why an item is part of the extraction: prints a
+    /// shortest chain of dependencies from an item selected by the
+    /// inclusion clauses given with `-i` (see the `into` subcommand)
+    /// to the items matching `<ITEM>`.
+    Why {
+        /// A Rust path, in which globs are allowed (e.g.
+        /// `mycrate::**::encode`).
+        #[arg(value_name = "ITEM", value_parser)]
+        item: Option<Namespace>,
+
+        #[command(flatten)]
+        translation_options: TranslationOptions,
+
+        /// Export the dependency graph of the items, with their
+        /// inclusion status, in the DOT format. "-" denotes stdout.
+        #[arg(long, value_name = "FILE")]
+        dot: Option<PathOrDash>,
+    },
+
+    /// Replays a bug report produced with `--bug-report <DIR>` (see
+    /// the `into` subcommand): the engine is run again on the inputs
+    /// saved in `<DIR>`. The translated files are written under
+    /// `<DIR>/<CRATE>/output`.
+    Replay {
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+    },
+
+    /// Shrinks a crate while a problem of the engine (e.g. a crash or
+    /// a bad translation) still reproduces. The problem is described
+    /// by `--diagnostic`, `--exit-code` and `--pattern`: when several
+    /// are given, all of them should hold. The crate is translated as
+    /// with the `into` subcommand, e.g. `cargo hax reduce
+    /// --diagnostic HAX0001 fstar`.
+    ///
+    /// First, items are excluded one group at a time with inclusion
+    /// clauses. Then, a copy of the crate is shrunk by removing items
+    /// and statements from its sources. The reduced crate is written
+    /// in `<DIR>`, with the inclusion clauses that still exclude
+    /// items in `<DIR>/hax-reduce.clauses` (to be used with `-i
+    /// @hax-reduce.clauses`).
+    Reduce {
+        /// The problem reproduces when the engine reports a
+        /// diagnostic with this code (e.g. `HAX0001`).
+        #[arg(long, value_name = "CODE")]
+        diagnostic: Option<String>,
+
+        /// The problem reproduces when the engine exits with this
+        /// code.
+        #[arg(long, value_name = "CODE")]
+        exit_code: Option<i32>,
+
+        /// The problem reproduces when the output of the engine (its
+        /// diagnostics, its standard error or the files it produces)
+        /// contains `<TEXT>`.
+        #[arg(long, value_name = "TEXT")]
+        pattern: Option<String>,
+
+        /// Where to write the reduced crate. Defaults to a sibling of
+        /// the directory of the package, named `<PKG_DIR>-reduced`,
+        /// so that relative path dependencies still resolve.
+        #[arg(long, value_name = "DIR")]
+        reduced_dir: Option<PathBuf>,
+
+        /// Only exclude items with inclusion clauses: the sources of
+        /// the crate are not edited.
+        #[arg(long)]
+        items_only: bool,
+
+        #[command(flatten)]
+        backend: BackendOptions<E>,
+    },
+
+    /// Sets up hax for the package of the current directory: adds
+    /// `hax-lib` to its dependencies, allows the `hax` cfgs in its
+    /// lints, and creates a starter file of inclusion clauses
+    /// (`hax-include.clauses`) and the directories
+    /// `proofs/<BACKEND>/extraction` with build files for each
+    /// backend. Existing files are left untouched.
+    Init {
+        /// The backends to set up.
+        #[arg(
+            value_name = "BACKEND",
+            value_delimiter = ',',
+            value_parser = BACKEND_NAMES,
+            default_values = ["fstar"],
+        )]
+        backends: Vec<String>,
+
+        /// Don't add `hax-lib` to the dependencies of the package.
+        #[arg(long)]
+        no_hax_lib: bool,
+
+        /// Overwrite the files that already exist.
+        #[arg(long)]
+        force: bool,
+    },
+
     #[command(flatten)]
     CliExtension(E::Command),
 }
@@ -437,7 +888,37 @@ impl<E: Extension> Command<E> {
 #[derive(JsonSchema, ValueEnum, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ExportBodyKind {
     Thir,
+    /// MIR as built from THIR
     MirBuilt,
+    /// MIR once constants are promoted
+    MirPromoted,
+    /// MIR once drops are elaborated and constants are checked
+    MirElaborated,
+    /// MIR as optimized for code generation
+    MirOptimized,
+    /// MIR used for compile-time evaluation (constants, statics and
+    /// `const fn`s only)
+    MirCtfe,
+}
+
+impl ExportBodyKind {
+    /// The flavours of MIR among the body kinds `kinds`, sorted.
+    pub fn mir_flavours(kinds: &[Self]) -> Vec<MirFlavour> {
+        let mut flavours: Vec<_> = kinds
+            .iter()
+            .filter_map(|kind| match kind {
+                Self::Thir => None,
+                Self::MirBuilt => Some(MirFlavour::Built),
+                Self::MirPromoted => Some(MirFlavour::Promoted),
+                Self::MirElaborated => Some(MirFlavour::Elaborated),
+                Self::MirOptimized => Some(MirFlavour::Optimized),
+                Self::MirCtfe => Some(MirFlavour::Ctfe),
+            })
+            .collect();
+        flavours.sort();
+        flavours.dedup();
+        flavours
+    }
 }
 
 #[derive_group(Serializers)]
@@ -456,7 +937,11 @@ pub struct ExtensibleOptions<E: Extension> {
     /// which glob patterns are allowed. The glob pattern * matches
     /// any name, the glob pattern ** matches zero, one or more
     /// names. For instance, `A::B::C::D::X` and `A::E::F::D::Y`
-    /// matches `A::**::D::*`.
+    /// matches `A::**::D::*`. Names can also contain wildcards,
+    /// character classes and alternatives (see `--include-namespaces`
+    /// of the `into` subcommand): since patterns are separated by
+    /// commas, alternatives should be separated with `|` (e.g.
+    /// `A::{b|c}_*`).
     #[arg(
         short = 'i',
         long = "inline-macro-call",
@@ -470,6 +955,12 @@ pub struct ExtensibleOptions<E: Extension> {
     )]
     pub inline_macro_calls: Vec<Namespace>,
 
+    /// Replace the body of each constant or static that calls a
+    /// `const fn` by its value, as evaluated by rustc. By default,
+    /// such calls are exported as they are written.
+    #[arg(long)]
+    pub evaluate_const_fns: bool,
+
     /// Semi-colon terminated list of arguments to pass to the
     /// `cargo build` invocation. For example, to apply this
     /// program on a package `foo`, use `-C -p foo ;`. (make sure
@@ -487,7 +978,9 @@ pub struct ExtensibleOptions<E: Extension> {
     /// Apply the command to every local package of the dependency closure. By
     /// default, the command is only applied to the primary packages (i.e. the
     /// package(s) of the current directory, or the ones selected with cargo
-    /// options like `-C -p <PKG> ;`).
+    /// options like `-C -p <PKG> ;`). When translating a package,
+    /// the items of its dependencies that were processed by hax as
+    /// well are given to the engine as a read-only context.
     #[arg(long = "deps")]
     pub deps: bool,
 
@@ -499,7 +992,10 @@ pub struct ExtensibleOptions<E: Extension> {
     pub no_custom_target_directory: bool,
 
     /// Diagnostic format. Sets `cargo`'s `--message-format` as well,
-    /// if not present.
+    /// if not present. With `human`, the progress of the engine is
+    /// shown live when stderr is a terminal; with `json`, it is
+    /// reported as `PhaseStarted`, `PhaseFinished` and
+    /// `ItemsProgress` messages.
     #[arg(long, default_value = "human")]
     pub message_format: MessageFormat,
 
@@ -516,11 +1012,49 @@ pub enum MessageFormat {
     Json,
 }
 
+impl<E: Extension> NormalizePaths for BackendOptions<E> {
+    fn normalize_paths(&mut self) {
+        self.translation_options
+            .include_namespaces
+            .normalize_paths();
+        match &mut self.backend {
+            Backend::Fstar(options) => options.interfaces.normalize_paths(),
+            Backend::ProVerif(options) => options.assume_items.normalize_paths(),
+            _ => (),
+        }
+    }
+}
+
 impl<E: Extension> NormalizePaths for Command<E> {
     fn normalize_paths(&mut self) {
         use Command::*;
         match self {
+            Backend(backend) => backend.normalize_paths(),
             JSON { output_file, .. } => output_file.normalize_paths(),
+            Items {
+                translation_options,
+                ..
+            } => translation_options.include_namespaces.normalize_paths(),
+            Why {
+                translation_options,
+                dot,
+                ..
+            } => {
+                translation_options.include_namespaces.normalize_paths();
+                if let Some(dot) = dot {
+                    dot.normalize_paths()
+                }
+            }
+            Reduce {
+                backend,
+                reduced_dir,
+                ..
+            } => {
+                backend.normalize_paths();
+                if let Some(dir) = reduced_dir {
+                    dir.normalize_paths()
+                }
+            }
             _ => (),
         }
     }
@@ -536,8 +1070,139 @@ impl From<Options> for hax_frontend_exporter_options::Options {
     fn from(opts: Options) -> hax_frontend_exporter_options::Options {
         hax_frontend_exporter_options::Options {
             inline_macro_calls: opts.inline_macro_calls,
+            evaluate_const_fns: opts.evaluate_const_fns,
+            mir_flavours: ExportBodyKind::mir_flavours(&opts.command.body_kinds()),
         }
     }
 }
 
 pub const ENV_VAR_OPTIONS_FRONTEND: &str = "DRIVER_HAX_FRONTEND_OPTS";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(clause: &str) -> InclusionClause {
+        parse_inclusion_clause(clause).unwrap()
+    }
+
+    #[test]
+    fn predicates() {
+        let clause = parse("+k::**/kind=fn,!pub");
+        assert!(matches!(
+            clause.kind,
+            InclusionKind::Included(DepsKind::Transitive)
+        ));
+        assert_eq!(clause.namespace.to_string(), "k::**");
+        assert!(matches!(
+            &clause.predicates[..],
+            [
+                ItemPredicate {
+                    negated: false,
+                    selector: ItemSelector::Kind(ItemKindSelector::Fn)
+                },
+                ItemPredicate {
+                    negated: true,
+                    selector: ItemSelector::Public
+                },
+            ]
+        ));
+        assert_eq!(clause.to_string(), "+k::**/kind=fn,!pub");
+    }
+
+    #[test]
+    fn predicates_with_globs() {
+        let clause = parse("-k::**/trait=core::ops::{Add,Sub},attr=[ab]*");
+        assert!(matches!(clause.kind, InclusionKind::Excluded));
+        assert_eq!(clause.namespace.to_string(), "k::**");
+        let [ItemPredicate {
+            selector: ItemSelector::ImplOf(trait_),
+            ..
+        }, ItemPredicate {
+            selector: ItemSelector::Attribute(attr),
+            ..
+        }] = &clause.predicates[..]
+        else {
+            panic!("unexpected predicates: {:?}", clause.predicates)
+        };
+        assert!(trait_.matches(&["core".into(), "ops".into(), "Sub".into()]));
+        assert!(!trait_.matches(&["core".into(), "ops".into(), "Mul".into()]));
+        assert!(attr.matches(&["bab".into()]));
+        assert!(!attr.matches(&["cab".into()]));
+    }
+
+    #[test]
+    fn globs_are_not_predicates() {
+        let clause = parse("+[a-z]*::**");
+        assert!(clause.predicates.is_empty());
+        assert_eq!(clause.namespace.to_string(), "[a-z]*::**");
+        let clause = parse("-[!t]est_*::**");
+        assert!(matches!(clause.kind, InclusionKind::Excluded));
+        assert!(clause.predicates.is_empty());
+        assert!(clause.namespace.matches(&["best_x".into(), "f".into()]));
+        assert!(!clause.namespace.matches(&["test_x".into(), "f".into()]));
+        let clause = parse("+{ntt,poly}::f/pub");
+        assert_eq!(clause.namespace.to_string(), "{ntt,poly}::f");
+        assert_eq!(clause.predicates.len(), 1);
+    }
+
+    /// Writes `files` (pairs of relative paths and contents) in a
+    /// fresh temporary directory, which is returned.
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hax-clauses-{name}-{}", std::process::id()));
+        for (path, contents) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn clause_files() {
+        let dir = write_files(
+            "nested",
+            &[
+                ("a/clauses", "# comment\n-**\n\n+k::f # trailing\n@b/more\n"),
+                ("a/b/more", "@../../c/last\n+:k::g\n"),
+                ("c/last", "+!k::h/pub\n"),
+            ],
+        );
+        let mut clauses = vec![parse(&format!("@{}", dir.join("a/clauses").display()))];
+        clauses.normalize_paths();
+        let clauses: Vec<_> = clauses.iter().map(InclusionClause::to_string).collect();
+        assert_eq!(clauses, ["-**", "+k::f", "+!k::h/pub", "+:k::g"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn clause_file_errors() {
+        let dir = write_files(
+            "errors",
+            &[
+                ("cycle", "+k::f\n@sub/cycle\n"),
+                ("sub/cycle", "@../cycle\n"),
+                ("invalid", "+k::f\n*k::g\n"),
+            ],
+        );
+        let err = parse_inclusion_clause(&format!("@{}", dir.join("cycle").display()))
+            .unwrap_err()
+            .to_string();
+        assert!(err.ends_with("cycle` includes itself"), "{err}");
+        let err = parse_inclusion_clause(&format!("@{}", dir.join("invalid").display()))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid:2: "), "{err}");
+        assert!(parse_inclusion_clause(&format!("@{}", dir.join("missing").display())).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_clauses() {
+        assert!(parse_inclusion_clause("").is_err());
+        assert!(parse_inclusion_clause("*k::f").is_err());
+        assert!(parse_inclusion_clause("+k::f/kind=mod").is_err());
+        assert!(parse_inclusion_clause("+k::f/public").is_err());
+        assert!(parse_inclusion_clause("+k::f/pub/pub").is_err());
+    }
+}
@@ -26,7 +26,47 @@ pub enum HaxMessage {
     ProfilingData(crate::engine_api::ProfilingData) = 5,
     Stats {
         errors_per_item: Vec<(hax_frontend_exporter::DefId, usize)>,
+        per_module: Vec<ModuleStats>,
+        per_kind: Vec<KindStats>,
     } = 6,
+    UnchangedModules {
+        unchanged: usize,
+        total: usize,
+    } = 7,
+    PhaseStarted {
+        phase: String,
+        quantity: u32,
+    } = 8,
+    PhaseFinished {
+        phase: String,
+        errored: bool,
+    } = 9,
+    ItemsProgress {
+        processed: u32,
+        total: u32,
+    } = 10,
+}
+
+/// Translation statistics of the items of a module.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone, JsonSchema)]
+pub struct ModuleStats {
+    pub module: String,
+    /// Number of items processed by the engine.
+    pub items: usize,
+    /// Number of items translated without errors.
+    pub successful: usize,
+}
+
+/// Number of diagnostics of a given kind.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone, JsonSchema)]
+pub struct KindStats {
+    /// The code of the kind (e.g. `HAX0001`).
+    pub code: String,
+    /// The name of the kind (e.g. `Unimplemented`).
+    pub name: String,
+    pub count: usize,
 }
 
 impl HaxMessage {
@@ -70,6 +70,8 @@ impl std::fmt::Display for Diagnostics {
 
             Kind::FStarParseError { fstar_snippet, details: _ } => write!(f, "The following code snippet could not be parsed as valid F*:\n```\n{fstar_snippet}\n```"),
 
+            Kind::FrontendExportError { details } => write!(f, "The frontend failed to export this item: {details}."),
+
             _ => write!(f, "{:?}", self.kind),
         }
     }
@@ -144,6 +146,11 @@ pub enum Kind {
         fstar_snippet: String,
         details: String,
     } = 13,
+
+    /// The frontend failed to export an item
+    FrontendExportError {
+        details: String,
+    } = 14,
 }
 
 impl Kind {
@@ -17,16 +17,15 @@ fn compute_offset(src: &str, line: usize, col: usize) -> usize {
 }
 
 impl ReportCtx {
-    /// Read the contents of a file. The result is cached.
-    fn file_contents<'a>(&'a mut self, path: PathBuf) -> Rc<String> {
-        self.files
-            .entry(path.clone())
-            .or_insert_with(|| {
-                let s =
-                    std::fs::read_to_string(&path).expect(&format!("Unable to read file {path:?}"));
-                Rc::new(s)
-            })
-            .clone()
+    /// Read the contents of a file. The result is cached. Returns
+    /// `None` when the file cannot be read (e.g. when replaying a bug
+    /// report without the sources of the crate).
+    fn file_contents<'a>(&'a mut self, path: PathBuf) -> Option<Rc<String>> {
+        if !self.files.contains_key(&path) {
+            let s = std::fs::read_to_string(&path).ok()?;
+            self.files.insert(path.clone(), Rc::new(s));
+        }
+        self.files.get(&path).cloned()
     }
 }
 
@@ -49,7 +48,10 @@ impl Diagnostics {
                     if path.is_relative() {
                         path = working_dir.join(&path);
                     };
-                    report_ctx.file_contents(path)
+                    let Some(source) = report_ctx.file_contents(path) else {
+                        continue;
+                    };
+                    source
                 };
                 let start = compute_offset(&source, span.lo.line, span.lo.col);
                 let end = compute_offset(&source, span.hi.line, span.hi.col);
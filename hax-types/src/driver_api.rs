@@ -8,6 +8,11 @@ pub struct EmitHaxMetaMessage {
     pub working_dir: PathBuf,
     pub manifest_dir: PathBuf,
     pub path: PathBuf,
+    /// May the items of the crate depend on the backend, i.e. does the
+    /// crate use `hax-lib` or the `hax_backend_*` cfgs?
+    pub uses_backend_cfgs: bool,
+    /// Profiling data for the different steps of the driver.
+    pub profiling: Vec<crate::engine_api::ProfilingData>,
 }
 #[derive_group(Serializers)]
 #[derive(Debug, Clone)]
@@ -28,6 +33,7 @@ pub struct HaxMeta<Body: hax_frontend_exporter::IsBody> {
     )>,
     pub def_ids: Vec<hax_frontend_exporter::DefId>,
     pub comments: Vec<(hax_frontend_exporter::Span, String)>,
+    pub macro_expansions: Vec<hax_frontend_exporter::MacroExpansion>,
     pub hax_version: String,
 }
 
@@ -48,11 +54,24 @@ where
     }
     #[tracing::instrument(level = "trace", skip(reader))]
     pub fn read(reader: impl std::io::Read) -> (Self, id_table::Table) {
+        Self::read_with_id_offset(reader, 0)
+    }
+    /// Reads a `haxmeta` file, shifting all of its table IDs by
+    /// `id_offset`. Use `id_table::Table::next_id` to compute an
+    /// offset that makes the resulting table disjoint from an
+    /// already loaded one.
+    #[tracing::instrument(level = "trace", skip(reader))]
+    pub fn read_with_id_offset(
+        reader: impl std::io::Read,
+        id_offset: u32,
+    ) -> (Self, id_table::Table) {
         let reader = zstd::stream::read::Decoder::new(reader).unwrap();
         let reader = std::io::BufReader::new(reader);
-        let haxmeta = id_table::WithTable::<HaxMeta<Body>>::destruct(
-            serde_brief::from_reader(reader).unwrap(),
-        );
+        let haxmeta = id_table::WithTable::<HaxMeta<Body>>::with_id_offset(id_offset, || {
+            id_table::WithTable::<HaxMeta<Body>>::destruct(
+                serde_brief::from_reader(reader).unwrap(),
+            )
+        });
         if haxmeta.0.hax_version != crate::HAX_VERSION {
             let version = haxmeta.0.hax_version;
             let expected = crate::HAX_VERSION;
@@ -62,38 +81,59 @@ where
     }
 }
 
+/// Runs `$body` with `$t` bound to the type of bodies that matches
+/// the list of body kinds `$kind` (see `--kind` of `cargo hax json`):
+/// `(MIR, THIR)`, where absent components are dropped (`()` if no
+/// kind is requested at all). `MIR` is a `MirBody` if a single MIR
+/// flavour is requested, and otherwise the list of the requested MIR
+/// flavours of a body (see `Options::mir_flavours`).
 #[macro_export]
 macro_rules! with_kind_type {
     ($kind:expr, <$t:ident>|| $body:expr) => {{
-        mod from {
-            pub use hax_types::cli_options::ExportBodyKind::{MirBuilt as MB, Thir as T};
-        }
+        use ::hax_types::cli_options::{ExportBodyKind, MirFlavour};
         mod to {
-            pub type T = hax_frontend_exporter::ThirBody;
-            pub type MB = hax_frontend_exporter::MirBody<hax_frontend_exporter::mir_kinds::Built>;
+            use hax_frontend_exporter::{mir_kinds, MirBody};
+            pub type Thir = hax_frontend_exporter::ThirBody;
+            pub type MirBuilt = MirBody<mir_kinds::Built>;
+            pub type MirPromoted = MirBody<mir_kinds::Promoted>;
+            pub type MirElaborated = MirBody<mir_kinds::Elaborated>;
+            pub type MirOptimized = MirBody<mir_kinds::Optimized>;
+            pub type MirCtfe = MirBody<mir_kinds::CTFE>;
+            pub type Mir = Vec<hax_frontend_exporter::AnyMirBody>;
         }
-        let mut kind: Vec<::hax_types::cli_options::ExportBodyKind> = $kind;
-        kind.sort();
-        kind.dedup();
-        match kind.as_slice() {
-            [from::MB] => {
-                type $t = to::MB;
-                $body
-            }
-            [from::T] => {
-                type $t = to::T;
-                $body
-            }
-            [from::T, from::MB] => {
-                type $t = (to::MB, to::T);
+        let kind: Vec<ExportBodyKind> = $kind;
+        let thir = kind.contains(&ExportBodyKind::Thir);
+        match ExportBodyKind::mir_flavours(&kind).as_slice() {
+            [] if thir => {
+                type $t = to::Thir;
                 $body
             }
             [] => {
                 type $t = ();
                 $body
             }
-            _ => panic!("Unsupported kind {:#?}", kind),
+            [MirFlavour::Built] => $crate::with_kind_type!(@thir thir, to::MirBuilt, <$t>|| $body),
+            [MirFlavour::Promoted] => {
+                $crate::with_kind_type!(@thir thir, to::MirPromoted, <$t>|| $body)
+            }
+            [MirFlavour::Elaborated] => {
+                $crate::with_kind_type!(@thir thir, to::MirElaborated, <$t>|| $body)
+            }
+            [MirFlavour::Optimized] => {
+                $crate::with_kind_type!(@thir thir, to::MirOptimized, <$t>|| $body)
+            }
+            [MirFlavour::Ctfe] => $crate::with_kind_type!(@thir thir, to::MirCtfe, <$t>|| $body),
+            _ => $crate::with_kind_type!(@thir thir, to::Mir, <$t>|| $body),
         }
     }};
+    (@thir $thir:ident, $mir:ty, <$t:ident>|| $body:expr) => {
+        if $thir {
+            type $t = ($mir, to::Thir);
+            $body
+        } else {
+            type $t = $mir;
+            $body
+        }
+    };
 }
 pub use with_kind_type;
@@ -13,6 +13,24 @@ pub struct EngineOptions {
         hax_frontend_exporter::DefId,
         hax_frontend_exporter::ImplInfos,
     )>,
+    /// The dependencies of the crate being translated for which a
+    /// `haxmeta` file was found.
+    pub dependencies: Vec<DependencyContext>,
+}
+
+/// The items of a dependency of the crate being translated. Those
+/// items are not translated: the engine uses them as a read-only
+/// context (e.g. to resolve attributes or `impl` informations of
+/// items defined in sibling crates).
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone)]
+pub struct DependencyContext {
+    pub crate_name: String,
+    pub items: Vec<hax_frontend_exporter::Item<ThirBody>>,
+    pub impl_infos: Vec<(
+        hax_frontend_exporter::DefId,
+        hax_frontend_exporter::ImplInfos,
+    )>,
 }
 
 #[derive_group(Serializers)]
@@ -64,6 +82,8 @@ pub struct Output {
 pub struct ProfilingData {
     /// What context are we profiling?
     pub context: String,
+    /// When did this start? (in nanoseconds since the Unix epoch)
+    pub start_ns: u64,
     /// How long this took?
     pub time_ns: u64,
     /// How much memory this took? This is using OCaml's
@@ -78,6 +98,32 @@ pub struct ProfilingData {
     pub errored: bool,
 }
 
+impl ProfilingData {
+    /// Profiling data for an action that started at `start` and that
+    /// just finished. This is used outside of the engine, where
+    /// memory usage is not measured.
+    pub fn finished(
+        context: impl Into<String>,
+        start: std::time::SystemTime,
+        quantity: u32,
+    ) -> Self {
+        let since_epoch = |time: std::time::SystemTime| {
+            time.duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64
+        };
+        let start_ns = since_epoch(start);
+        Self {
+            context: context.into(),
+            start_ns,
+            time_ns: since_epoch(std::time::SystemTime::now()).saturating_sub(start_ns),
+            memory: 0,
+            quantity,
+            errored: false,
+        }
+    }
+}
+
 pub mod protocol {
     use super::*;
 
@@ -92,6 +138,24 @@ pub mod protocol {
         ProfilingData(ProfilingData),
         /// Declares a list of items that will be processed by the engine
         ItemProcessed(Vec<hax_frontend_exporter::DefId>),
+        /// The engine started a phase (e.g. the import of THIR items,
+        /// or a phase of the backend pipeline) on `quantity` items
+        PhaseStarted {
+            phase: String,
+            quantity: u32,
+        },
+        /// The engine finished the phase `phase`
+        PhaseFinished {
+            phase: String,
+            errored: bool,
+        },
+        /// `processed` out of `total` items of the current phase were
+        /// processed. This is only sent by phases that process items
+        /// one by one, at most once per percent.
+        ItemsProgress {
+            processed: u32,
+            total: u32,
+        },
         Exit,
         Ping,
     }
@@ -116,4 +180,5 @@ pub struct WithDefIds<Body: hax_frontend_exporter::IsBody> {
     )>,
     pub items: Vec<hax_frontend_exporter::Item<Body>>,
     pub comments: Vec<(hax_frontend_exporter::Span, String)>,
+    pub macro_expansions: Vec<hax_frontend_exporter::MacroExpansion>,
 }
@@ -6,13 +6,29 @@ use std::process::{Command, Stdio};
 
 #[derive(Clone, Debug, serde::Serialize)]
 pub enum TestKind {
-    Translate { backend: String },
+    Translate {
+        backend: String,
+    },
+    /// Lists the items with their inclusion status (`cargo hax
+    /// items`), e.g. to test inclusion clauses without running the
+    /// engine. `name` tells apart the tests of a same crate.
+    Items {
+        name: String,
+    },
+    /// Exports the crate as JSON (`cargo hax json`) twice, and checks
+    /// that both exports are byte-identical. `name` tells apart the
+    /// tests of a same crate.
+    Json {
+        name: String,
+    },
 }
 
 impl TestKind {
     fn as_name(&self) -> String {
         (match self {
             TestKind::Translate { backend } => ["into".to_string(), backend.clone()],
+            TestKind::Items { name } => ["items".to_string(), name.clone()],
+            TestKind::Json { name } => ["json".to_string(), name.clone()],
         })
         .join("-")
     }
@@ -44,7 +60,22 @@ pub struct TestSpec {
     pub positive: bool,
     pub snapshot: TestSnapshot,
     pub include_flag: Option<String>,
+    /// Extra arguments given to the backend, or to `cargo hax json`
     pub backend_options: Option<Vec<String>>,
+    /// Extra arguments given to `cargo hax` itself, before the
+    /// subcommand, e.g. `["--evaluate-const-fns"]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hax_options: Option<Vec<String>>,
+    /// For JSON exports, the paths (globs are allowed) of the items
+    /// to snapshot, e.g. `["mycrate::f"]`. Every item is kept if this
+    /// is unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<String>>,
+    /// For JSON exports, the object keys whose values are kept in the
+    /// snapshot (they are searched for in the selected items), e.g.
+    /// `["Assert"]`. Whole items are kept if this is unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keys: Option<Vec<String>>,
 }
 
 impl From<Value> for TestSpec {
@@ -69,6 +100,9 @@ impl From<Value> for TestSpec {
             issue_id: o["positive"].as_u64(),
             include_flag: o["include-flag"].as_str().map(|s| s.into()),
             backend_options: serde_json::from_value(o["backend-options"].clone()).unwrap(),
+            hax_options: serde_json::from_value(o["hax-options"].clone()).unwrap(),
+            items: serde_json::from_value(o["items"].clone()).unwrap(),
+            keys: serde_json::from_value(o["keys"].clone()).unwrap(),
             snapshot: as_opt_bool(snapshot, true)
                 .map(|b| TestSnapshot {
                     stderr: b,
@@ -109,6 +143,12 @@ pub struct Test {
 
 impl Test {
     fn as_args(&self) -> Vec<String> {
+        let mut args = self.spec.hax_options.clone().unwrap_or_default();
+        args.extend(self.subcommand_args());
+        args
+    }
+
+    fn subcommand_args(&self) -> Vec<String> {
         match &self.kind {
             TestKind::Translate { backend } => {
                 let mut args = vec![];
@@ -124,8 +164,86 @@ impl Test {
                 }
                 args
             }
+            TestKind::Items { .. } => {
+                let mut args = vec!["items".to_string()];
+                if let Some(i) = self.spec.include_flag.as_ref() {
+                    args.push("-i".to_string());
+                    args.push(i.to_string());
+                }
+                args
+            }
+            TestKind::Json { .. } => {
+                let mut args = vec!["json".to_string(), "-o".to_string(), "-".to_string()];
+                if let Some(options) = &self.spec.backend_options {
+                    args.extend_from_slice(options.as_slice());
+                }
+                args
+            }
         }
     }
+
+    /// Keeps the items of a JSON export whose paths match
+    /// `self.spec.items`, and pretty prints them (or the values of
+    /// their keys listed in `self.spec.keys`).
+    fn select_items(&self, export: &str) -> Option<String> {
+        let items: Vec<Value> = serde_json::from_str(export).ok()?;
+        let patterns: Vec<hax_types::cli_options::Namespace> = self
+            .spec
+            .items
+            .iter()
+            .flatten()
+            .map(|pattern| pattern.parse().unwrap())
+            .collect();
+        let path_of = |item: &Value| -> Vec<String> {
+            let def_id = &item["owner_id"]["contents"]["value"];
+            std::iter::once(def_id["krate"].as_str().unwrap_or_default().to_string())
+                .chain(
+                    def_id["path"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|chunk| {
+                            match &chunk["data"] {
+                                Value::Object(data) => data.values().next().and_then(Value::as_str),
+                                data => data.as_str(),
+                            }
+                            .unwrap_or_default()
+                            .to_string()
+                        }),
+                )
+                .collect()
+        };
+        let items: Vec<Value> = items
+            .into_iter()
+            .filter(|item| {
+                self.spec.items.is_none()
+                    || patterns
+                        .iter()
+                        .any(|pattern| pattern.matches(&path_of(item)))
+            })
+            .collect();
+        let Some(keys) = &self.spec.keys else {
+            return serde_json::to_string_pretty(&items).ok();
+        };
+        fn collect(value: &Value, keys: &[String], found: &mut Vec<Value>) {
+            match value {
+                Value::Object(object) => {
+                    for (key, value) in object {
+                        if keys.contains(key) {
+                            found.push(serde_json::json!({ key: value }));
+                        } else {
+                            collect(value, keys, found)
+                        }
+                    }
+                }
+                Value::Array(values) => values.iter().for_each(|v| collect(v, keys, found)),
+                _ => (),
+            }
+        }
+        let mut found = vec![];
+        collect(&Value::Array(items), keys, &mut found);
+        serde_json::to_string_pretty(&found).ok()
+    }
 }
 
 impl std::fmt::Display for Test {
@@ -141,17 +259,35 @@ impl std::fmt::Display for Test {
 impl Test {
     fn into_runner(self, workspace: String) -> Result<(), libtest_mimic::Failed> {
         // 1. cook a command
-        let mut cmd = Command::hax(&["-C"]);
-        cmd.arg("--manifest-path").arg(self.info.manifest.clone());
-        cmd.arg(";");
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-        cmd.args(self.as_args());
+        let command = |hax_options: &[&str]| {
+            let mut cmd = Command::hax(&["-C"]);
+            cmd.arg("--manifest-path").arg(self.info.manifest.clone());
+            cmd.arg(";");
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            cmd.args(hax_options);
+            cmd.args(self.as_args());
+            cmd
+        };
+        let mut cmd = command(&[]);
 
         // 2. execute it (twice, idea of @franziskuskiefer, so that
         // the messages related to building dependencies are not
-        // included in the second one)
-        let _ = cmd.output().unwrap();
+        // included in the second one). JSON exports are checked to
+        // be reproducible: the second run exports the crate again,
+        // without the cache of cargo.
+        let first = cmd.output().unwrap();
+        let mut cmd = if matches!(self.kind, TestKind::Json { .. }) {
+            command(&["--disable-cargo-cache"])
+        } else {
+            cmd
+        };
         let out = cmd.output().unwrap();
+        if matches!(self.kind, TestKind::Json { .. }) && first.stdout != out.stdout {
+            Err(format!(
+                "Exporting twice gave different outputs.\nThe command was: {:?}",
+                cmd
+            ))?
+        }
 
         let command_successful = out.status.success();
         let cleanup = |s: String| {
@@ -195,7 +331,12 @@ impl Test {
                             "files": Value::Object(out.files.into_iter().map(|file| (file.path, Value::String(file.contents))).collect())
                         })
                     })
-                    .unwrap_or_else(|_| Value::String(cleanup(sout.clone()))),
+                    .ok()
+                    .or_else(|| match self.kind {
+                        TestKind::Json { .. } => self.select_items(&sout).map(Value::String),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| Value::String(cleanup(sout.clone()))),
             );
         }
 
@@ -283,6 +424,8 @@ fn parse_hax_tests_metadata(info: TestInfo, metadata: &Value) -> Vec<Test> {
             info: info.clone(),
             kind: match a.as_str() {
                 "into" => TestKind::Translate { backend: b },
+                "items" => TestKind::Items { name: b },
+                "json" => TestKind::Json { name: b },
                 _ => panic!(
                     "unexpected metadata [hax-tests.{}.{}] for package {:#?}",
                     a, b, info
@@ -0,0 +1,19 @@
+#![allow(dead_code)]
+
+#[repr(u8)]
+enum Small {
+    A = 1,
+    B = 4,
+}
+
+#[repr(C, i64)]
+enum Tagged {
+    X(u8),
+    Y { y: u16 },
+}
+
+#[repr(align(8))]
+enum Aligned {
+    P,
+    Q,
+}
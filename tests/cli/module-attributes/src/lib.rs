@@ -0,0 +1,31 @@
+#![allow(dead_code)]
+
+pub fn f() {}
+
+/// Excluded with an inner attribute, except for `kept`
+mod excluded {
+    #![cfg_attr(hax, hax_lib::exclude)]
+
+    pub fn g() {}
+
+    #[hax_lib::include]
+    pub fn kept() {}
+
+    mod nested {
+        pub fn h() {}
+    }
+}
+
+/// Excluded with an attribute on the module declaration
+#[cfg_attr(hax, hax_lib::exclude)]
+mod outlined;
+
+/// Included with an inner attribute, except for `dropped`
+mod included {
+    #![cfg_attr(hax, hax_lib::include)]
+
+    pub fn i() {}
+
+    #[hax_lib::exclude]
+    pub fn dropped() {}
+}
@@ -0,0 +1,3 @@
+pub fn j() {}
+
+pub struct S;
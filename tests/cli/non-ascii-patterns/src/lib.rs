@@ -0,0 +1,11 @@
+//! The frontend (`cargo hax items`) and the engine (`cargo hax into`)
+//! select the same items: in both, the characters of a pattern are
+//! Unicode scalar values.
+#![allow(dead_code)]
+
+fn café() {}
+fn cafe() {}
+fn naïve() {}
+fn naive() {}
+fn 日本() {}
+fn 本日() {}
@@ -0,0 +1,45 @@
+#![allow(dead_code)]
+
+pub fn pub_f() {}
+fn private_f() {}
+
+pub struct PubS(u8);
+struct PrivateS;
+
+#[inline]
+pub fn inlined_f() {}
+#[cold]
+fn cold_f() {}
+#[must_use]
+fn must_use_f() -> u8 {
+    0
+}
+
+impl core::ops::Add for PubS {
+    type Output = PubS;
+    fn add(self, rhs: PubS) -> PubS {
+        PubS(self.0 + rhs.0)
+    }
+}
+impl core::ops::Sub for PubS {
+    type Output = PubS;
+    fn sub(self, rhs: PubS) -> PubS {
+        PubS(self.0 - rhs.0)
+    }
+}
+impl core::ops::Mul for PubS {
+    type Output = PubS;
+    fn mul(self, rhs: PubS) -> PubS {
+        PubS(self.0 * rhs.0)
+    }
+}
+
+pub mod math {
+    pub fn add_1() {}
+    pub fn test_1() {}
+    fn sub_22() {}
+}
+
+mod zeta {
+    pub fn add_1() {}
+}
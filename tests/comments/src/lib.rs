@@ -0,0 +1,10 @@
+pub fn halve_even(x: u8) -> u8 {
+    // The lowest bit is dropped
+    let y = x / 2;
+    match x % 2 {
+        // Even numbers are halved
+        0 => y,
+        /* Odd numbers are kept */
+        _ => x,
+    }
+}
@@ -0,0 +1,18 @@
+/// Building a tuple struct calls its constructor, a `const fn` which
+/// needs no evaluation: `ORIGIN` is exported as written
+pub struct Point(pub u8, pub u8);
+pub const ORIGIN: Point = Point(0, 0);
+
+const fn bytes(x: u32) -> [u8; 4] {
+    x.to_be_bytes()
+}
+
+/// A byte array computed by a `const fn`, exported as a byte string
+pub const TABLE: [u8; 4] = bytes(0x01020304);
+
+const fn rotate(x: u32, n: u32) -> u32 {
+    x.rotate_left(n)
+}
+
+/// Round constants computed by a `const fn`
+pub const ROUNDS: [u32; 3] = [rotate(1, 1), rotate(1, 2), rotate(1, 3)];
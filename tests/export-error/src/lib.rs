@@ -0,0 +1,16 @@
+//! The exporter fails on `C`: its evaluated value is a scalar of
+//! a struct with a field. `C` is exported as an `ExportError` item,
+//! while the other items are exported as usual. The error is reported
+//! as a warning: the crate still compiles.
+
+pub struct Wrapper(u32);
+
+const fn wrap(x: u32) -> Wrapper {
+    Wrapper(x)
+}
+
+pub const C: Wrapper = wrap(3);
+
+pub fn exported() -> u32 {
+    42
+}
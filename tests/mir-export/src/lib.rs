@@ -0,0 +1,27 @@
+/// Asserts for a bounds check, an overflow and a division by zero
+pub fn checks(a: [u32; 4], i: usize, x: u32) -> u32 {
+    a[i] + a[0] / x
+}
+
+fn id(n: usize) -> usize {
+    n
+}
+
+/// Inline assembly, and a call that unwinds to a cleanup block
+/// dropping `v`
+pub fn asm_and_unwind(v: Vec<u8>) -> usize {
+    unsafe { core::arch::asm!("/* {0} */", in(reg) v.len()) };
+    id(v.len())
+}
+
+const fn double(n: usize) -> usize {
+    2 * n
+}
+
+/// Evaluating `N` requires the MIR of `double` for CTFE
+const N: usize = double(4);
+
+/// `id` is inlined in the optimized MIR of `inlined`
+pub fn inlined(n: usize) -> usize {
+    id(n) + N
+}
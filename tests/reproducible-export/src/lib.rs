@@ -0,0 +1,28 @@
+mod shapes;
+pub use shapes::*;
+
+// Sums the areas of some shapes
+pub fn total<S: Shape>(shapes: &[S]) -> u64 {
+    shapes.iter().map(|s| s.area()).fold(0, |acc, a| acc + a)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    A(u8),
+    B { x: u16 },
+    C,
+}
+
+pub fn kind_value(k: Kind) -> u16 {
+    match k {
+        Kind::A(x) => x as u16,
+        Kind::B { x } => x,
+        Kind::C => 0,
+    }
+}
+
+pub const TABLE: [u32; 3] = [1, 2, 3];
+
+pub fn values() -> Vec<Option<(u8, i32)>> {
+    vec![Some((1, -1)), None]
+}
@@ -0,0 +1,37 @@
+pub trait Shape: Clone {
+    type Unit;
+    fn area(&self) -> u64;
+    fn scale<T: Into<u64> + Copy>(&self, k: T) -> u64 {
+        self.area() * k.into()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub w: u64,
+    pub h: u64,
+}
+
+impl Shape for Rect {
+    type Unit = u8;
+    fn area(&self) -> u64 {
+        self.w * self.h
+    }
+}
+
+pub struct Wrap<T>(pub T);
+
+impl<T: Shape> Wrap<T>
+where
+    T::Unit: Copy,
+{
+    pub fn area(&self) -> u64 {
+        self.0.area()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Wrap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "Wrap({:?})", self.0)
+    }
+}